@@ -1,5 +1,6 @@
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use futures_util::{ready, TryFuture};
@@ -32,6 +33,35 @@ where
     }
 }
 
+// Calls a function with a reference to some state, followed by the whole
+// extracted tuple, for `Filter::and_then_with`.
+#[allow(missing_debug_implementations)]
+pub struct WithState<S, F> {
+    pub(super) state: Arc<S>,
+    pub(super) callback: F,
+}
+
+impl<S, F: Clone> Clone for WithState<S, F> {
+    fn clone(&self) -> Self {
+        WithState {
+            state: self.state.clone(),
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+impl<S, F, Args, R> Func<Args> for WithState<S, F>
+where
+    F: Fn(&S, Args) -> R,
+{
+    type Output = R;
+
+    #[inline]
+    fn call(&self, args: Args) -> Self::Output {
+        (self.callback)(&self.state, args)
+    }
+}
+
 #[allow(missing_debug_implementations)]
 #[pin_project]
 pub struct AndThenFuture<T, F>
@@ -0,0 +1,95 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::ready;
+use tokio::task::JoinHandle;
+
+use super::Func;
+use crate::reject::{self, Rejection};
+
+// Calls a function on the blocking thread pool, for `Filter::map_blocking`.
+#[derive(Clone, Copy, Debug)]
+pub struct Blocking<F> {
+    pub(super) callback: F,
+}
+
+impl<F, Args> Func<Args> for Blocking<F>
+where
+    F: Func<Args> + Clone + Send + 'static,
+    F::Output: Send + 'static,
+    Args: Send + 'static,
+{
+    type Output = BlockingFuture<F::Output>;
+
+    fn call(&self, args: Args) -> Self::Output {
+        let callback = self.callback.clone();
+        BlockingFuture {
+            handle: tokio::task::spawn_blocking(move || callback.call(args)),
+        }
+    }
+}
+
+// Like `Blocking`, with a function returning a `Result`, for
+// `Filter::and_then_blocking`.
+#[derive(Clone, Copy, Debug)]
+pub struct TryBlocking<F> {
+    pub(super) callback: F,
+}
+
+impl<F, Args, T, E> Func<Args> for TryBlocking<F>
+where
+    F: Func<Args, Output = Result<T, E>> + Clone + Send + 'static,
+    T: Send + 'static,
+    E: Into<Rejection> + Send + 'static,
+    Args: Send + 'static,
+{
+    type Output = TryBlockingFuture<T, E>;
+
+    fn call(&self, args: Args) -> Self::Output {
+        let callback = self.callback.clone();
+        TryBlockingFuture {
+            handle: tokio::task::spawn_blocking(move || callback.call(args)),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct BlockingFuture<T> {
+    handle: JoinHandle<T>,
+}
+
+impl<T> Future for BlockingFuture<T> {
+    type Output = Result<T, Rejection>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = ready!(Pin::new(&mut self.handle).poll(cx));
+        Poll::Ready(result.map_err(join_error))
+    }
+}
+
+#[allow(missing_debug_implementations)]
+pub struct TryBlockingFuture<T, E> {
+    handle: JoinHandle<Result<T, E>>,
+}
+
+impl<T, E: Into<Rejection>> Future for TryBlockingFuture<T, E> {
+    type Output = Result<T, Rejection>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let result = ready!(Pin::new(&mut self.handle).poll(cx));
+        Poll::Ready(match result {
+            Ok(result) => result.map_err(Into::into),
+            Err(err) => Err(join_error(err)),
+        })
+    }
+}
+
+fn join_error(err: tokio::task::JoinError) -> Rejection {
+    if err.is_panic() {
+        tracing::error!("blocking handler panicked");
+    } else {
+        tracing::debug!("blocking handler cancelled: {}", err);
+    }
+    reject::blocking_handler_failed()
+}
@@ -3,10 +3,11 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 
-use futures_util::TryFutureExt;
+use futures_util::{future, TryFutureExt};
 
 use super::{Filter, FilterBase, Internal, Tuple};
-use crate::reject::Rejection;
+use crate::reject::{self, Rejection};
+use crate::reply::Reply;
 
 /// A type representing a boxed [`Filter`](crate::Filter) trait object.
 ///
@@ -66,9 +67,47 @@ impl<T: Tuple> fmt::Debug for BoxedFilter<T> {
     }
 }
 
+/// A boxed [`Filter`](crate::Filter) of any reply, with its type erased.
+///
+/// Since every `DynFilter` has the same type, they can be built at runtime,
+/// kept in collections, and combined with [`DynFilter::one_of`], such as for
+/// a route table that plugins register their routes into.
+///
+/// To create one, call `Filter::boxed_clone` on a filter extracting a reply.
+///
+/// # Examples
+///
+/// ```
+/// use warp::{Filter, filters::DynFilter};
+///
+/// let mut routes: Vec<DynFilter> = Vec::new();
+/// routes.push(warp::path("hello").map(|| "hello").boxed_clone());
+/// routes.push(warp::path("status").map(warp::reply).boxed_clone());
+///
+/// let api = DynFilter::one_of(routes);
+/// ```
+pub type DynFilter = BoxedFilter<(Box<dyn Reply>,)>;
+
+impl DynFilter {
+    /// Combines the filters into one trying each, in order, like `Filter::or`.
+    ///
+    /// If there are no filters, the combined filter always rejects with a
+    /// `404 Not Found`.
+    pub fn one_of(filters: impl IntoIterator<Item = DynFilter>) -> DynFilter {
+        let mut filters = filters.into_iter();
+        match filters.next() {
+            Some(first) => filters.fold(first, |all, filter| all.or(filter).unify().boxed()),
+            None => crate::any()
+                .and_then(|| future::err::<Box<dyn Reply>, _>(reject::not_found()))
+                .boxed(),
+        }
+    }
+}
+
 fn _assert_send() {
     fn _assert<T: Send>() {}
     _assert::<BoxedFilter<()>>();
+    _assert::<DynFilter>();
 }
 
 impl<T: Tuple + Send> FilterBase for BoxedFilter<T> {
@@ -0,0 +1,51 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::{ready, TryFuture};
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+use crate::reply::Reply;
+
+#[derive(Clone, Copy, Debug)]
+pub struct BoxReply<F> {
+    pub(super) filter: F,
+}
+
+impl<F, R> FilterBase for BoxReply<F>
+where
+    F: Filter<Extract = (R,)>,
+    R: Reply + 'static,
+{
+    type Extract = (Box<dyn Reply>,);
+    type Error = F::Error;
+    type Future = BoxReplyFuture<F::Future>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        BoxReplyFuture {
+            inner: self.filter.filter(Internal),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct BoxReplyFuture<F> {
+    #[pin]
+    inner: F,
+}
+
+impl<F, R> Future for BoxReplyFuture<F>
+where
+    F: TryFuture<Ok = (R,)>,
+    R: Reply + 'static,
+{
+    type Output = Result<(Box<dyn Reply>,), F::Error>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let (reply,) = ready!(self.project().inner.try_poll(cx))?;
+        Poll::Ready(Ok((Box::new(reply),)))
+    }
+}
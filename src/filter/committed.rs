@@ -0,0 +1,53 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::TryFuture;
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+use crate::reject::Rejection;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Committed<T> {
+    pub(super) filter: T,
+}
+
+impl<T> FilterBase for Committed<T>
+where
+    T: Filter,
+    T::Error: Into<Rejection>,
+{
+    type Extract = T::Extract;
+    type Error = Rejection;
+    type Future = CommittedFuture<T>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        CommittedFuture {
+            extract: self.filter.filter(Internal),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct CommittedFuture<T: Filter> {
+    #[pin]
+    extract: T::Future,
+}
+
+impl<T> Future for CommittedFuture<T>
+where
+    T: Filter,
+    T::Error: Into<Rejection>,
+{
+    type Output = Result<T::Extract, Rejection>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.project()
+            .extract
+            .try_poll(cx)
+            .map_err(|err| err.into().commit())
+    }
+}
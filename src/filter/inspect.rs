@@ -0,0 +1,55 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::{ready, TryFuture};
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Inspect<T, F> {
+    pub(super) filter: T,
+    pub(super) callback: F,
+}
+
+impl<T, F> FilterBase for Inspect<T, F>
+where
+    T: Filter,
+    F: Fn(&T::Extract) + Clone + Send,
+{
+    type Extract = T::Extract;
+    type Error = T::Error;
+    type Future = InspectFuture<T, F>;
+    #[inline]
+    fn filter(&self, _: Internal) -> Self::Future {
+        InspectFuture {
+            extract: self.filter.filter(Internal),
+            callback: self.callback.clone(),
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct InspectFuture<T: Filter, F> {
+    #[pin]
+    extract: T::Future,
+    callback: F,
+}
+
+impl<T, F> Future for InspectFuture<T, F>
+where
+    T: Filter,
+    F: Fn(&T::Extract),
+{
+    type Output = Result<T::Extract, T::Error>;
+
+    #[inline]
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let pin = self.project();
+        let ex = ready!(pin.extract.try_poll(cx))?;
+        (pin.callback)(&ex);
+        Poll::Ready(Ok(ex))
+    }
+}
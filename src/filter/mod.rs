@@ -1,10 +1,15 @@
 mod and;
 mod and_then;
+pub(crate) mod blocking;
 mod boxed;
+mod boxed_reply;
+mod committed;
+mod inspect;
 mod map;
 mod map_err;
 mod or;
 mod or_else;
+mod or_if;
 mod recover;
 pub(crate) mod service;
 mod then;
@@ -18,15 +23,21 @@ use futures_util::{future, TryFuture, TryFutureExt};
 
 pub(crate) use crate::generic::{one, Combine, Either, Func, One, Tuple};
 use crate::reject::{CombineRejection, IsReject, Rejection};
+use crate::reply::Reply;
 use crate::route::{self, Route};
 
 pub(crate) use self::and::And;
-use self::and_then::AndThen;
-pub use self::boxed::BoxedFilter;
+use self::and_then::{AndThen, WithState};
+use self::blocking::{Blocking, TryBlocking};
+pub use self::boxed::{BoxedFilter, DynFilter};
+use self::boxed_reply::BoxReply;
+use self::committed::Committed;
+use self::inspect::Inspect;
 pub(crate) use self::map::Map;
 pub(crate) use self::map_err::MapErr;
 pub(crate) use self::or::Or;
 use self::or_else::OrElse;
+use self::or_if::OrIf;
 use self::recover::Recover;
 use self::then::Then;
 use self::unify::Unify;
@@ -123,6 +134,51 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Like [`and`](Filter::and), but once this filter matches, rejections
+    /// of the other filter take precedence over those of any other route.
+    ///
+    /// Normally, when all routes of an [`or`](Filter::or) reject a request,
+    /// the most specific rejection is used, so a `405 Method Not Allowed`
+    /// from one route wins over a `404 Not Found` from another, whatever
+    /// their order. That's often confusing when a route that clearly owns
+    /// a path is beaten by an unrelated one. With `and_strict`, a route is
+    /// committed once this filter, such as its path, matched, and its
+    /// rejection is the one returned.
+    ///
+    /// Other routes are still tried, so several committed routes for the
+    /// same path with different methods work as usual. Only rejections of
+    /// `other` are committed, so the rest of a route should be part of it,
+    /// rather than added afterwards with `and`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// // A `GET /users/abc` is a `404 Not Found`. With `and`, the
+    /// // `405 Method Not Allowed` of the unrelated `upload` route would win.
+    /// let users = warp::path("users")
+    ///     .and_strict(warp::path::param::<u32>().and(warp::path::end()))
+    ///     .map(|id: u32| format!("user #{}", id));
+    /// let upload = warp::post()
+    ///     .and(warp::path("upload"))
+    ///     .map(|| "uploaded");
+    /// let routes = users.or(upload);
+    /// ```
+    fn and_strict<F>(self, other: F) -> And<Self, Committed<F>>
+    where
+        Self: Sized,
+        <Self::Extract as Tuple>::HList: Combine<<F::Extract as Tuple>::HList>,
+        F: Filter + Clone,
+        F::Error: Into<Rejection>,
+        Rejection: CombineRejection<Self::Error>,
+    {
+        And {
+            first: self,
+            second: Committed { filter: other },
+        }
+    }
+
     /// Composes a new `Filter` of either this or the other filter.
     ///
     /// # Example
@@ -147,6 +203,45 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Composes a new `Filter` of either this or the other filter, trying the
+    /// other filter only if `predicate` returns `true` for the rejection.
+    ///
+    /// Otherwise, the rejection is returned right away, without trying the
+    /// other filter. Like [`or`](Filter::or), rejections of both filters are
+    /// combined.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    /// use warp::reject::MethodNotAllowed;
+    ///
+    /// // If `/item` doesn't allow the method, keep looking for a route, but
+    /// // if it has any other problem, such as a missing header, stop here.
+    /// let item = warp::path("item")
+    ///     .and(warp::get())
+    ///     .and(warp::header::<String>("x-token"))
+    ///     .map(|_token: String| "item");
+    ///
+    /// let route = item.or_if(
+    ///     |rejection: &warp::Rejection| rejection.find::<MethodNotAllowed>().is_some(),
+    ///     warp::any().map(|| "fallback"),
+    /// );
+    /// ```
+    fn or_if<P, F>(self, predicate: P, other: F) -> OrIf<Self, P, F>
+    where
+        Self: Filter<Error = Rejection> + Sized,
+        P: Fn(&Rejection) -> bool + Clone + Send,
+        F: Filter + Clone + Send,
+        F::Error: Into<Rejection>,
+    {
+        OrIf {
+            first: self,
+            predicate,
+            second: other,
+        }
+    }
+
     /// Composes this `Filter` with a function receiving the extracted value.
     ///
     ///
@@ -199,6 +294,35 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Calls a function with a reference to the extracted values, for side
+    /// effects like logging, and passes them through unchanged.
+    ///
+    /// The function receives the extracted tuple, so a single value is a
+    /// `&(T,)`. It isn't called when the filter rejects.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let route = warp::path::param()
+    ///     .and(warp::header::optional::<String>("user-agent"))
+    ///     .inspect(|(id, agent): &(u64, Option<String>)| {
+    ///         println!("#{} requested by {:?}", id, agent);
+    ///     })
+    ///     .map(|id: u64, _agent| format!("Hello #{}", id));
+    /// ```
+    fn inspect<F>(self, fun: F) -> Inspect<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self::Extract) + Clone,
+    {
+        Inspect {
+            filter: self,
+            callback: fun,
+        }
+    }
+
     /// Composes this `Filter` with an async function receiving
     /// the extracted value.
     ///
@@ -265,6 +389,114 @@ pub trait Filter: FilterBase {
         }
     }
 
+    /// Composes this `Filter` with a blocking function receiving the
+    /// extracted value.
+    ///
+    /// The function is run on the blocking thread pool, like with
+    /// `tokio::task::spawn_blocking`, so it may use synchronous I/O or heavy
+    /// computation without holding up other requests. If it panics, the
+    /// request is rejected with a `500 Internal Server Error`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// // Map `/:id`
+    /// warp::path::param().map_blocking(|id: u64| {
+    ///   // Something CPU heavy...
+    ///   format!("Hello #{}", id)
+    /// });
+    /// ```
+    fn map_blocking<F>(self, fun: F) -> AndThen<Self, Blocking<F>>
+    where
+        Self: Sized,
+        F: Func<Self::Extract> + Clone + Send + 'static,
+        F::Output: Send + 'static,
+        Self::Extract: Send + 'static,
+        Rejection: CombineRejection<Self::Error>,
+    {
+        AndThen {
+            filter: self,
+            callback: Blocking { callback: fun },
+        }
+    }
+
+    /// Composes this `Filter` with a fallible blocking function receiving
+    /// the extracted value.
+    ///
+    /// Like [`map_blocking`](Filter::map_blocking), the function is run on the
+    /// blocking thread pool. An error it returns rejects the request, like
+    /// with `and_then`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// // Validate `/:id`
+    /// warp::path::param().and_then_blocking(|id: u64| {
+    ///     // Something like a lookup with a blocking database driver...
+    ///     if id != 0 {
+    ///         Ok(format!("Hello #{}", id))
+    ///     } else {
+    ///         Err(warp::reject::not_found())
+    ///     }
+    /// });
+    /// ```
+    fn and_then_blocking<F, T, E>(self, fun: F) -> AndThen<Self, TryBlocking<F>>
+    where
+        Self: Sized,
+        F: Func<Self::Extract, Output = Result<T, E>> + Clone + Send + 'static,
+        T: Send + 'static,
+        E: Into<Rejection> + Send + 'static,
+        Self::Extract: Send + 'static,
+        Rejection: CombineRejection<Self::Error>,
+    {
+        AndThen {
+            filter: self,
+            callback: TryBlocking { callback: fun },
+        }
+    }
+
+    /// Composes this `Filter` with a fallible async function receiving some
+    /// shared state and the extracted values.
+    ///
+    /// This is like [`Filter::and_then`], but the function is always given a
+    /// reference to `state` first, followed by the extracted values as a
+    /// single tuple. The state is shared between all requests, without
+    /// needing to be `Clone`. To use it in the returned future, clone what's
+    /// needed out of it first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use warp::Filter;
+    ///
+    /// let names = HashMap::from([(1, "alice"), (2, "bob")]);
+    ///
+    /// warp::path!("users" / u64).and_then_with(names, |names, (id,)| {
+    ///     let name = names.get(&id).copied();
+    ///     async move { name.ok_or_else(warp::reject::not_found) }
+    /// });
+    /// ```
+    fn and_then_with<S, F, R>(self, state: S, fun: F) -> AndThen<Self, WithState<S, F>>
+    where
+        Self: Sized,
+        F: Fn(&S, Self::Extract) -> R + Clone,
+        R: TryFuture + Send,
+        R::Error: CombineRejection<Self::Error>,
+    {
+        AndThen {
+            filter: self,
+            callback: WithState {
+                state: std::sync::Arc::new(state),
+                callback: fun,
+            },
+        }
+    }
+
     /// Compose this `Filter` with a function receiving an error.
     ///
     /// The function should return some `TryFuture` type yielding the
@@ -288,6 +520,38 @@ pub trait Filter: FilterBase {
     /// This is useful for "customizing" rejections into new response types.
     /// See also the [rejections example][ex].
     ///
+    /// The error type is the one of the function's `TryFuture`. Returning
+    /// `Err` with the rejection for the ones it doesn't handle keeps those
+    /// rejecting, so routes composed with [`or`](Filter::or) afterwards are
+    /// still tried.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    /// use warp::http::StatusCode;
+    /// use warp::reject::InvalidQuery;
+    ///
+    /// #[derive(serde_derive::Deserialize)]
+    /// struct Search {
+    ///     q: String,
+    /// }
+    ///
+    /// let search = warp::path("search")
+    ///     .and(warp::query::<Search>())
+    ///     .map(|search: Search| format!("results for {}", search.q))
+    ///     .recover(|err: warp::Rejection| async move {
+    ///         if err.find::<InvalidQuery>().is_some() {
+    ///             Ok(warp::reply::with_status("bad search", StatusCode::BAD_REQUEST))
+    ///         } else {
+    ///             Err(err)
+    ///         }
+    ///     });
+    ///
+    /// // A `404 Not Found` from `search` still falls through to here.
+    /// let route = search.or(warp::any().map(|| "catch-all"));
+    /// ```
+    ///
     /// [ex]: https://github.com/seanmonstar/warp/blob/master/examples/rejections.rs
     fn recover<F>(self, fun: F) -> Recover<Self, F>
     where
@@ -375,6 +639,33 @@ pub trait Filter: FilterBase {
         UntupleOne { filter: self }
     }
 
+    /// Boxes the extracted reply into a `Box<dyn Reply>`.
+    ///
+    /// Combining replies of different types with `or` extracts nested
+    /// `Either`s. This erases them into a single type, without boxing the
+    /// filter itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::{Filter, Reply};
+    ///
+    /// let route = warp::path("a").map(|| "a")
+    ///     .or(warp::path("b").map(warp::reply))
+    ///     .or(warp::path("c").map(|| warp::reply::json(&"c")))
+    ///     .boxed_reply()
+    ///     .map(|reply: Box<dyn Reply>| {
+    ///         warp::reply::with_header(reply, "server", "warp")
+    ///     });
+    /// ```
+    fn boxed_reply<R>(self) -> BoxReply<Self>
+    where
+        Self: Filter<Extract = (R,)> + Sized,
+        R: Reply + 'static,
+    {
+        BoxReply { filter: self }
+    }
+
     /// Wraps the current filter with some wrapper.
     ///
     /// The wrapper may do some preparation work before starting this filter,
@@ -431,6 +722,30 @@ pub trait Filter: FilterBase {
     {
         BoxedFilter::new(self)
     }
+
+    /// Boxes this filter into a [`DynFilter`], erasing the type of its reply.
+    ///
+    /// Unlike `boxed`, the resulting type is the same for every filter, so
+    /// they can be stored together in collections and combined at runtime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let routes = vec![
+    ///     warp::path("a").map(|| "a").boxed_clone(),
+    ///     warp::path("b").map(warp::reply).boxed_clone(),
+    /// ];
+    /// ```
+    fn boxed_clone<R>(self) -> DynFilter
+    where
+        Self: Filter<Extract = (R,)> + Sized + Send + Sync + 'static,
+        Self::Error: Into<Rejection>,
+        R: Reply + 'static,
+    {
+        BoxedFilter::new(self.map(|reply: R| Box::new(reply) as Box<dyn Reply>))
+    }
 }
 
 impl<T: FilterBase> Filter for T {}
@@ -0,0 +1,109 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::{ready, TryFuture};
+use pin_project::pin_project;
+
+use super::{Filter, FilterBase, Internal};
+use crate::generic::Either;
+use crate::reject::{CombineRejection, Rejection};
+use crate::route;
+
+#[derive(Clone, Copy, Debug)]
+pub struct OrIf<T, P, U> {
+    pub(super) first: T,
+    pub(super) predicate: P,
+    pub(super) second: U,
+}
+
+impl<T, P, U> FilterBase for OrIf<T, P, U>
+where
+    T: Filter<Error = Rejection>,
+    P: Fn(&Rejection) -> bool + Clone + Send,
+    U: Filter + Clone + Send,
+    U::Error: Into<Rejection>,
+{
+    type Extract = (Either<T::Extract, U::Extract>,);
+    type Error = Rejection;
+    type Future = OrIfFuture<T, P, U>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        let idx = route::with(|route| route.matched_path_index());
+        OrIfFuture {
+            state: State::First(
+                self.first.filter(Internal),
+                self.predicate.clone(),
+                self.second.clone(),
+            ),
+            original_path_index: idx,
+        }
+    }
+}
+
+#[allow(missing_debug_implementations)]
+#[pin_project]
+pub struct OrIfFuture<T: Filter, P, U: Filter> {
+    #[pin]
+    state: State<T, P, U>,
+    original_path_index: usize,
+}
+
+#[pin_project(project = StateProj)]
+enum State<T: Filter, P, U: Filter> {
+    First(#[pin] T::Future, P, U),
+    Second(Option<Rejection>, #[pin] U::Future),
+    Done,
+}
+
+impl<T, P, U> Future for OrIfFuture<T, P, U>
+where
+    T: Filter<Error = Rejection>,
+    P: Fn(&Rejection) -> bool,
+    U: Filter,
+    U::Error: Into<Rejection>,
+{
+    type Output = Result<(Either<T::Extract, U::Extract>,), Rejection>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let pin = self.as_mut().project();
+            let idx = *pin.original_path_index;
+            let (err1, fut2) = match pin.state.project() {
+                StateProj::First(first, predicate, second) => match ready!(first.try_poll(cx)) {
+                    Ok(ex1) => {
+                        return Poll::Ready(Ok((Either::A(ex1),)));
+                    }
+                    Err(e) => {
+                        route::with(|route| route.reset_matched_path_index(idx));
+                        if !predicate(&e) {
+                            return Poll::Ready(Err(e));
+                        }
+                        (e, second.filter(Internal))
+                    }
+                },
+                StateProj::Second(err1, second) => {
+                    let ex2 = match ready!(second.try_poll(cx)) {
+                        Ok(ex2) => Ok((Either::B(ex2),)),
+                        Err(e) => {
+                            route::with(|route| route.reset_matched_path_index(idx));
+                            let err1 = err1.take().expect("polled after complete");
+                            Err(e.into().combine(err1))
+                        }
+                    };
+                    self.set(OrIfFuture {
+                        state: State::Done,
+                        original_path_index: idx,
+                    });
+                    return Poll::Ready(ex2);
+                }
+                StateProj::Done => panic!("polled after complete"),
+            };
+
+            self.set(OrIfFuture {
+                state: State::Second(Some(err1), fut2),
+                original_path_index: idx,
+            });
+        }
+    }
+}
@@ -5,6 +5,7 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use futures_util::future::TryFuture;
+use http::StatusCode;
 use hyper::service::Service;
 use pin_project::pin_project;
 
@@ -103,6 +104,11 @@ where
     }
 }
 
+// Marks the response of a rejection that was not found, so the server can
+// replace it with its `not_found` reply.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct UnhandledNotFound;
+
 #[pin_project]
 #[derive(Debug)]
 pub struct FilteredFuture<F> {
@@ -130,7 +136,11 @@ where
             Poll::Pending => Poll::Pending,
             Poll::Ready(Err(err)) => {
                 tracing::debug!("rejected: {:?}", err);
-                Poll::Ready(Ok(err.into_response()))
+                let mut res = err.into_response();
+                if err.status() == StatusCode::NOT_FOUND {
+                    res.extensions_mut().insert(UnhandledNotFound);
+                }
+                Poll::Ready(Ok(res))
             }
         }
     }
@@ -0,0 +1,253 @@
+//! Authentication Filters
+
+use std::error::Error as StdError;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::future;
+use http::header::AUTHORIZATION;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::filter::{filter_fn, Filter, One};
+use crate::reject::{self, Rejection};
+use crate::route::Route;
+
+/// Create a `Filter` that requires a valid JSON Web Token, and extracts its
+/// claims as a `C`.
+///
+/// The token is taken from an `authorization: Bearer <token>` header. Its
+/// signature is checked by the `verifier`, so that warp doesn't depend on a
+/// particular crypto library. Tokens with an `alg` of `none` are never valid.
+///
+/// If the claims have an `exp` or an `nbf`, they're checked against the
+/// current time. The claims are then deserialized into a `C`.
+///
+/// The request is rejected with a `401 Unauthorized` if any of this fails,
+/// with an [`InvalidJwt`] telling why.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use serde_derive::Deserialize;
+/// use warp::Filter;
+/// use warp::auth::{JwtHeader, JwtVerifier};
+///
+/// struct MyVerifier;
+///
+/// impl JwtVerifier for MyVerifier {
+///     fn verify(&self, header: &JwtHeader, message: &[u8], signature: &[u8]) -> bool {
+///         // Check `signature` over `message` with your crypto library of
+///         // choice, using the key and algorithm of `header`.
+///         # let _ = (header, message, signature);
+///         # false
+///     }
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Claims {
+///     sub: String,
+/// }
+///
+/// let route = warp::path("me")
+///     .and(warp::auth::jwt(Arc::new(MyVerifier)))
+///     .map(|claims: Claims| format!("Hello, {}!", claims.sub));
+/// ```
+pub fn jwt<C>(
+    verifier: Arc<dyn JwtVerifier>,
+) -> impl Filter<Extract = One<C>, Error = Rejection> + Clone
+where
+    C: DeserializeOwned + Send + 'static,
+{
+    filter_fn(move |route| future::ready(verify_jwt(route, &*verifier).map(|claims| (claims,))))
+}
+
+/// Verifies the signatures of JSON Web Tokens.
+///
+/// Used by the [`jwt`] filter.
+pub trait JwtVerifier: Send + Sync {
+    /// Returns whether `signature` is valid for `message`.
+    ///
+    /// The `message` is the `<header>.<payload>` part of the token, and
+    /// `signature` is the decoded last part. The `header` tells which
+    /// algorithm and key the token claims to be signed with, which should be
+    /// checked against the ones expected.
+    fn verify(&self, header: &JwtHeader, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// The header of a JSON Web Token.
+#[derive(Clone, Debug)]
+pub struct JwtHeader {
+    alg: String,
+    kid: Option<String>,
+    value: Value,
+}
+
+impl JwtHeader {
+    /// The `alg` of the token, such as `"HS256"`.
+    pub fn alg(&self) -> &str {
+        &self.alg
+    }
+
+    /// The `kid` of the token, naming the key it's signed with.
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    /// Any other field of the header, by name.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.value.get(name)
+    }
+}
+
+/// Why a JSON Web Token was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JwtErrorKind {
+    /// There wasn't an `authorization: Bearer` header.
+    Missing,
+    /// The token couldn't be decoded, or its claims didn't fit the type.
+    Malformed,
+    /// The signature didn't verify.
+    InvalidSignature,
+    /// The token's `exp` has passed.
+    Expired,
+    /// The token's `nbf` hasn't been reached yet.
+    NotYetValid,
+}
+
+/// An error used to reject requests without a valid JSON Web Token.
+#[derive(Debug)]
+pub struct InvalidJwt {
+    kind: JwtErrorKind,
+}
+
+impl InvalidJwt {
+    /// Why the token was rejected.
+    pub fn kind(&self) -> JwtErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for InvalidJwt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.kind {
+            JwtErrorKind::Missing => "Missing bearer token",
+            JwtErrorKind::Malformed => "Malformed bearer token",
+            JwtErrorKind::InvalidSignature => "Invalid bearer token signature",
+            JwtErrorKind::Expired => "Expired bearer token",
+            JwtErrorKind::NotYetValid => "Bearer token not yet valid",
+        })
+    }
+}
+
+impl StdError for InvalidJwt {}
+
+fn verify_jwt<C: DeserializeOwned>(
+    route: &Route,
+    verifier: &dyn JwtVerifier,
+) -> Result<C, Rejection> {
+    let token = route
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            let (scheme, token) = value.split_at(value.find(' ')?);
+            if scheme.eq_ignore_ascii_case("bearer") {
+                Some(token.trim())
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| invalid(JwtErrorKind::Missing))?;
+
+    let mut parts = token.split('.');
+    let (header, payload, signature) =
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(header), Some(payload), Some(signature), None) => (header, payload, signature),
+            _ => return Err(invalid(JwtErrorKind::Malformed)),
+        };
+    let message = &token.as_bytes()[..token.len() - signature.len() - 1];
+
+    let header = decode_json(header)
+        .and_then(|value| {
+            let alg = value.get("alg")?.as_str()?.to_owned();
+            let kid = value.get("kid").and_then(Value::as_str).map(str::to_owned);
+            Some(JwtHeader { alg, kid, value })
+        })
+        .ok_or_else(|| invalid(JwtErrorKind::Malformed))?;
+    let claims = decode_json(payload).ok_or_else(|| invalid(JwtErrorKind::Malformed))?;
+    let signature = decode_base64url(signature).ok_or_else(|| invalid(JwtErrorKind::Malformed))?;
+
+    if header.alg.eq_ignore_ascii_case("none") || !verifier.verify(&header, message, &signature) {
+        return Err(invalid(JwtErrorKind::InvalidSignature));
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    if let Some(exp) = claims.get("exp") {
+        match exp.as_f64() {
+            Some(exp) if now < exp => (),
+            Some(_) => return Err(invalid(JwtErrorKind::Expired)),
+            None => return Err(invalid(JwtErrorKind::Malformed)),
+        }
+    }
+    if let Some(nbf) = claims.get("nbf") {
+        match nbf.as_f64() {
+            Some(nbf) if now >= nbf => (),
+            Some(_) => return Err(invalid(JwtErrorKind::NotYetValid)),
+            None => return Err(invalid(JwtErrorKind::Malformed)),
+        }
+    }
+
+    serde_json::from_value(claims).map_err(|err| {
+        tracing::debug!("jwt claims error: {}", err);
+        invalid(JwtErrorKind::Malformed)
+    })
+}
+
+fn decode_json(part: &str) -> Option<Value> {
+    serde_json::from_slice(&decode_base64url(part)?).ok()
+}
+
+// Decodes unpadded base64url, as used by JSON Web Tokens.
+//
+// Only the canonical encoding is accepted, the bits left over after the last
+// byte must be zero.
+fn decode_base64url(input: &str) -> Option<Vec<u8>> {
+    fn sextet(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'-' => Some(62),
+            b'_' => Some(63),
+            _ => None,
+        }
+    }
+
+    let input = input.as_bytes();
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    for chunk in input.chunks(4) {
+        let mut bits = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            bits |= sextet(*c)? << (18 - 6 * i);
+        }
+        let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        match chunk.len() {
+            4 => out.extend_from_slice(&bytes),
+            3 if bits & 0xFF == 0 => out.extend_from_slice(&bytes[..2]),
+            2 if bits & 0xFFFF == 0 => out.push(bytes[0]),
+            _ => return None,
+        }
+    }
+    Some(out)
+}
+
+fn invalid(kind: JwtErrorKind) -> Rejection {
+    tracing::debug!("jwt rejected: {:?}", kind);
+    reject::known(InvalidJwt { kind })
+}
@@ -4,15 +4,20 @@
 
 use std::error::Error as StdError;
 use std::fmt;
+use std::future::Future;
+use std::marker::PhantomData;
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
-use bytes::{Buf, Bytes};
+use bytes::{Buf, Bytes, BytesMut};
 use futures_util::{future, ready, Stream, TryFutureExt};
 use headers::ContentLength;
 use http::header::CONTENT_TYPE;
+use hyper::body::HttpBody;
 use hyper::Body;
 use serde::de::DeserializeOwned;
+use tokio::time::Sleep;
 
 use crate::filter::{filter_fn, filter_fn_one, Filter, FilterBase};
 use crate::reject::{self, Rejection};
@@ -62,6 +67,86 @@ pub fn content_length_limit(limit: u64) -> impl Filter<Extract = (), Error = Rej
         .untuple_one()
 }
 
+/// Require the request body to keep arriving, with no more than `idle`
+/// between each chunk.
+///
+/// This applies to the body filters that come after it, like `json` or
+/// `bytes`, and is only armed while they are reading the body. If no data
+/// arrives within `idle`, they reject with a `408 Request Timeout`. This
+/// protects against clients holding onto resources by trickling the body.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let upload = warp::body::read_timeout(Duration::from_secs(10))
+///     .and(warp::body::content_length_limit(1024 * 32))
+///     .and(warp::body::bytes());
+/// ```
+pub fn read_timeout(idle: Duration) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        let result = route.take_body().map(|body| {
+            let body = IdleTimeout {
+                body,
+                idle,
+                sleep: None,
+            };
+            route.set_body(Body::wrap_stream(body));
+        });
+        future::ready(result.ok_or_else(|| {
+            tracing::error!("request body already taken in previous filter");
+            reject::known(BodyConsumedMultipleTimes { _p: () })
+        }))
+    })
+}
+
+/// Require the `content-type` of the request to be one of `allowed`.
+///
+/// Only the media type is compared, ignoring parameters such as `charset`,
+/// and case-insensitively. If the `content-type` is missing, can't be parsed,
+/// or isn't allowed, the request is rejected with a
+/// `415 Unsupported Media Type`. The body isn't read.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let upload = warp::body::accept_content_types(&["image/png", "image/jpeg"])
+///     .and(warp::body::content_length_limit(1024 * 1024))
+///     .and(warp::body::bytes());
+/// ```
+pub fn accept_content_types(
+    allowed: &'static [&'static str],
+) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        let ct = route
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<mime::Mime>().ok());
+        match ct {
+            Some(ct)
+                if allowed
+                    .iter()
+                    .any(|allowed| ct.essence_str().eq_ignore_ascii_case(allowed)) =>
+            {
+                future::ok(())
+            }
+            Some(ct) => {
+                tracing::debug!("content-type {} isn't one of {:?}", ct, allowed);
+                future::err(reject::unsupported_media_type())
+            }
+            None => {
+                tracing::debug!("content-type missing or invalid");
+                future::err(reject::unsupported_media_type())
+            }
+        }
+    })
+}
+
 /// Create a `Filter` that extracts the request body as a `futures::Stream`.
 ///
 /// If other filters have already extracted the body, this filter will reject
@@ -106,8 +191,113 @@ pub fn bytes() -> impl Filter<Extract = (Bytes,), Error = Rejection> + Copy {
     body().and_then(|body: hyper::Body| {
         hyper::body::to_bytes(body).map_err(|err| {
             tracing::debug!("to_bytes error: {}", err);
-            reject::known(BodyReadError(err))
+            read_error(err)
+        })
+    })
+}
+
+/// Returns a `Filter` that matches any request and extracts its text body,
+/// decoded into a `String`.
+///
+/// The body is decoded with the `charset` parameter of the `content-type`
+/// header, defaulting to UTF-8. The supported charsets are `utf-8`,
+/// `us-ascii`, `iso-8859-1` (and its aliases like `latin1`), `utf-16`,
+/// `utf-16le` and `utf-16be`.
+///
+/// A body that isn't valid in its charset is rejected with
+/// [`InvalidCharset`](InvalidCharset), and a charset that isn't supported with
+/// a `415 Unsupported Media Type`.
+///
+/// # Warning
+///
+/// This does not have a default size limit, it would be wise to use one to
+/// prevent a overly large request from using too much memory.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::body::content_length_limit(1024 * 32)
+///     .and(warp::body::text())
+///     .map(|text: String| {
+///         format!("{} characters", text.chars().count())
+///     });
+/// ```
+pub fn text() -> impl Filter<Extract = (String,), Error = Rejection> + Copy {
+    charset()
+        .and(bytes())
+        .and_then(|charset: Charset, bytes: Bytes| async move {
+            charset.decode(&bytes).ok_or_else(|| {
+                tracing::debug!("request body isn't valid {:?}", charset);
+                reject::known(InvalidCharset { _p: () })
+            })
         })
+}
+
+/// Returns a `Filter` that matches any request and extracts up to `max` bytes
+/// of the body, along with whether the body was truncated.
+///
+/// Unlike [`buffered`](buffered), a body longer than `max` isn't rejected.
+/// Reading stops once `max` bytes are read, and the rest of the body is left
+/// unread. This allows handling large bodies in a handler, such as replying
+/// with a custom `413` page or working with a partial body.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::StatusCode;
+/// use warp::Filter;
+///
+/// let route = warp::body::bytes_with_limit(1024 * 32)
+///     .map(|body: bytes::Bytes, truncated: bool| {
+///         if truncated {
+///             warp::reply::with_status("upload at most 32KiB", StatusCode::PAYLOAD_TOO_LARGE)
+///         } else {
+///             warp::reply::with_status("thanks", StatusCode::OK)
+///         }
+///     });
+/// ```
+pub fn bytes_with_limit(
+    max: u64,
+) -> impl Filter<Extract = (Bytes, bool), Error = Rejection> + Copy {
+    body()
+        .and_then(move |body: Body| to_bytes_up_to(body, max))
+        .untuple_one()
+}
+
+/// Returns a `Filter` that reads the whole body, extracting it as `Bytes`
+/// while leaving it for the filters that come after it.
+///
+/// The body of a request can normally be taken only once. This reads it into
+/// a buffer and puts a copy of it back, so that middleware can inspect it,
+/// such as to log or audit it, and a later filter like `json` or `form` can
+/// still consume it.
+///
+/// The body is rejected with a `413 Payload Too Large` as soon as it grows
+/// past `max` bytes, whether or not it has a `content-length`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use warp::Filter;
+///
+/// let audit = warp::body::buffered(1024 * 32).map(|body: bytes::Bytes| {
+///     println!("request body: {:?}", body);
+/// }).untuple_one();
+///
+/// let route = audit
+///     .and(warp::body::json())
+///     .map(|simple_map: HashMap<String, String>| {
+///         "Got a JSON body!"
+///     });
+/// ```
+pub fn buffered(max: u64) -> impl Filter<Extract = (Bytes,), Error = Rejection> + Copy {
+    body().and_then(move |body: Body| async move {
+        let buf = to_bytes_limited(body, max).await?;
+        crate::route::with(|route| route.set_body(Body::from(buf.clone())));
+        Ok::<_, Rejection>(buf)
     })
 }
 
@@ -144,7 +334,7 @@ pub fn aggregate() -> impl Filter<Extract = (impl Buf,), Error = Rejection> + Co
     body().and_then(|body: ::hyper::Body| {
         hyper::body::aggregate(body).map_err(|err| {
             tracing::debug!("aggregate error: {}", err);
-            reject::known(BodyReadError(err))
+            read_error(err)
         })
     })
 }
@@ -180,6 +370,141 @@ pub fn json<T: DeserializeOwned + Send>() -> impl Filter<Extract = (T,), Error =
         })
 }
 
+/// Returns a `Filter` that matches any request and extracts a JSON-decoded
+/// body as a dynamic `serde_json::Value`.
+///
+/// This is useful when the body doesn't have a fixed schema, such as when
+/// forwarding or transforming arbitrary JSON. It behaves the same as
+/// [`json`](json), rejecting with a `415 Unsupported Media Type` if the
+/// `content-type` isn't JSON, and a `400 Bad Request` if the body isn't
+/// valid JSON.
+///
+/// Like [`json_streaming`](json_streaming), the body is rejected with a
+/// `413 Payload Too Large` as soon as it grows past `max` bytes, whether or
+/// not it has a `content-length`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::body::json_value(1024 * 32)
+///     .map(|value: serde_json::Value| {
+///         format!("Got a JSON {}!", if value.is_object() { "object" } else { "value" })
+///     });
+/// ```
+pub fn json_value(
+    max: u64,
+) -> impl Filter<Extract = (serde_json::Value,), Error = Rejection> + Copy {
+    json_streaming(max)
+}
+
+/// Returns a `Filter` that matches any request and extracts a `Stream` of
+/// JSON-decoded values from the body.
+///
+/// Values are decoded as the body arrives, so only one of them is buffered
+/// at a time, of no more than `max` bytes. This suits bodies that are too
+/// large to buffer whole, such as those of bulk imports.
+///
+/// With a `content-type` of `application/x-ndjson`, the body is read as
+/// newline-delimited JSON, one value per line, skipping blank lines.
+/// Otherwise, the body must be a JSON array, and each of its elements is a
+/// value. Any other `content-type` than those and `application/json` is
+/// rejected with a `415 Unsupported Media Type`.
+///
+/// The stream yields an error, and ends, at the first value that can't be
+/// decoded, or that grows past `max` bytes, such as a line that never ends.
+///
+/// # Warning
+///
+/// This does not limit the size of the whole body, only that of each value.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::{Stream, TryStreamExt};
+/// use serde_derive::Deserialize;
+/// use warp::body::BodyDeserializeError;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// struct Record {
+///     id: u64,
+/// }
+///
+/// async fn import(records: impl Stream<Item = Result<Record, BodyDeserializeError>>) -> String {
+///     let imported = records
+///         .try_fold(0, |count, _record| async move { Ok(count + 1) })
+///         .await;
+///     match imported {
+///         Ok(count) => format!("imported {} records", count),
+///         Err(err) => format!("import failed: {}", err),
+///     }
+/// }
+///
+/// let route = warp::post()
+///     .and(warp::body::json_stream::<Record>(1024 * 16))
+///     .then(import);
+/// ```
+pub fn json_stream<T: DeserializeOwned + Send>(
+    max: u64,
+) -> impl Filter<
+    Extract = (impl Stream<Item = Result<T, BodyDeserializeError>> + Send,),
+    Error = Rejection,
+> + Copy {
+    is_json_stream_type()
+        .and(body())
+        .map(move |lines: bool, body: Body| JsonStream {
+            body,
+            buf: BytesMut::new(),
+            max,
+            state: if lines {
+                JsonState::Lines { pos: 0 }
+            } else {
+                JsonState::Start
+            },
+            done: false,
+            _marker: PhantomData,
+        })
+}
+
+/// Returns a `Filter` that matches any request and extracts a JSON-decoded
+/// body, reading no more than `max` bytes of it.
+///
+/// Unlike combining `content_length_limit` with `json`, the limit is enforced
+/// on the bytes actually received, so it also applies to bodies without a
+/// `content-length`, such as chunked ones. The body is read into a buffer,
+/// rejecting with a `413 Payload Too Large` as soon as it grows past `max`,
+/// and is only deserialized once complete. A `content-length` larger than
+/// `max` is rejected before reading anything.
+///
+/// Otherwise, this behaves like [`json`](json).
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use warp::Filter;
+///
+/// let route = warp::body::json_streaming(1024 * 32)
+///     .map(|simple_map: HashMap<String, String>| {
+///         "Got a JSON body!"
+///     });
+/// ```
+pub fn json_streaming<T: DeserializeOwned + Send>(
+    max: u64,
+) -> impl Filter<Extract = (T,), Error = Rejection> + Copy {
+    is_content_type::<Json>()
+        .and(body())
+        .and_then(move |body: Body| async move {
+            let buf = to_bytes_limited(body, max).await?;
+            Json::decode(buf).map_err(|err| {
+                tracing::debug!("request json body error: {}", err);
+                reject::known(BodyDeserializeError { cause: err })
+            })
+        })
+}
+
 /// Returns a `Filter` that matches any request and extracts a
 /// `Future` of a form encoded body.
 ///
@@ -215,6 +540,145 @@ pub fn form<T: DeserializeOwned + Send>() -> impl Filter<Extract = (T,), Error =
         })
 }
 
+/// Returns a `Filter` that matches any request and extracts a form encoded
+/// body as key-value pairs, reading no more than `max` bytes of it.
+///
+/// The pairs are kept in the order they were sent, including duplicate keys,
+/// such as from repeated checkboxes or a multiple `<select>`. Decoding into
+/// a map with [`form`](form) would keep only one value for each key.
+///
+/// The body is read like [`json_streaming`](json_streaming), rejecting with a
+/// `413 Payload Too Large` as soon as it grows past `max`. A `content-type`
+/// other than `application/x-www-form-urlencoded` is rejected with a
+/// `415 Unsupported Media Type`. Invalid UTF-8 in the keys and values is
+/// replaced, rather than rejected.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::body::form_pairs(1024 * 16)
+///     .map(|pairs: Vec<(String, String)>| {
+///         let toppings = pairs
+///             .into_iter()
+///             .filter(|(key, _)| key == "topping")
+///             .map(|(_, value)| value)
+///             .collect::<Vec<_>>();
+///         format!("toppings: {}", toppings.join(", "))
+///     });
+/// ```
+pub fn form_pairs(
+    max: u64,
+) -> impl Filter<Extract = (Vec<(String, String)>,), Error = Rejection> + Copy {
+    is_content_type::<Form>()
+        .and(body())
+        .and_then(move |body: Body| async move {
+            let buf = to_bytes_limited(body, max).await?;
+            Form::decode(buf).map_err(|err| {
+                tracing::debug!("request form body error: {}", err);
+                reject::known(BodyDeserializeError { cause: err })
+            })
+        })
+}
+
+/// Returns a `Filter` that matches any request with a gzip encoded body, and
+/// extracts the decompressed body as `Bytes`.
+///
+/// The request must have a `content-encoding: gzip` header, or else it is
+/// rejected with a `415 Unsupported Media Type`.
+///
+/// To protect against "zip bombs", the body is inflated incrementally, and
+/// the request is rejected as soon as either:
+///
+/// - The decompressed size exceeds `max_decompressed` bytes, rejecting with
+///   a `413 Payload Too Large`.
+/// - The decompressed size exceeds `max_ratio` times the compressed size,
+///   rejecting with a `400 Bad Request`.
+///
+/// *This function requires the `"compression-gzip"` feature.*
+///
+/// # Warning
+///
+/// This limits the *decompressed* size only. The compressed body is still
+/// read completely, so a limit such as `content_length_limit` should be used
+/// as well.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Accept up to 1mb compressed, inflating to at most 16mb, with a ratio
+/// // no worse than 100:1.
+/// let route = warp::body::content_length_limit(1024 * 1024)
+///     .and(warp::body::gunzip(16 * 1024 * 1024, 100))
+///     .map(|bytes: bytes::Bytes| {
+///         format!("inflated {} bytes", bytes.len())
+///     });
+/// ```
+#[cfg(feature = "compression-gzip")]
+pub fn gunzip(
+    max_decompressed: u64,
+    max_ratio: u32,
+) -> impl Filter<Extract = (Bytes,), Error = Rejection> + Copy {
+    is_content_encoding_gzip()
+        .and(bytes())
+        .and_then(move |compressed: Bytes| inflate(compressed, max_decompressed, max_ratio))
+}
+
+#[cfg(feature = "compression-gzip")]
+fn is_content_encoding_gzip() -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(|route| {
+        let value = route.headers().get(http::header::CONTENT_ENCODING);
+        tracing::trace!("is_content_encoding_gzip? {:?}", value);
+        match value {
+            Some(value) if value.as_bytes().eq_ignore_ascii_case(b"gzip") => future::ok(()),
+            _ => {
+                tracing::debug!("content-encoding {:?} isn't gzip", value);
+                future::err(reject::unsupported_media_type())
+            }
+        }
+    })
+}
+
+#[cfg(feature = "compression-gzip")]
+async fn inflate(
+    compressed: Bytes,
+    max_decompressed: u64,
+    max_ratio: u32,
+) -> Result<Bytes, Rejection> {
+    use async_compression::tokio::bufread::GzipDecoder;
+    use futures_util::StreamExt;
+    use tokio_util::io::ReaderStream;
+
+    let max_ratio_len = (compressed.len() as u64).saturating_mul(u64::from(max_ratio));
+    let mut stream = ReaderStream::new(GzipDecoder::new(&compressed[..]));
+    let mut inflated = Vec::new();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| {
+            tracing::debug!("gunzip error: {}", err);
+            reject::known(BodyDecompressError { cause: err.into() })
+        })?;
+        inflated.extend_from_slice(&chunk);
+
+        let len = inflated.len() as u64;
+        if len > max_decompressed {
+            tracing::debug!("decompressed body is over limit {}", max_decompressed);
+            return Err(reject::payload_too_large());
+        }
+        if len > max_ratio_len {
+            tracing::debug!("decompressed body is over ratio limit {}", max_ratio);
+            return Err(reject::known(BodyDecompressError {
+                cause: "compression ratio is over the limit".into(),
+            }));
+        }
+    }
+
+    Ok(inflated.into())
+}
+
 // ===== Decoders =====
 
 trait Decode {
@@ -285,6 +749,330 @@ fn is_content_type<D: Decode>() -> impl Filter<Extract = (), Error = Rejection>
     })
 }
 
+#[derive(Clone, Copy, Debug)]
+enum Charset {
+    Utf8,
+    Ascii,
+    Latin1,
+    Utf16,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Charset {
+    fn from_label(label: &str) -> Option<Charset> {
+        let charset = match label.to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" => Charset::Utf8,
+            "us-ascii" | "ascii" => Charset::Ascii,
+            "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "l1" => Charset::Latin1,
+            "utf-16" => Charset::Utf16,
+            "utf-16le" => Charset::Utf16Le,
+            "utf-16be" => Charset::Utf16Be,
+            _ => return None,
+        };
+        Some(charset)
+    }
+
+    fn decode(self, bytes: &[u8]) -> Option<String> {
+        match self {
+            Charset::Utf8 => {
+                let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+                std::str::from_utf8(bytes).ok().map(str::to_owned)
+            }
+            Charset::Ascii => {
+                if bytes.is_ascii() {
+                    std::str::from_utf8(bytes).ok().map(str::to_owned)
+                } else {
+                    None
+                }
+            }
+            Charset::Latin1 => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+            // Without a byte order mark, UTF-16 is big endian (RFC 2781).
+            Charset::Utf16 => match bytes {
+                [0xFF, 0xFE, rest @ ..] => decode_utf16(rest, u16::from_le_bytes),
+                [0xFE, 0xFF, rest @ ..] => decode_utf16(rest, u16::from_be_bytes),
+                _ => decode_utf16(bytes, u16::from_be_bytes),
+            },
+            Charset::Utf16Le => decode_utf16(bytes, u16::from_le_bytes),
+            Charset::Utf16Be => decode_utf16(bytes, u16::from_be_bytes),
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> Option<String> {
+    let chunks = bytes.chunks_exact(2);
+    if !chunks.remainder().is_empty() {
+        return None;
+    }
+    let units = chunks.map(|pair| unit([pair[0], pair[1]]));
+    char::decode_utf16(units).collect::<Result<_, _>>().ok()
+}
+
+// Extracts the charset of the `content-type`, defaulting to UTF-8.
+fn charset() -> impl Filter<Extract = (Charset,), Error = Rejection> + Copy {
+    filter_fn_one(|route| {
+        let value = match route.headers().get(CONTENT_TYPE) {
+            Some(value) => value,
+            None => return future::ok(Charset::Utf8),
+        };
+        let ct = value
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<mime::Mime>().ok());
+        let ct = match ct {
+            Some(ct) => ct,
+            None => {
+                tracing::debug!("content-type {:?} couldn't be parsed", value);
+                return future::err(reject::unsupported_media_type());
+            }
+        };
+        match ct.get_param(mime::CHARSET) {
+            None => future::ok(Charset::Utf8),
+            Some(label) => match Charset::from_label(label.as_str()) {
+                Some(charset) => future::ok(charset),
+                None => {
+                    tracing::debug!("unsupported charset {:?}", label.as_str());
+                    future::err(reject::unsupported_media_type())
+                }
+            },
+        }
+    })
+}
+
+// Extracts whether the body is newline-delimited JSON, or else a JSON array.
+fn is_json_stream_type() -> impl Filter<Extract = (bool,), Error = Rejection> + Copy {
+    filter_fn_one(|route| {
+        let value = match route.headers().get(CONTENT_TYPE) {
+            Some(value) => value,
+            None => return future::ok(false),
+        };
+        let ct = value
+            .to_str()
+            .ok()
+            .and_then(|s| s.parse::<mime::Mime>().ok());
+        match ct {
+            Some(ct) if ct.type_() == mime::APPLICATION && ct.subtype() == "x-ndjson" => {
+                future::ok(true)
+            }
+            Some(ct) if ct.type_() == mime::APPLICATION && ct.subtype() == mime::JSON => {
+                future::ok(false)
+            }
+            _ => {
+                tracing::debug!("content-type {:?} isn't a JSON stream", value);
+                future::err(reject::unsupported_media_type())
+            }
+        }
+    })
+}
+
+// ===== JsonStream =====
+
+struct JsonStream<T> {
+    body: Body,
+    buf: BytesMut,
+    max: u64,
+    state: JsonState,
+    done: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+// Where in the body a `JsonStream` is. `pos` is how far into `buf` has been
+// scanned.
+enum JsonState {
+    Lines {
+        pos: usize,
+    },
+    // Before the `[` of an array.
+    Start,
+    // After the `[`, where the array may be empty.
+    First,
+    // Scanning an element for the `,` or `]` after it.
+    Element {
+        pos: usize,
+        depth: usize,
+        in_string: bool,
+        escaped: bool,
+    },
+    // After an element, before the `,` or `]`.
+    Separator,
+    // After the `]`.
+    End,
+}
+
+impl JsonState {
+    fn element() -> JsonState {
+        JsonState::Element {
+            pos: 0,
+            depth: 0,
+            in_string: false,
+            escaped: false,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> JsonStream<T> {
+    // Decodes the next value in `buf`, if it has all of one.
+    fn next_value(&mut self) -> Result<Option<T>, BoxError> {
+        loop {
+            match self.state {
+                JsonState::Lines { ref mut pos } => {
+                    match self.buf[*pos..].iter().position(|&b| b == b'\n') {
+                        Some(i) if (*pos + i) as u64 > self.max => return Err(too_large()),
+                        Some(i) => {
+                            let line = self.buf.split_to(*pos + i + 1);
+                            *pos = 0;
+                            if !line.iter().all(u8::is_ascii_whitespace) {
+                                return serde_json::from_slice(&line).map(Some).map_err(Into::into);
+                            }
+                        }
+                        None => {
+                            *pos = self.buf.len();
+                            return Ok(None);
+                        }
+                    }
+                }
+                JsonState::Start => match self.skip_whitespace() {
+                    Some(b'[') => {
+                        self.buf.advance(1);
+                        self.state = JsonState::First;
+                    }
+                    Some(_) => return Err("expected a JSON array".into()),
+                    None => return Ok(None),
+                },
+                JsonState::First => match self.skip_whitespace() {
+                    Some(b']') => {
+                        self.buf.advance(1);
+                        self.state = JsonState::End;
+                    }
+                    Some(_) => self.state = JsonState::element(),
+                    None => return Ok(None),
+                },
+                JsonState::Element {
+                    ref mut pos,
+                    ref mut depth,
+                    ref mut in_string,
+                    ref mut escaped,
+                } => {
+                    while *pos < self.buf.len() {
+                        let b = self.buf[*pos];
+                        if *in_string {
+                            if *escaped {
+                                *escaped = false;
+                            } else if b == b'\\' {
+                                *escaped = true;
+                            } else if b == b'"' {
+                                *in_string = false;
+                            }
+                        } else {
+                            match b {
+                                b'"' => *in_string = true,
+                                b'[' | b'{' => *depth += 1,
+                                b',' | b']' if *depth == 0 => {
+                                    if *pos as u64 > self.max {
+                                        return Err(too_large());
+                                    }
+                                    let element = self.buf.split_to(*pos);
+                                    self.state = JsonState::Separator;
+                                    return serde_json::from_slice(&element)
+                                        .map(Some)
+                                        .map_err(Into::into);
+                                }
+                                b']' | b'}' => *depth = depth.saturating_sub(1),
+                                _ => (),
+                            }
+                        }
+                        *pos += 1;
+                    }
+                    return Ok(None);
+                }
+                JsonState::Separator => match self.skip_whitespace() {
+                    Some(b',') => {
+                        self.buf.advance(1);
+                        self.state = JsonState::element();
+                    }
+                    Some(b']') => {
+                        self.buf.advance(1);
+                        self.state = JsonState::End;
+                    }
+                    Some(_) => return Err("expected `,` or `]` after an array element".into()),
+                    None => return Ok(None),
+                },
+                JsonState::End => match self.skip_whitespace() {
+                    Some(_) => return Err("trailing characters after the JSON array".into()),
+                    None => return Ok(None),
+                },
+            }
+        }
+    }
+
+    // Drops leading whitespace from `buf`, returning the next byte.
+    fn skip_whitespace(&mut self) -> Option<u8> {
+        let n = self
+            .buf
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+        self.buf.advance(n);
+        self.buf.first().copied()
+    }
+
+    // Decodes what's left once the whole body has been read. It's no larger
+    // than `max`, as that's checked before reading more.
+    fn finish(&mut self) -> Result<Option<T>, BoxError> {
+        match self.state {
+            JsonState::Lines { .. } if !self.buf.iter().all(u8::is_ascii_whitespace) => {
+                let line = self.buf.split();
+                serde_json::from_slice(&line).map(Some).map_err(Into::into)
+            }
+            JsonState::Lines { .. } | JsonState::End => Ok(None),
+            _ => Err("unexpected end of the JSON array".into()),
+        }
+    }
+}
+
+fn too_large() -> BoxError {
+    "a value is larger than the limit".into()
+}
+
+impl<T: DeserializeOwned> Stream for JsonStream<T> {
+    type Item = Result<T, BodyDeserializeError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+            let result = match this.next_value() {
+                Ok(Some(value)) => Ok(Some(value)),
+                // Only the incomplete value is left in `buf`.
+                Ok(None) if this.buf.len() as u64 > this.max => Err(too_large()),
+                Ok(None) => match ready!(Pin::new(&mut this.body).poll_next(cx)) {
+                    Some(Ok(chunk)) => {
+                        this.buf.extend_from_slice(&chunk);
+                        continue;
+                    }
+                    Some(Err(err)) => Err(err.into()),
+                    None => {
+                        this.done = true;
+                        this.finish()
+                    }
+                },
+                Err(err) => Err(err),
+            };
+            return match result {
+                Ok(Some(value)) => Poll::Ready(Some(Ok(value))),
+                Ok(None) => Poll::Ready(None),
+                Err(cause) => {
+                    tracing::debug!("request json stream error: {}", cause);
+                    this.done = true;
+                    Poll::Ready(Some(Err(BodyDeserializeError { cause })))
+                }
+            };
+        }
+    }
+}
+
 // ===== BodyStream =====
 
 struct BodyStream {
@@ -308,12 +1096,87 @@ impl Stream for BodyStream {
     }
 }
 
+// ===== IdleTimeout =====
+
+struct IdleTimeout {
+    body: Body,
+    idle: Duration,
+    // Armed when waiting for the next chunk.
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Stream for IdleTimeout {
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let Poll::Ready(item) = Pin::new(&mut this.body).poll_next(cx) {
+            this.sleep = None;
+            return Poll::Ready(item.map(|result| result.map_err(Into::into)));
+        }
+
+        let idle = this.idle;
+        let sleep = this
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(idle)));
+        ready!(sleep.as_mut().poll(cx));
+        tracing::debug!("request body idle for {:?}", idle);
+        Poll::Ready(Some(Err(BodyReadTimeout { _p: () }.into())))
+    }
+}
+
+// Like `hyper::body::to_bytes`, but stops reading once over `max` bytes.
+pub(crate) async fn to_bytes_limited(body: Body, max: u64) -> Result<Bytes, Rejection> {
+    if HttpBody::size_hint(&body).lower() > max {
+        tracing::debug!("content-length is over limit {}", max);
+        return Err(reject::payload_too_large());
+    }
+
+    match to_bytes_up_to(body, max).await? {
+        (_, true) => {
+            tracing::debug!("body is over limit {}", max);
+            Err(reject::payload_too_large())
+        }
+        (buf, false) => Ok(buf),
+    }
+}
+
+// Reads at most `max` bytes of the body, and whether there was more.
+async fn to_bytes_up_to(mut body: Body, max: u64) -> Result<(Bytes, bool), Rejection> {
+    let mut buf = BytesMut::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|err| {
+            tracing::debug!("to_bytes error: {}", err);
+            read_error(err)
+        })?;
+        let room = max - buf.len() as u64;
+        if chunk.len() as u64 > room {
+            buf.extend_from_slice(&chunk[..room as usize]);
+            return Ok((buf.freeze(), true));
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok((buf.freeze(), false))
+}
+
+fn read_error(err: hyper::Error) -> Rejection {
+    let timed_out = match err.source() {
+        Some(source) => source.is::<BodyReadTimeout>(),
+        None => false,
+    };
+    if timed_out {
+        reject::known(BodyReadTimeout { _p: () })
+    } else {
+        reject::known(BodyReadError(err))
+    }
+}
+
 // ===== Rejections =====
 
 /// An error used in rejections when deserializing a request body fails.
 #[derive(Debug)]
 pub struct BodyDeserializeError {
-    cause: BoxError,
+    pub(crate) cause: BoxError,
 }
 
 impl fmt::Display for BodyDeserializeError {
@@ -328,6 +1191,27 @@ impl StdError for BodyDeserializeError {
     }
 }
 
+/// An error used in rejections when decompressing a request body fails.
+#[cfg(feature = "compression-gzip")]
+#[derive(Debug)]
+pub struct BodyDecompressError {
+    cause: BoxError,
+}
+
+#[cfg(feature = "compression-gzip")]
+impl fmt::Display for BodyDecompressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Request body decompress error: {}", self.cause)
+    }
+}
+
+#[cfg(feature = "compression-gzip")]
+impl StdError for BodyDecompressError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(self.cause.as_ref())
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct BodyReadError(::hyper::Error);
 
@@ -339,6 +1223,16 @@ impl fmt::Display for BodyReadError {
 
 impl StdError for BodyReadError {}
 
+unit_error! {
+    /// An error used in rejections when the request body stops arriving.
+    pub BodyReadTimeout: "Request body read timed out"
+}
+
+unit_error! {
+    /// An error used in rejections when a text body isn't valid in its charset.
+    pub InvalidCharset: "Request body is not valid in its charset"
+}
+
 unit_error! {
     pub(crate) BodyConsumedMultipleTimes: "Request body consumed multiple times"
 }
@@ -0,0 +1,515 @@
+//! Cache Filters
+//!
+//! Filters to let clients reuse responses they already have, by validating
+//! them with conditional requests.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::time::SystemTime;
+
+use headers::{
+    ETag, HeaderMapExt, IfMatch, IfModifiedSince, IfNoneMatch, IfRange, IfUnmodifiedSince,
+    LastModified, Range,
+};
+use http::StatusCode;
+use hyper::Body;
+
+use self::internal::VersionedFilter;
+use crate::filter::{Filter, One, WrapSealed};
+use crate::reject::{self, Rejection};
+use crate::reply::{Reply, Response};
+
+/// Creates a `Filter` that extracts the conditional request headers.
+///
+/// This is everything needed to evaluate conditional and range requests
+/// against a representation, such as to reply with a `304 Not Modified`, a
+/// `412 Precondition Failed`, or a `206 Partial Content`. Headers that are
+/// missing or invalid are `None`.
+///
+/// The headers are parsed into the types of the [`headers`] crate.
+///
+/// [`headers`]: https://docs.rs/headers/0.3
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use warp::Filter;
+/// use warp::cache::CacheValidators;
+/// use warp::http::StatusCode;
+///
+/// let updated = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+///
+/// let route = warp::cache::validators().map(move |validators: CacheValidators| {
+///     let unmodified = validators
+///         .if_modified_since()
+///         .map(|since| !since.is_modified(updated))
+///         .unwrap_or(false);
+///     if unmodified {
+///         warp::reply::with_status("", StatusCode::NOT_MODIFIED)
+///     } else {
+///         warp::reply::with_status("the resource", StatusCode::OK)
+///     }
+/// });
+/// ```
+pub fn validators() -> impl Filter<Extract = One<CacheValidators>, Error = Infallible> + Copy {
+    crate::header::optional2()
+        .and(crate::header::optional2())
+        .and(crate::header::optional2())
+        .and(crate::header::optional2())
+        .and(crate::header::optional2())
+        .and(crate::header::optional2())
+        .map(
+            |if_match, if_none_match, if_modified_since, if_unmodified_since, if_range, range| {
+                CacheValidators {
+                    if_match,
+                    if_none_match,
+                    if_modified_since,
+                    if_unmodified_since,
+                    if_range,
+                    range,
+                }
+            },
+        )
+}
+
+/// The conditional request headers of a request.
+///
+/// Extracted by the [`validators`] filter.
+#[derive(Clone, Debug)]
+pub struct CacheValidators {
+    pub(crate) if_match: Option<IfMatch>,
+    pub(crate) if_none_match: Option<IfNoneMatch>,
+    pub(crate) if_modified_since: Option<IfModifiedSince>,
+    pub(crate) if_unmodified_since: Option<IfUnmodifiedSince>,
+    pub(crate) if_range: Option<IfRange>,
+    pub(crate) range: Option<Range>,
+}
+
+impl CacheValidators {
+    /// The `if-match` header.
+    pub fn if_match(&self) -> Option<&IfMatch> {
+        self.if_match.as_ref()
+    }
+
+    /// The `if-none-match` header.
+    pub fn if_none_match(&self) -> Option<&IfNoneMatch> {
+        self.if_none_match.as_ref()
+    }
+
+    /// The `if-modified-since` header.
+    pub fn if_modified_since(&self) -> Option<&IfModifiedSince> {
+        self.if_modified_since.as_ref()
+    }
+
+    /// The `if-unmodified-since` header.
+    pub fn if_unmodified_since(&self) -> Option<&IfUnmodifiedSince> {
+        self.if_unmodified_since.as_ref()
+    }
+
+    /// The `if-range` header.
+    pub fn if_range(&self) -> Option<&IfRange> {
+        self.if_range.as_ref()
+    }
+
+    /// The `range` header.
+    pub fn range(&self) -> Option<&Range> {
+        self.range.as_ref()
+    }
+
+    /// Validates a `reply` for a representation with the `etag`.
+    ///
+    /// The reply is a `412 Precondition Failed` if the `if-match` header
+    /// doesn't match the `etag`, strongly, or a `304 Not Modified` if the
+    /// `if-none-match` header matches it, weakly. Otherwise, it's `reply`,
+    /// with the `etag` added when it's successful.
+    ///
+    /// Since `304 Not Modified` is only meant for `GET` and `HEAD` requests,
+    /// unsafe methods should check `if-none-match` themselves.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    /// use warp::cache::CacheValidators;
+    ///
+    /// let route = warp::get()
+    ///     .and(warp::path("user"))
+    ///     .and(warp::cache::validators())
+    ///     .map(|validators: CacheValidators| {
+    ///         let etag = "\"v42\"".parse().unwrap();
+    ///         validators.conditional(etag, warp::reply::json(&"sean"))
+    ///     });
+    /// ```
+    pub fn conditional<R: Reply>(&self, etag: ETag, reply: R) -> Conditional<R> {
+        let status = if self
+            .if_match
+            .as_ref()
+            .map(|if_match| !if_match.precondition_passes(&etag))
+            .unwrap_or(false)
+        {
+            Some(StatusCode::PRECONDITION_FAILED)
+        } else if self
+            .if_none_match
+            .as_ref()
+            .map(|if_none_match| !if_none_match.precondition_passes(&etag))
+            .unwrap_or(false)
+        {
+            Some(StatusCode::NOT_MODIFIED)
+        } else {
+            None
+        };
+        Conditional {
+            etag,
+            status,
+            reply,
+        }
+    }
+
+    /// Checks the preconditions of a request that modifies a resource.
+    ///
+    /// `etag` and `last_modified` describe the current version of the
+    /// resource. The request is rejected with a `412 Precondition Failed` if
+    /// the `if-match` header doesn't match the `etag`, strongly, or, when
+    /// there's no `if-match`, if the resource was modified after the
+    /// `if-unmodified-since` header. A precondition on a validator the
+    /// resource doesn't have fails, except for `if-match: *`, which passes
+    /// for any resource that exists.
+    ///
+    /// The resource is taken to exist if it has an `etag` or a
+    /// `last_modified` time, so pass `None` for both when it doesn't.
+    ///
+    /// This lets `PUT`, `PATCH` and `DELETE` handlers refuse to overwrite a
+    /// version the client hasn't seen. See
+    /// [`header::precondition`](crate::header::precondition) for a filter
+    /// when the current version is known up front.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    /// use warp::cache::CacheValidators;
+    ///
+    /// let route = warp::put()
+    ///     .and(warp::path!("user" / u32))
+    ///     .and(warp::cache::validators())
+    ///     .and_then(|id: u32, validators: CacheValidators| async move {
+    ///         let etag = format!("\"v{}\"", id).parse().unwrap();
+    ///         validators.precondition(Some(&etag), None)?;
+    ///         Ok::<_, warp::Rejection>(format!("updated user {}", id))
+    ///     });
+    /// ```
+    pub fn precondition(
+        &self,
+        etag: Option<&ETag>,
+        last_modified: Option<SystemTime>,
+    ) -> Result<(), Rejection> {
+        let passes = if let Some(ref if_match) = self.if_match {
+            let passes = match etag {
+                Some(etag) => if_match.precondition_passes(etag),
+                None => *if_match == IfMatch::any() && last_modified.is_some(),
+            };
+            tracing::trace!("if-match? {:?} vs {:?} = {}", if_match, etag, passes);
+            passes
+        } else if let Some(ref since) = self.if_unmodified_since {
+            let passes = last_modified
+                .map(|time| since.precondition_passes(time))
+                .unwrap_or(false);
+            tracing::trace!(
+                "if-unmodified-since? {:?} vs {:?} = {}",
+                since,
+                last_modified,
+                passes
+            );
+            passes
+        } else {
+            true
+        };
+
+        if passes {
+            Ok(())
+        } else {
+            tracing::debug!("precondition failed");
+            Err(reject::known(PreconditionFailed { _p: () }))
+        }
+    }
+
+    // Evaluates the preconditions for a representation with the
+    // `last_modified` time and `etag`, and the range to reply with.
+    pub(crate) fn check(self, last_modified: Option<LastModified>, etag: Option<&ETag>) -> Cond {
+        if let Some(since) = self.if_unmodified_since {
+            let precondition = last_modified
+                .map(|time| since.precondition_passes(time.into()))
+                .unwrap_or(false);
+
+            tracing::trace!(
+                "if-unmodified-since? {:?} vs {:?} = {}",
+                since,
+                last_modified,
+                precondition
+            );
+            if !precondition {
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = StatusCode::PRECONDITION_FAILED;
+                return Cond::NoBody(res);
+            }
+        }
+
+        // If-None-Match takes precedence over If-Modified-Since, when there's
+        // an ETag to compare.
+        let mut if_modified_since = self.if_modified_since;
+        if let (Some(if_none_match), Some(etag)) = (self.if_none_match, etag) {
+            let modified = if_none_match.precondition_passes(etag);
+            tracing::trace!(
+                "if-none-match? {:?} vs {:?} = {}",
+                if_none_match,
+                etag,
+                modified
+            );
+            if !modified {
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = StatusCode::NOT_MODIFIED;
+                return Cond::NoBody(res);
+            }
+            if_modified_since = None;
+        }
+
+        if let Some(since) = if_modified_since {
+            tracing::trace!(
+                "if-modified-since? header = {:?}, file = {:?}",
+                since,
+                last_modified
+            );
+            let unmodified = last_modified
+                .map(|time| !since.is_modified(time.into()))
+                // no last_modified means its always modified
+                .unwrap_or(false);
+            if unmodified {
+                let mut res = Response::new(Body::empty());
+                *res.status_mut() = StatusCode::NOT_MODIFIED;
+                return Cond::NoBody(res);
+            }
+        }
+
+        if let Some(if_range) = self.if_range {
+            tracing::trace!("if-range? {:?} vs {:?}", if_range, last_modified);
+            let can_range = !if_range.is_modified(etag, last_modified.as_ref());
+
+            if !can_range {
+                return Cond::WithBody(None);
+            }
+        }
+
+        Cond::WithBody(self.range)
+    }
+}
+
+pub(crate) enum Cond {
+    NoBody(Response),
+    WithBody(Option<Range>),
+}
+
+/// A reply validated against the conditional request headers.
+///
+/// Returned by [`CacheValidators::conditional`].
+#[derive(Debug)]
+pub struct Conditional<R> {
+    etag: ETag,
+    // Replaces the reply when a precondition is failed or not modified.
+    status: Option<StatusCode>,
+    reply: R,
+}
+
+impl<R: Reply> Reply for Conditional<R> {
+    fn into_response(self) -> Response {
+        let mut res = match self.status {
+            Some(StatusCode::PRECONDITION_FAILED) => {
+                return StatusCode::PRECONDITION_FAILED.into_response()
+            }
+            Some(status) => status.into_response(),
+            None => {
+                let res = self.reply.into_response();
+                if !res.status().is_success() {
+                    return res;
+                }
+                res
+            }
+        };
+        res.headers_mut().typed_insert(self.etag);
+        res
+    }
+}
+
+/// Wrap a `Filter` to validate its replies with a single, server-wide
+/// version, such as a build hash.
+///
+/// Successful replies get an `ETag: "<version>"` header. When a `GET` or
+/// `HEAD` request has an `If-None-Match` header matching it, a successful
+/// reply of the wrapped filter is replaced by a `304 Not Modified`.
+/// Rejections are left unchanged, so other routes combined with `or` still
+/// get a chance at the request.
+///
+/// Since every reply shares the same `ETag`, this fits content that only
+/// changes on deploy, like the shell of a single-page app. Changing the
+/// version makes every cached reply stale at once.
+///
+/// # Panics
+///
+/// Panics if `"<version>"` isn't a valid `ETag`, such as when `version`
+/// contains a `"` or non-ASCII characters.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let app = warp::path("app")
+///     .map(|| warp::reply::html("<!doctype html>..."))
+///     .with(warp::cache::versioned(env!("CARGO_PKG_VERSION")));
+/// ```
+pub fn versioned(version: &'static str) -> Versioned {
+    let etag = format!("\"{}\"", version)
+        .parse::<ETag>()
+        .unwrap_or_else(|_| panic!("invalid cache version: {:?}", version));
+    Versioned { etag }
+}
+
+/// A wrapping filter that validates replies with a server-wide version.
+///
+/// Returned by [`versioned`].
+#[derive(Clone)]
+pub struct Versioned {
+    etag: ETag,
+}
+
+impl<F> WrapSealed<F> for Versioned
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+{
+    type Wrapped = VersionedFilter<F>;
+
+    fn wrap(&self, inner: F) -> Self::Wrapped {
+        VersionedFilter {
+            etag: self.etag.clone(),
+            inner,
+        }
+    }
+}
+
+impl fmt::Debug for Versioned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Versioned")
+            .field("etag", &self.etag)
+            .finish()
+    }
+}
+
+unit_error! {
+    /// An error used to reject requests whose preconditions failed.
+    pub PreconditionFailed: "Precondition failed"
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_util::{ready, TryFuture};
+    use headers::{ETag, HeaderMapExt, IfNoneMatch};
+    use http::{Method, StatusCode};
+    use pin_project::pin_project;
+
+    use crate::filter::{Filter, FilterBase, Internal, One};
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[derive(Clone, Debug)]
+    pub struct VersionedFilter<F> {
+        pub(super) etag: ETag,
+        pub(super) inner: F,
+    }
+
+    impl<F> FilterBase for VersionedFilter<F>
+    where
+        F: Filter,
+        F::Extract: Send,
+        F::Future: Future,
+    {
+        type Extract = One<Validated<F::Extract>>;
+        type Error = F::Error;
+        type Future = ValidatedFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let not_modified = route::with(|route| {
+                let method = route.method();
+                if method != Method::GET && method != Method::HEAD {
+                    return false;
+                }
+                route
+                    .headers()
+                    .typed_get::<IfNoneMatch>()
+                    .map(|if_none_match| !if_none_match.precondition_passes(&self.etag))
+                    .unwrap_or(false)
+            });
+
+            ValidatedFuture {
+                etag: Some(self.etag.clone()),
+                not_modified,
+                inner: self.inner.filter(Internal),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct Validated<R> {
+        etag: ETag,
+        // Whether the client's copy is still fresh.
+        not_modified: bool,
+        inner: R,
+    }
+
+    impl<R: Reply> Reply for Validated<R> {
+        fn into_response(self) -> Response {
+            let res = self.inner.into_response();
+            if !res.status().is_success() {
+                return res;
+            }
+            let mut res = if self.not_modified {
+                tracing::trace!("cache version matches, not modified");
+                StatusCode::NOT_MODIFIED.into_response()
+            } else {
+                res
+            };
+            res.headers_mut().typed_insert(self.etag);
+            res
+        }
+    }
+
+    #[pin_project]
+    #[derive(Debug)]
+    pub struct ValidatedFuture<F> {
+        etag: Option<ETag>,
+        not_modified: bool,
+        #[pin]
+        inner: F,
+    }
+
+    impl<F> Future for ValidatedFuture<F>
+    where
+        F: TryFuture,
+    {
+        type Output = Result<One<Validated<F::Ok>>, F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let inner = ready!(pin.inner.try_poll(cx))?;
+            let etag = pin.etag.take().expect("polled after complete");
+            Poll::Ready(Ok((Validated {
+                etag,
+                not_modified: *pin.not_modified,
+                inner,
+            },)))
+        }
+    }
+}
@@ -21,6 +21,7 @@ use crate::reply::{Reply, Response};
 
 use self::internal::{CompressionProps, WithCompression};
 
+#[derive(Clone, Copy, Debug)]
 enum CompressionAlgo {
     #[cfg(feature = "compression-brotli")]
     BR,
@@ -30,24 +31,57 @@ enum CompressionAlgo {
     GZIP,
 }
 
-impl From<CompressionAlgo> for HeaderValue {
-    #[inline]
-    fn from(algo: CompressionAlgo) -> Self {
-        HeaderValue::from_static(match algo {
+impl CompressionAlgo {
+    fn as_str(self) -> &'static str {
+        match self {
             #[cfg(feature = "compression-brotli")]
             CompressionAlgo::BR => "br",
             #[cfg(feature = "compression-gzip")]
             CompressionAlgo::DEFLATE => "deflate",
             #[cfg(feature = "compression-gzip")]
             CompressionAlgo::GZIP => "gzip",
-        })
+        }
+    }
+}
+
+impl From<CompressionAlgo> for HeaderValue {
+    #[inline]
+    fn from(algo: CompressionAlgo) -> Self {
+        HeaderValue::from_static(algo.as_str())
     }
 }
 
 /// Compression
+///
+/// A response is only compressed if the request's `accept-encoding` header
+/// allows it, and the response doesn't already have a `content-encoding`.
 #[derive(Clone, Copy, Debug)]
 pub struct Compression<F> {
     func: F,
+    algo: CompressionAlgo,
+    min_size: u64,
+}
+
+impl<F> Compression<F> {
+    /// Sets the smallest body, in bytes, that will be compressed.
+    ///
+    /// Smaller bodies are sent as they are, since compressing them saves
+    /// little, if anything. Bodies of unknown length are always compressed.
+    /// Defaults to 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let route = warp::get()
+    ///     .and(warp::fs::dir("./static"))
+    ///     .with(warp::compression::gzip().min_size(1024));
+    /// ```
+    pub fn min_size(mut self, bytes: u64) -> Self {
+        self.min_size = bytes;
+        self
+    }
 }
 
 // TODO: The implementation of `gzip()`, `deflate()`, and `brotli()` could be replaced with
@@ -79,7 +113,11 @@ pub fn gzip() -> Compression<impl Fn(CompressionProps) -> Response + Copy> {
         props.head.headers.remove(CONTENT_LENGTH);
         Response::from_parts(props.head, body)
     };
-    Compression { func }
+    Compression {
+        func,
+        algo: CompressionAlgo::GZIP,
+        min_size: 0,
+    }
 }
 
 /// Create a wrapping filter that compresses the Body of a [`Response`](crate::reply::Response)
@@ -108,7 +146,11 @@ pub fn deflate() -> Compression<impl Fn(CompressionProps) -> Response + Copy> {
         props.head.headers.remove(CONTENT_LENGTH);
         Response::from_parts(props.head, body)
     };
-    Compression { func }
+    Compression {
+        func,
+        algo: CompressionAlgo::DEFLATE,
+        min_size: 0,
+    }
 }
 
 /// Create a wrapping filter that compresses the Body of a [`Response`](crate::reply::Response)
@@ -137,7 +179,11 @@ pub fn brotli() -> Compression<impl Fn(CompressionProps) -> Response + Copy> {
         props.head.headers.remove(CONTENT_LENGTH);
         Response::from_parts(props.head, body)
     };
-    Compression { func }
+    Compression {
+        func,
+        algo: CompressionAlgo::BR,
+        min_size: 0,
+    }
 }
 
 impl<FN, F> WrapSealed<F> for Compression<FN>
@@ -164,12 +210,16 @@ mod internal {
 
     use bytes::Bytes;
     use futures_util::{ready, Stream, TryFuture};
+    use http::header::{HeaderValue, CONTENT_ENCODING, CONTENT_LENGTH, VARY};
+    use hyper::body::HttpBody;
     use hyper::Body;
     use pin_project::pin_project;
 
     use crate::filter::{Filter, FilterBase, Internal};
+    use crate::filters::header::accepts_encoding;
     use crate::reject::IsReject;
     use crate::reply::{Reply, Response};
+    use crate::route;
 
     use super::Compression;
 
@@ -253,8 +303,11 @@ mod internal {
         type Future = WithCompressionFuture<FN, F::Future>;
 
         fn filter(&self, _: Internal) -> Self::Future {
+            let encoding = self.compress.algo.as_str();
+            let accepted = route::with(|route| accepts_encoding(route.headers(), &[encoding]));
             WithCompressionFuture {
                 compress: self.compress.clone(),
+                accepted,
                 future: self.filter.filter(Internal),
             }
         }
@@ -264,6 +317,8 @@ mod internal {
     #[pin_project]
     pub struct WithCompressionFuture<FN, F> {
         compress: Compression<FN>,
+        // Whether the request's `accept-encoding` allows the algorithm.
+        accepted: bool,
         #[pin]
         future: F,
     }
@@ -282,11 +337,40 @@ mod internal {
             let result = ready!(pin.future.try_poll(cx));
             match result {
                 Ok(reply) => {
-                    let resp = (self.compress.func)(reply.into_response().into());
+                    let mut resp = reply.into_response();
+                    if resp.headers().contains_key(CONTENT_ENCODING) {
+                        tracing::trace!("response is already encoded, not compressing");
+                        return Poll::Ready(Ok((Compressed(resp),)));
+                    }
+                    // Whether or not this response is compressed, others for
+                    // the same request may be, so caches must tell them apart.
+                    resp.headers_mut()
+                        .append(VARY, HeaderValue::from_static("accept-encoding"));
+                    if self.accepted && self.should_compress(&resp) {
+                        resp = (self.compress.func)(resp.into());
+                    }
                     Poll::Ready(Ok((Compressed(resp),)))
                 }
                 Err(reject) => Poll::Ready(Err(reject)),
             }
         }
     }
+
+    impl<FN, F> WithCompressionFuture<FN, F> {
+        fn should_compress(&self, resp: &Response) -> bool {
+            let len = HttpBody::size_hint(resp.body()).exact().or_else(|| {
+                resp.headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|len| len.to_str().ok())
+                    .and_then(|len| len.parse().ok())
+            });
+            match len {
+                Some(len) if len < self.compress.min_size => {
+                    tracing::trace!("response is below min_size, not compressing");
+                    false
+                }
+                _ => true,
+            }
+        }
+    }
 }
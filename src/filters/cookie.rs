@@ -2,10 +2,12 @@
 
 use futures_util::future;
 use headers::Cookie;
+use serde::de::DeserializeOwned;
 
 use super::header;
 use crate::filter::{Filter, One};
-use crate::reject::Rejection;
+use crate::reject::{self, Rejection};
+use std::borrow::Cow;
 use std::convert::Infallible;
 use std::str::FromStr;
 
@@ -44,3 +46,317 @@ where
         }
     })
 }
+
+/// Creates a `Filter` that requires a percent-encoded cookie by name.
+///
+/// Like [`cookie`](./fn.cookie.html), but the value is percent-decoded first,
+/// as [`reply::Cookie`](crate::reply::Cookie) encodes it.
+pub fn decoded<T>(name: &'static str) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy
+where
+    T: FromStr + Send + 'static,
+{
+    header::header2().and_then(move |cookie: Cookie| {
+        let cookie = cookie
+            .get(name)
+            .ok_or_else(|| crate::reject::missing_cookie(name))
+            .and_then(|s| {
+                T::from_str(&decode_value(s)).map_err(|_| crate::reject::missing_cookie(name))
+            });
+        future::ready(cookie)
+    })
+}
+
+/// Creates a `Filter` that deserializes all the cookies of a request into a
+/// `T`, treating them as a map of names to values.
+///
+/// Values are percent-decoded, and deserialized like a query string, so they
+/// can be strings, numbers, booleans, and so on. Cookies without a matching field are
+/// ignored, and fields without a cookie must be optional, or have a
+/// `#[serde(default)]`. If a cookie has an invalid value, or a required one
+/// is missing, the request is rejected with a `400 Bad Request`.
+///
+/// # Example
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// struct Prefs {
+///     #[serde(default)]
+///     theme: Option<String>,
+///     #[serde(default = "default_page_size")]
+///     page_size: u32,
+/// }
+///
+/// fn default_page_size() -> u32 {
+///     20
+/// }
+///
+/// let route = warp::cookie::deserialize()
+///     .map(|prefs: Prefs| {
+///         format!("{:?} theme, {} per page", prefs.theme, prefs.page_size)
+///     });
+/// ```
+pub fn deserialize<T>() -> impl Filter<Extract = One<T>, Error = Rejection> + Copy
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    header::optional2().and_then(|opt: Option<Cookie>| {
+        let pairs = opt
+            .as_ref()
+            .map(|cookie| {
+                cookie
+                    .iter()
+                    .map(|(name, value)| (name, decode_value(value)))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        // Reuse the query string deserializer for values like numbers.
+        let result = serde_urlencoded::to_string(&pairs)
+            .map_err(|err| err.to_string())
+            .and_then(|encoded| serde_urlencoded::from_str(&encoded).map_err(|err| err.to_string()))
+            .map_err(|err| {
+                tracing::debug!("failed to deserialize cookies: {}", err);
+                reject::known(InvalidCookie { _p: () })
+            });
+        future::ready(result)
+    })
+}
+
+// Percent-decodes a value, as encoded by `reply::Cookie`. Values that don't
+// decode to UTF-8 are used as they are.
+fn decode_value(value: &str) -> Cow<'_, str> {
+    percent_encoding::percent_decode_str(value)
+        .decode_utf8()
+        .unwrap_or(Cow::Borrowed(value))
+}
+
+unit_error! {
+    /// An error used to reject requests with cookies that can't be deserialized.
+    pub InvalidCookie: "Invalid request cookies"
+}
+
+/// A secret key to sign or encrypt cookies with.
+///
+/// Create it once, and clone it into the filters and handlers that need it.
+/// Cookies signed or encrypted with one key can only be read with the same
+/// key, so it needs to be kept between restarts of the server, and shared by
+/// all the servers behind a load balancer.
+///
+/// *This type requires the `"sign"` feature.*
+#[cfg(feature = "sign")]
+#[derive(Clone)]
+pub struct Key {
+    signing: ring::hmac::Key,
+    encryption: std::sync::Arc<ring::aead::LessSafeKey>,
+}
+
+#[cfg(feature = "sign")]
+impl Key {
+    /// Derives a key from a secret.
+    ///
+    /// The secret should be at least 32 random bytes. Separate keys for
+    /// signing and for encryption are derived from it with HKDF.
+    pub fn new(secret: impl AsRef<[u8]>) -> Key {
+        use ring::{aead, hkdf, hmac};
+
+        let prk = hkdf::Salt::new(hkdf::HKDF_SHA256, b"warp cookie").extract(secret.as_ref());
+        let signing = prk
+            .expand(&[b"signing"], hmac::HMAC_SHA256)
+            .expect("hkdf output length")
+            .into();
+        let encryption: aead::UnboundKey = prk
+            .expand(&[b"encryption"], &aead::AES_256_GCM)
+            .expect("hkdf output length")
+            .into();
+        Key {
+            signing,
+            encryption: std::sync::Arc::new(aead::LessSafeKey::new(encryption)),
+        }
+    }
+
+    /// Generates a random key.
+    ///
+    /// Useful for tests, or when cookies don't need to outlive the process.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system's random number generator fails.
+    pub fn generate() -> Key {
+        let mut secret = [0u8; 32];
+        getrandom::getrandom(&mut secret).expect("cookie key randomness");
+        Key::new(secret)
+    }
+
+    /// Signs the value of a cookie, to be read with
+    /// [`signed`](./fn.signed.html).
+    ///
+    /// The value is still readable by the client, but can't be changed by it.
+    /// The signature covers the name of the cookie too, so a value can't be
+    /// moved to another cookie either.
+    pub fn sign(&self, mut cookie: crate::reply::Cookie) -> crate::reply::Cookie {
+        let tag = ring::hmac::sign(&self.signing, &signed_message(&cookie.name, &cookie.value));
+        cookie.value = format!("{}.{}", cookie.value, encode_hex(tag.as_ref()));
+        cookie
+    }
+
+    /// Encrypts the value of a cookie, to be read with
+    /// [`encrypted`](./fn.encrypted.html).
+    ///
+    /// The value can neither be read nor changed by the client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the system's random number generator fails.
+    pub fn encrypt(&self, mut cookie: crate::reply::Cookie) -> crate::reply::Cookie {
+        use ring::aead;
+
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        getrandom::getrandom(&mut nonce).expect("cookie nonce randomness");
+        let mut sealed = std::mem::take(&mut cookie.value).into_bytes();
+        self.encryption
+            .seal_in_place_append_tag(
+                aead::Nonce::assume_unique_for_key(nonce),
+                aead::Aad::from(cookie.name.as_bytes()),
+                &mut sealed,
+            )
+            .expect("cookie value too large to encrypt");
+        cookie.value = format!("{}{}", encode_hex(&nonce), encode_hex(&sealed));
+        cookie
+    }
+
+    fn verify(&self, name: &str, value: &str) -> Option<String> {
+        let value = percent_encoding::percent_decode_str(value)
+            .decode_utf8()
+            .ok()?;
+        let (value, tag) = value.rsplit_once('.')?;
+        let tag = crate::sign::decode_hex(tag.as_bytes())?;
+        ring::hmac::verify(&self.signing, &signed_message(name, value), &tag).ok()?;
+        Some(value.to_owned())
+    }
+
+    fn decrypt(&self, name: &str, value: &str) -> Option<String> {
+        use ring::aead;
+
+        let mut sealed = crate::sign::decode_hex(value.as_bytes())?;
+        if sealed.len() < aead::NONCE_LEN {
+            return None;
+        }
+        let nonce = aead::Nonce::try_assume_unique_for_key(&sealed[..aead::NONCE_LEN]).ok()?;
+        let opened = self
+            .encryption
+            .open_in_place(
+                nonce,
+                aead::Aad::from(name.as_bytes()),
+                &mut sealed[aead::NONCE_LEN..],
+            )
+            .ok()?;
+        String::from_utf8(opened.to_vec()).ok()
+    }
+}
+
+#[cfg(feature = "sign")]
+impl std::fmt::Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Skip showing the secret.
+        f.debug_struct("Key").finish_non_exhaustive()
+    }
+}
+
+/// Creates a `Filter` that requires a cookie signed with `key`, by name.
+///
+/// If found and the signature is valid, extracts the value of the cookie.
+/// Rejects with a `400 Bad Request` if the signature doesn't match, and like
+/// [`cookie`](./fn.cookie.html) otherwise.
+///
+/// *This function requires the `"sign"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::cookie::Key;
+/// use warp::reply::Cookie;
+///
+/// let key = Key::new("a secret that is at least 32 bytes long");
+///
+/// let login = {
+///     let key = key.clone();
+///     warp::path("login").map(move || {
+///         let session = key.sign(Cookie::new("user", "sean").path("/"));
+///         warp::reply::with_cookie(warp::reply(), session)
+///     })
+/// };
+/// let profile = warp::path("profile")
+///     .and(warp::cookie::signed(key, "user"))
+///     .map(|user: String| format!("Hello, {}", user));
+/// ```
+#[cfg(feature = "sign")]
+pub fn signed<T>(
+    key: Key,
+    name: &'static str,
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Clone
+where
+    T: FromStr + Send + 'static,
+{
+    header::header2().and_then(move |cookie: Cookie| {
+        let value = cookie
+            .get(name)
+            .ok_or_else(|| crate::reject::missing_cookie(name))
+            .and_then(|value| key.verify(name, value).ok_or_else(tampered))
+            .and_then(|s| T::from_str(&s).map_err(|_| crate::reject::missing_cookie(name)));
+        future::ready(value)
+    })
+}
+
+/// Creates a `Filter` that requires a cookie encrypted with `key`, by name.
+///
+/// If found and it decrypts, extracts the value of the cookie. Rejects with
+/// a `400 Bad Request` if it doesn't, and like [`cookie`](./fn.cookie.html)
+/// otherwise.
+///
+/// Values are encrypted with AES-256-GCM, using the name of the cookie as
+/// associated data.
+///
+/// *This function requires the `"sign"` feature.*
+#[cfg(feature = "sign")]
+pub fn encrypted<T>(
+    key: Key,
+    name: &'static str,
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Clone
+where
+    T: FromStr + Send + 'static,
+{
+    header::header2().and_then(move |cookie: Cookie| {
+        let value = cookie
+            .get(name)
+            .ok_or_else(|| crate::reject::missing_cookie(name))
+            .and_then(|value| key.decrypt(name, value).ok_or_else(tampered))
+            .and_then(|s| T::from_str(&s).map_err(|_| crate::reject::missing_cookie(name)));
+        future::ready(value)
+    })
+}
+
+#[cfg(feature = "sign")]
+fn signed_message(name: &str, value: &str) -> Vec<u8> {
+    format!("{}={}", name, value).into_bytes()
+}
+
+#[cfg(feature = "sign")]
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(feature = "sign")]
+fn tampered() -> Rejection {
+    tracing::debug!("cookie signature or encryption doesn't match");
+    reject::known(TamperedCookie { _p: () })
+}
+
+#[cfg(feature = "sign")]
+unit_error! {
+    /// An error used to reject requests with a signed or encrypted cookie
+    /// that fails to verify.
+    pub TamperedCookie: "Invalid signed or encrypted cookie"
+}
@@ -0,0 +1,145 @@
+//! CSRF Filters
+//!
+//! Filters implementing the "double-submit cookie" defense against cross-site
+//! request forgery. A random token is set as a cookie, and scripts of the same
+//! origin send it back in a request header. Since another site can't read the
+//! cookie, it can't forge a request with a matching header.
+
+use std::fmt;
+
+use futures_util::future;
+use headers::{Cookie, HeaderMapExt};
+use http::header::{HeaderValue, SET_COOKIE};
+
+use crate::filter::{filter_fn, Filter};
+use crate::reject::{self, Rejection};
+use crate::reply::{Reply, Response};
+
+/// Create a `Filter` that requires the CSRF token of a cookie and a header
+/// to match.
+///
+/// The request is rejected with a `403 Forbidden` if either the cookie or the
+/// header is missing, or if their values aren't equal. The values are compared
+/// in constant time.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let update = warp::post()
+///     .and(warp::csrf::double_submit("csrf-token", "x-csrf-token"))
+///     .map(|| "updated");
+/// ```
+pub fn double_submit(
+    cookie_name: &'static str,
+    header_name: &'static str,
+) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        tracing::trace!("csrf::double_submit({:?}, {:?})", cookie_name, header_name);
+        let cookie = route.headers().typed_get::<Cookie>();
+        let cookie = cookie.as_ref().and_then(|cookie| cookie.get(cookie_name));
+        let header = route.headers().get(header_name);
+
+        match (cookie, header) {
+            (Some(cookie), Some(header))
+                if constant_time_eq(cookie.as_bytes(), header.as_bytes()) =>
+            {
+                future::ok(())
+            }
+            (Some(_), Some(_)) => {
+                tracing::debug!("csrf token in cookie and header don't match");
+                future::err(reject::known(CsrfForbidden { _p: () }))
+            }
+            _ => {
+                tracing::debug!("csrf token missing from cookie or header");
+                future::err(reject::known(CsrfForbidden { _p: () }))
+            }
+        }
+    })
+}
+
+/// Wrap an `impl Reply` to set a cookie with a fresh CSRF token.
+///
+/// The cookie is set with `Path=/`, `Secure`, and `SameSite=Strict`. It is
+/// intentionally *not* `HttpOnly`, since scripts need to read the token to
+/// send it back in a header.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let page = warp::get()
+///     .map(|| warp::reply::html("<script>/* ... */</script>"))
+///     .map(|reply| warp::csrf::with_token(reply, "csrf-token"));
+/// ```
+pub fn with_token<T: Reply>(reply: T, cookie_name: &'static str) -> WithToken<T> {
+    WithToken {
+        cookie_name,
+        reply,
+        token: token(),
+    }
+}
+
+/// Wraps an `impl Reply` and sets a CSRF cookie when rendering.
+///
+/// Returned by `warp::csrf::with_token`.
+pub struct WithToken<T> {
+    cookie_name: &'static str,
+    reply: T,
+    token: Option<String>,
+}
+
+impl<T: Reply> Reply for WithToken<T> {
+    fn into_response(self) -> Response {
+        let mut res = self.reply.into_response();
+        let cookie_name = self.cookie_name;
+        let cookie = self.token.and_then(|token| {
+            let cookie = format!("{}={}; Path=/; Secure; SameSite=Strict", cookie_name, token);
+            HeaderValue::from_str(&cookie)
+                .map_err(|err| tracing::error!("csrf cookie error: {}", err))
+                .ok()
+        });
+        match cookie {
+            Some(cookie) => {
+                res.headers_mut().append(SET_COOKIE, cookie);
+                res
+            }
+            None => http::StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for WithToken<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Skip showing the token itself.
+        f.debug_struct("WithToken")
+            .field("cookie_name", &self.cookie_name)
+            .field("reply", &self.reply)
+            .finish()
+    }
+}
+
+// Generates a random token, hex encoded.
+fn token() -> Option<String> {
+    let mut bytes = [0u8; 32];
+    if let Err(err) = getrandom::getrandom(&mut bytes) {
+        tracing::error!("csrf token error: {}", err);
+        return None;
+    }
+    Some(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    // The length of the token isn't a secret.
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+unit_error! {
+    /// An error used to reject requests that fail a CSRF check.
+    pub CsrfForbidden: "CSRF token missing or invalid"
+}
@@ -6,6 +6,7 @@ use futures_util::future;
 
 use crate::filter::{filter_fn_one, Filter};
 use crate::reject::{self, Rejection};
+use crate::route;
 
 /// Get a previously set extension of the current route.
 ///
@@ -30,6 +31,37 @@ pub fn optional<T: Clone + Send + Sync + 'static>(
     filter_fn_one(|route| future::ok(route.extensions().get::<T>().cloned()))
 }
 
+/// Set an extension of the current route to a clone of `value`.
+///
+/// Filters and handlers after this one in the chain can read it with
+/// [`get`] or [`optional`]. An extension of the same type that was already
+/// set is replaced.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// #[derive(Clone)]
+/// struct Tenant(&'static str);
+///
+/// let route = warp::path("acme")
+///     .and(warp::ext::set(Tenant("acme")))
+///     .and(warp::ext::get::<Tenant>())
+///     .map(|tenant: Tenant| format!("hello {}", tenant.0));
+/// ```
+pub fn set<T: Clone + Send + Sync + 'static>(
+    value: T,
+) -> impl Filter<Extract = (), Error = Infallible> + Clone {
+    crate::any()
+        .map(move || {
+            route::with(|route| {
+                route.extensions_mut().insert(value.clone());
+            })
+        })
+        .untuple_one()
+}
+
 unit_error! {
     /// An error used to reject if `get` cannot find the extension.
     pub MissingExtension: "Missing request extension"
@@ -1,7 +1,6 @@
 //! File System Filters
 
 use std::cmp;
-use std::convert::Infallible;
 use std::fs::Metadata;
 use std::future::Future;
 use std::io;
@@ -9,24 +8,26 @@ use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::Poll;
+use std::time::SystemTime;
 
 use bytes::{Bytes, BytesMut};
 use futures_util::future::Either;
 use futures_util::{future, ready, stream, FutureExt, Stream, StreamExt, TryFutureExt};
-use headers::{
-    AcceptRanges, ContentLength, ContentRange, ContentType, HeaderMapExt, IfModifiedSince, IfRange,
-    IfUnmodifiedSince, LastModified, Range,
-};
+use headers::{ContentType, HeaderMapExt, LastModified};
+use http::header::{HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_DISPOSITION};
 use http::StatusCode;
 use hyper::Body;
-use percent_encoding::percent_decode_str;
+use percent_encoding::{
+    percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS, NON_ALPHANUMERIC,
+};
 use tokio::fs::File as TkFile;
 use tokio::io::AsyncSeekExt;
 use tokio_util::io::poll_read_buf;
 
+use crate::cache::{CacheValidators, Cond};
 use crate::filter::{Filter, FilterClone, One};
 use crate::reject::{self, Rejection};
-use crate::reply::{Reply, Response};
+use crate::reply::{range_response, Reply, Response};
 
 /// Creates a `Filter` that serves a File at the `path`.
 ///
@@ -45,14 +46,34 @@ use crate::reply::{Reply, Response};
 /// let route = warp::fs::file("/www/static/app.js");
 /// ```
 pub fn file(path: impl Into<PathBuf>) -> impl FilterClone<Extract = One<File>, Error = Rejection> {
+    file_with_config(path, Config::default())
+}
+
+/// Creates a `Filter` that serves a File at the `path` like [`file`],
+/// configured by `config`.
+///
+/// # Example
+///
+/// ```
+/// // Serves the report as a download named `report-2024.csv`.
+/// let config = warp::fs::Config::new().attachment("report-2024.csv".to_owned());
+/// let route = warp::fs::file_with_config("/var/reports/latest.csv", config);
+/// ```
+pub fn file_with_config(
+    path: impl Into<PathBuf>,
+    config: Config,
+) -> impl FilterClone<Extract = One<File>, Error = Rejection> {
     let path = Arc::new(path.into());
+    let disposition = config.attachment.as_deref().map(attachment_disposition);
+    let immutable = config.immutable;
     crate::any()
         .map(move || {
             tracing::trace!("file: {:?}", path);
             ArcPath(path.clone())
         })
-        .and(conditionals())
-        .and_then(file_reply)
+        .and(crate::cache::validators())
+        .and_then(move |path, conditionals| file_reply(path, conditionals, disposition.clone()))
+        .map(move |file: File| file.immutable_if(immutable))
 }
 
 /// Creates a `Filter` that serves a directory at the base `path` joined
@@ -78,33 +99,260 @@ pub fn file(path: impl Into<PathBuf>) -> impl FilterClone<Extract = One<File>, E
 /// // - `GET /static/css/app.css` would serve the file `/www/static/css/app.css`
 /// ```
 pub fn dir(path: impl Into<PathBuf>) -> impl FilterClone<Extract = One<File>, Error = Rejection> {
+    dir_with_config(path, Config::default())
+}
+
+/// Creates a `Filter` that serves a directory like [`dir`], configured by
+/// `config`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Serves `/www/files`, listing the contents of directories that have
+/// // no `default.htm`.
+/// let config = warp::fs::Config::new()
+///     .index_file("default.htm".to_owned())
+///     .directory_listing(true);
+/// let route = warp::path("files")
+///     .and(warp::fs::dir_with_config("/www/files", config));
+/// ```
+pub fn dir_with_config(
+    path: impl Into<PathBuf>,
+    config: Config,
+) -> impl FilterClone<Extract = One<File>, Error = Rejection> {
+    let base = Arc::new(path.into());
+    let immutable = config.immutable;
+    let listing = config.directory_listing;
+    crate::get()
+        .or(crate::head())
+        .unify()
+        .and(path_from_tail(base, Arc::new(config)))
+        .and(crate::path::full())
+        .and(crate::cache::validators())
+        .and_then(
+            move |path: ArcPath,
+                  is_dir: bool,
+                  full: crate::path::FullPath,
+                  conditionals: CacheValidators| async move {
+                if !is_dir {
+                    return file_reply(path, conditionals, None).await;
+                }
+                if listing {
+                    return listing_reply(path, full.as_str()).await;
+                }
+                tracing::debug!("dir: no index file for {:?}", path.as_ref());
+                Err(reject::not_found())
+            },
+        )
+        .map(move |file: File| file.immutable_if(immutable))
+}
+
+/// How [`dir_with_config`] serves a directory.
+#[derive(Clone, Debug)]
+pub struct Config {
+    index_file: Option<String>,
+    directory_listing: bool,
+    attachment: Option<String>,
+    immutable: bool,
+}
+
+impl Config {
+    /// Creates the default configuration, the same as used by [`dir`].
+    pub fn new() -> Self {
+        Config {
+            index_file: Some("index.html".to_owned()),
+            directory_listing: false,
+            attachment: None,
+            immutable: false,
+        }
+    }
+
+    /// Sets the name of the file served for requests of a directory.
+    ///
+    /// `index_file(None)` means that no file is served in place of a
+    /// directory. Defaults to `index.html`.
+    pub fn index_file(mut self, name: impl Into<Option<String>>) -> Self {
+        self.index_file = name.into();
+        self
+    }
+
+    /// Sets whether to list the contents of a directory that doesn't have an
+    /// index file.
+    ///
+    /// The listing is an HTML page with the name, size and modification time
+    /// of each entry. Defaults to `false`.
+    pub fn directory_listing(mut self, enabled: bool) -> Self {
+        self.directory_listing = enabled;
+        self
+    }
+
+    /// Sets the name a file is downloaded as.
+    ///
+    /// When set, files are served with a `content-disposition: attachment`,
+    /// so that browsers save them instead of displaying them. Names that
+    /// aren't plain ASCII are also encoded as described in RFC 5987, with an
+    /// ASCII fallback for older clients. Defaults to `None`.
+    ///
+    /// Since it names a single file, this only applies to
+    /// [`file_with_config`], and is ignored by [`dir_with_config`].
+    pub fn attachment(mut self, name: impl Into<Option<String>>) -> Self {
+        self.attachment = name.into();
+        self
+    }
+
+    /// Sets whether served files are cached forever.
+    ///
+    /// When enabled, files are served with
+    /// `cache-control: public, max-age=31536000, immutable`, which suits
+    /// directories of "fingerprinted" assets, whose file names change
+    /// whenever their contents do. To only mark some files, see
+    /// [`File::immutable`]. Defaults to `false`.
+    pub fn immutable(mut self, enabled: bool) -> Self {
+        self.immutable = enabled;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::new()
+    }
+}
+
+/// Creates a `Filter` that serves a directory like [`dir`], but only the
+/// files that `authorize` allows.
+///
+/// Once the request path has been resolved to a file, and the file's metadata
+/// read, `authorize` is called with a [`Context`] describing the request and
+/// the file. If the future it returns resolves to `false`, the request is
+/// rejected with a `403 Forbidden`, without opening the file.
+///
+/// This allows per-file access control, such as checking that the requesting
+/// user owns a file, using the same path resolution as `dir`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Only serve files under `/www/private/<user>/` to that user.
+/// let route = warp::path("private").and(warp::fs::dir_authorized(
+///     "/www/private",
+///     |ctx: &warp::fs::Context| {
+///         let user = ctx
+///             .headers()
+///             .get("x-user")
+///             .and_then(|v| v.to_str().ok())
+///             .map(|user| std::path::Path::new("/www/private").join(user));
+///         let allowed = match user {
+///             Some(dir) => ctx.path().starts_with(dir),
+///             None => false,
+///         };
+///         async move { allowed }
+///     },
+/// ));
+/// ```
+pub fn dir_authorized<F, Fut>(
+    path: impl Into<PathBuf>,
+    authorize: F,
+) -> impl FilterClone<Extract = One<File>, Error = Rejection>
+where
+    F: Fn(&Context) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = bool> + Send + 'static,
+{
     let base = Arc::new(path.into());
+    let authorize = Arc::new(authorize);
     crate::get()
         .or(crate::head())
         .unify()
-        .and(path_from_tail(base))
-        .and(conditionals())
-        .and_then(file_reply)
+        .and(path_from_tail(base, Arc::new(Config::new())))
+        .and(crate::header::headers_cloned())
+        .and_then(move |path: ArcPath, _is_dir: bool, headers: HeaderMap| {
+            let authorize = authorize.clone();
+            async move {
+                let metadata = match tokio::fs::metadata(path.as_ref()).await {
+                    Ok(metadata) => metadata,
+                    Err(err) => {
+                        tracing::debug!("file metadata error: {}", err);
+                        return Err(reject::not_found());
+                    }
+                };
+                let ctx = Context {
+                    path,
+                    metadata,
+                    headers,
+                };
+                if authorize(&ctx).await {
+                    Ok(ctx.path)
+                } else {
+                    tracing::debug!("file not authorized: {:?}", ctx.path.as_ref().display());
+                    Err(reject::known(FilePermissionError { _p: () }))
+                }
+            }
+        })
+        .and(crate::cache::validators())
+        .and_then(|path, conditionals| file_reply(path, conditionals, None))
 }
 
+/// The request and resolved file that [`dir_authorized`] asks to authorize.
+#[derive(Debug)]
+pub struct Context {
+    path: ArcPath,
+    metadata: Metadata,
+    headers: HeaderMap,
+}
+
+impl Context {
+    /// The path of the requested file.
+    pub fn path(&self) -> &Path {
+        self.path.as_ref()
+    }
+
+    /// The metadata of the requested file.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// The headers of the request.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+// Resolves the request path under `base`, replacing a directory with its
+// index file. The directory is kept when there's no index file, or when it's
+// missing and the directory can be listed instead, extracting whether it was.
 fn path_from_tail(
     base: Arc<PathBuf>,
-) -> impl FilterClone<Extract = One<ArcPath>, Error = Rejection> {
-    crate::path::tail().and_then(move |tail: crate::path::Tail| {
-        future::ready(sanitize_path(base.as_ref(), tail.as_str())).and_then(|mut buf| async {
-            let is_dir = tokio::fs::metadata(buf.clone())
-                .await
-                .map(|m| m.is_dir())
-                .unwrap_or(false);
-
-            if is_dir {
-                tracing::debug!("dir: appending index.html to directory path");
-                buf.push("index.html");
-            }
-            tracing::trace!("dir: {:?}", buf);
-            Ok(ArcPath(Arc::new(buf)))
+    config: Arc<Config>,
+) -> impl FilterClone<Extract = (ArcPath, bool), Error = Rejection> {
+    crate::path::tail()
+        .and_then(move |tail: crate::path::Tail| {
+            let config = config.clone();
+            future::ready(sanitize_path(base.as_ref(), tail.as_str())).and_then(|buf| async move {
+                let is_dir = tokio::fs::metadata(buf.clone())
+                    .await
+                    .map(|m| m.is_dir())
+                    .unwrap_or(false);
+
+                if is_dir {
+                    if let Some(ref index_file) = config.index_file {
+                        let index = buf.join(index_file);
+                        // Without a listing to fall back to, a missing index
+                        // is simply not found.
+                        if !config.directory_listing || tokio::fs::metadata(&index).await.is_ok() {
+                            tracing::debug!("dir: appending {} to directory path", index_file);
+                            return Ok((ArcPath(Arc::new(index)), false));
+                        }
+                    }
+                }
+                tracing::trace!("dir: {:?}", buf);
+                Ok((ArcPath(Arc::new(buf)), is_dir))
+            })
         })
-    })
+        .untuple_one()
 }
 
 fn sanitize_path(base: impl AsRef<Path>, tail: &str) -> Result<PathBuf, Rejection> {
@@ -134,84 +382,6 @@ fn sanitize_path(base: impl AsRef<Path>, tail: &str) -> Result<PathBuf, Rejectio
     Ok(buf)
 }
 
-#[derive(Debug)]
-struct Conditionals {
-    if_modified_since: Option<IfModifiedSince>,
-    if_unmodified_since: Option<IfUnmodifiedSince>,
-    if_range: Option<IfRange>,
-    range: Option<Range>,
-}
-
-enum Cond {
-    NoBody(Response),
-    WithBody(Option<Range>),
-}
-
-impl Conditionals {
-    fn check(self, last_modified: Option<LastModified>) -> Cond {
-        if let Some(since) = self.if_unmodified_since {
-            let precondition = last_modified
-                .map(|time| since.precondition_passes(time.into()))
-                .unwrap_or(false);
-
-            tracing::trace!(
-                "if-unmodified-since? {:?} vs {:?} = {}",
-                since,
-                last_modified,
-                precondition
-            );
-            if !precondition {
-                let mut res = Response::new(Body::empty());
-                *res.status_mut() = StatusCode::PRECONDITION_FAILED;
-                return Cond::NoBody(res);
-            }
-        }
-
-        if let Some(since) = self.if_modified_since {
-            tracing::trace!(
-                "if-modified-since? header = {:?}, file = {:?}",
-                since,
-                last_modified
-            );
-            let unmodified = last_modified
-                .map(|time| !since.is_modified(time.into()))
-                // no last_modified means its always modified
-                .unwrap_or(false);
-            if unmodified {
-                let mut res = Response::new(Body::empty());
-                *res.status_mut() = StatusCode::NOT_MODIFIED;
-                return Cond::NoBody(res);
-            }
-        }
-
-        if let Some(if_range) = self.if_range {
-            tracing::trace!("if-range? {:?} vs {:?}", if_range, last_modified);
-            let can_range = !if_range.is_modified(None, last_modified.as_ref());
-
-            if !can_range {
-                return Cond::WithBody(None);
-            }
-        }
-
-        Cond::WithBody(self.range)
-    }
-}
-
-fn conditionals() -> impl Filter<Extract = One<Conditionals>, Error = Infallible> + Copy {
-    crate::header::optional2()
-        .and(crate::header::optional2())
-        .and(crate::header::optional2())
-        .and(crate::header::optional2())
-        .map(
-            |if_modified_since, if_unmodified_since, if_range, range| Conditionals {
-                if_modified_since,
-                if_unmodified_since,
-                if_range,
-                range,
-            },
-        )
-}
-
 /// A file response.
 #[derive(Debug)]
 pub struct File {
@@ -242,6 +412,57 @@ impl File {
     pub fn path(&self) -> &Path {
         self.path.as_ref()
     }
+
+    /// Mark this file as immutable, allowing it to be cached forever.
+    ///
+    /// This adds a `cache-control: public, max-age=31536000, immutable`
+    /// header, which is appropriate for "fingerprinted" assets, where the
+    /// file name changes whenever its contents do. It can still be
+    /// overridden by setting the header again afterwards.
+    ///
+    /// To mark every file a filter serves, see [`Config::immutable`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// // Only files like `app.8f3a2c.js` are marked immutable.
+    /// fn is_fingerprinted(path: &std::path::Path) -> bool {
+    ///     path.file_name()
+    ///         .and_then(|name| name.to_str())
+    ///         .map(|name| name.split('.').count() > 2)
+    ///         .unwrap_or(false)
+    /// }
+    ///
+    /// let route = warp::path("static")
+    ///     .and(warp::fs::dir("/www/static"))
+    ///     .map(|file: warp::filters::fs::File| {
+    ///         if is_fingerprinted(file.path()) {
+    ///             file.immutable()
+    ///         } else {
+    ///             file
+    ///         }
+    ///     });
+    /// ```
+    pub fn immutable(mut self) -> Self {
+        let status = self.resp.status();
+        if status.is_success() || status == StatusCode::NOT_MODIFIED {
+            self.resp.headers_mut().insert(
+                CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=31536000, immutable"),
+            );
+        }
+        self
+    }
+
+    fn immutable_if(self, enabled: bool) -> Self {
+        if enabled {
+            self.immutable()
+        } else {
+            self
+        }
+    }
 }
 
 // Silly wrapper since Arc<PathBuf> doesn't implement AsRef<Path> ;_;
@@ -262,10 +483,11 @@ impl Reply for File {
 
 fn file_reply(
     path: ArcPath,
-    conditionals: Conditionals,
+    conditionals: CacheValidators,
+    disposition: Option<HeaderValue>,
 ) -> impl Future<Output = Result<File, Rejection>> + Send {
     TkFile::open(path.clone()).then(move |res| match res {
-        Ok(f) => Either::Left(file_conditional(f, path, conditionals)),
+        Ok(f) => Either::Left(file_conditional(f, path, conditionals, disposition)),
         Err(err) => {
             let rej = match err.kind() {
                 io::ErrorKind::NotFound => {
@@ -290,6 +512,177 @@ fn file_reply(
     })
 }
 
+// Characters escaped in RFC 5987 values, all but `attr-char`.
+const ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!')
+    .remove(b'#')
+    .remove(b'$')
+    .remove(b'&')
+    .remove(b'+')
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'^')
+    .remove(b'_')
+    .remove(b'`')
+    .remove(b'|')
+    .remove(b'~');
+
+// The `content-disposition` of a download named `name`.
+fn attachment_disposition(name: &str) -> HeaderValue {
+    let is_plain = |c: char| (c.is_ascii_graphic() || c == ' ') && c != '"' && c != '\\';
+    let fallback = name
+        .chars()
+        .map(|c| if is_plain(c) { c } else { '_' })
+        .collect::<String>();
+    let disposition = if name.chars().all(is_plain) {
+        format!("attachment; filename=\"{}\"", fallback)
+    } else {
+        format!(
+            "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+            fallback,
+            utf8_percent_encode(name, ATTR_CHAR)
+        )
+    };
+    HeaderValue::from_str(&disposition).expect("disposition is encoded")
+}
+
+// Characters escaped in the names of a listing's links, besides controls.
+const LISTING_NAME: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'\'')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+async fn listing_reply(path: ArcPath, request_path: &str) -> Result<File, Rejection> {
+    let mut read_dir = match tokio::fs::read_dir(path.as_ref()).await {
+        Ok(read_dir) => read_dir,
+        Err(err) => {
+            tracing::debug!("dir: read_dir error: {}", err);
+            return Err(reject::not_found());
+        }
+    };
+
+    let mut entries = Vec::new();
+    loop {
+        let entry = match read_dir.next_entry().await {
+            Ok(Some(entry)) => entry,
+            Ok(None) => break,
+            Err(err) => {
+                tracing::debug!("dir: read_dir error: {}", err);
+                return Err(reject::not_found());
+            }
+        };
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        // Leave out entries that couldn't be requested.
+        if name.starts_with("..") || name.contains('\\') || (cfg!(windows) && name.contains(':')) {
+            continue;
+        }
+        // Follows symlinks, like serving the entry would.
+        if let Ok(metadata) = tokio::fs::metadata(entry.path()).await {
+            entries.push((name, metadata));
+        }
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    // The request path may hold characters like `"`, so it's escaped too.
+    let base = html_escape(request_path.trim_end_matches('/'));
+    let title = html_escape(&percent_decode_str(request_path).decode_utf8_lossy());
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Index of {0}</title>\n</head>\n<body>\n<h1>Index of {0}</h1>\n\
+         <table>\n<tr><th>Name</th><th>Size</th><th>Modified</th></tr>\n",
+        title
+    );
+    if !base.is_empty() {
+        html.push_str("<tr><td><a href=\"../\">../</a></td><td></td><td></td></tr>\n");
+    }
+    for (name, metadata) in entries {
+        let slash = if metadata.is_dir() { "/" } else { "" };
+        let size = if metadata.is_dir() {
+            "-".to_owned()
+        } else {
+            metadata.len().to_string()
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|since| listing_date(since.as_secs()))
+            .unwrap_or_default();
+        html.push_str(&format!(
+            "<tr><td><a href=\"{}/{}{}\">{}{}</a></td><td>{}</td><td>{}</td></tr>\n",
+            base,
+            utf8_percent_encode(&name, LISTING_NAME),
+            slash,
+            html_escape(&name),
+            slash,
+            size,
+            modified,
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    let mut resp = Response::new(Body::from(html));
+    resp.headers_mut()
+        .typed_insert(ContentType::from(mime::TEXT_HTML_UTF_8));
+    Ok(File { resp, path })
+}
+
+fn html_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Formats seconds since the unix epoch as `YYYY-MM-DD HH:MM` in UTC.
+fn listing_date(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs / 3_600,
+        secs % 3_600 / 60
+    )
+}
+
+// Converts days since the unix epoch into a year, month and day.
+//
+// From Howard Hinnant's `civil_from_days`.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
 async fn file_metadata(f: TkFile) -> Result<(TkFile, Metadata), Rejection> {
     match f.metadata().await {
         Ok(meta) => Ok((f, meta)),
@@ -303,53 +696,35 @@ async fn file_metadata(f: TkFile) -> Result<(TkFile, Metadata), Rejection> {
 fn file_conditional(
     f: TkFile,
     path: ArcPath,
-    conditionals: Conditionals,
+    conditionals: CacheValidators,
+    disposition: Option<HeaderValue>,
 ) -> impl Future<Output = Result<File, Rejection>> + Send {
     file_metadata(f).map_ok(move |(file, meta)| {
-        let mut len = meta.len();
+        let len = meta.len();
         let modified = meta.modified().ok().map(LastModified::from);
 
-        let resp = match conditionals.check(modified) {
+        let resp = match conditionals.check(modified, None) {
             Cond::NoBody(resp) => resp,
             Cond::WithBody(range) => {
-                bytes_range(range, len)
-                    .map(|(start, end)| {
-                        let sub_len = end - start;
-                        let buf_size = optimal_buf_size(&meta);
-                        let stream = file_stream(file, buf_size, (start, end));
-                        let body = Body::wrap_stream(stream);
-
-                        let mut resp = Response::new(body);
-
-                        if sub_len != len {
-                            *resp.status_mut() = StatusCode::PARTIAL_CONTENT;
-                            resp.headers_mut().typed_insert(
-                                ContentRange::bytes(start..end, len).expect("valid ContentRange"),
-                            );
-
-                            len = sub_len;
-                        }
+                let buf_size = optimal_buf_size(&meta);
+                let mut resp = range_response(range, len, |range| {
+                    Body::wrap_stream(file_stream(file, buf_size, range))
+                });
 
-                        let mime = mime_guess::from_path(path.as_ref()).first_or_octet_stream();
+                if resp.status().is_success() {
+                    let mime = mime_guess::from_path(path.as_ref()).first_or_octet_stream();
+                    resp.headers_mut().typed_insert(ContentType::from(mime));
 
-                        resp.headers_mut().typed_insert(ContentLength(len));
-                        resp.headers_mut().typed_insert(ContentType::from(mime));
-                        resp.headers_mut().typed_insert(AcceptRanges::bytes());
+                    if let Some(last_modified) = modified {
+                        resp.headers_mut().typed_insert(last_modified);
+                    }
 
-                        if let Some(last_modified) = modified {
-                            resp.headers_mut().typed_insert(last_modified);
-                        }
+                    if let Some(disposition) = disposition {
+                        resp.headers_mut().insert(CONTENT_DISPOSITION, disposition);
+                    }
+                }
 
-                        resp
-                    })
-                    .unwrap_or_else(|BadRange| {
-                        // bad byte range
-                        let mut resp = Response::new(Body::empty());
-                        *resp.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
-                        resp.headers_mut()
-                            .typed_insert(ContentRange::unsatisfied_bytes(len));
-                        resp
-                    })
+                resp
             }
         };
 
@@ -357,51 +732,6 @@ fn file_conditional(
     })
 }
 
-struct BadRange;
-
-fn bytes_range(range: Option<Range>, max_len: u64) -> Result<(u64, u64), BadRange> {
-    use std::ops::Bound;
-
-    let range = if let Some(range) = range {
-        range
-    } else {
-        return Ok((0, max_len));
-    };
-
-    let ret = range
-        .iter()
-        .map(|(start, end)| {
-            let start = match start {
-                Bound::Unbounded => 0,
-                Bound::Included(s) => s,
-                Bound::Excluded(s) => s + 1,
-            };
-
-            let end = match end {
-                Bound::Unbounded => max_len,
-                Bound::Included(s) => {
-                    // For the special case where s == the file size
-                    if s == max_len {
-                        s
-                    } else {
-                        s + 1
-                    }
-                }
-                Bound::Excluded(s) => s,
-            };
-
-            if start < end && end <= max_len {
-                Ok((start, end))
-            } else {
-                tracing::trace!("unsatisfiable byte range: {}-{}/{}", start, end, max_len);
-                Err(BadRange)
-            }
-        })
-        .next()
-        .unwrap_or(Ok((0, max_len)));
-    ret
-}
-
 fn file_stream(
     mut file: TkFile,
     buf_size: usize,
@@ -489,6 +819,291 @@ fn get_block_size(_metadata: &Metadata) -> usize {
     DEFAULT_READ_BUF_SIZE
 }
 
+// ===== Archive =====
+
+/// The format of an archive served by [`archive`].
+///
+/// *This type requires the `"archive"` feature.*
+#[cfg(feature = "archive")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// An uncompressed tarball, `.tar`.
+    Tar,
+    /// A gzip compressed tarball, `.tar.gz`.
+    ///
+    /// *This variant requires the `"compression-gzip"` feature.*
+    #[cfg(feature = "compression-gzip")]
+    TarGz,
+    /// A zip file, `.zip`.
+    ///
+    /// Files are stored without compression.
+    Zip,
+}
+
+#[cfg(feature = "archive")]
+impl Format {
+    fn content_type(self) -> &'static str {
+        match self {
+            Format::Tar => "application/x-tar",
+            #[cfg(feature = "compression-gzip")]
+            Format::TarGz => "application/gzip",
+            Format::Zip => "application/zip",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Tar => "tar",
+            #[cfg(feature = "compression-gzip")]
+            Format::TarGz => "tar.gz",
+            Format::Zip => "zip",
+        }
+    }
+}
+
+/// Creates a `Filter` that serves an archive of the directory at `path`.
+///
+/// The archive is generated while the response body is streamed, without
+/// buffering it in memory, and is sent with a `content-disposition` header
+/// naming it after the directory.
+///
+/// Every file in the directory and its subdirectories is included. Symlinks
+/// to files are followed, but symlinks to directories are skipped.
+///
+/// Like [`file`], this doesn't filter based on any information of the
+/// request. If the directory doesn't exist, the request is rejected.
+///
+/// *This function requires the `"archive"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::filters::fs::Format;
+///
+/// // `GET /download` serves `/www/static` as `static.zip`.
+/// let route = warp::path("download")
+///     .and(warp::fs::archive("/www/static", Format::Zip));
+/// ```
+#[cfg(feature = "archive")]
+pub fn archive(
+    path: impl Into<PathBuf>,
+    format: Format,
+) -> impl FilterClone<Extract = One<Archive>, Error = Rejection> {
+    let path = ArcPath(Arc::new(path.into()));
+    crate::any().and_then(move || archive_reply(path.clone(), format))
+}
+
+/// An archive of a directory, streamed as the response body.
+///
+/// Returned by `warp::fs::archive`.
+#[cfg(feature = "archive")]
+#[derive(Debug)]
+pub struct Archive {
+    resp: Response,
+}
+
+#[cfg(feature = "archive")]
+impl Reply for Archive {
+    fn into_response(self) -> Response {
+        self.resp
+    }
+}
+
+#[cfg(feature = "archive")]
+async fn archive_reply(path: ArcPath, format: Format) -> Result<Archive, Rejection> {
+    use http::header::CONTENT_TYPE;
+
+    match tokio::fs::metadata(&path).await {
+        Ok(meta) if meta.is_dir() => (),
+        Ok(_) => {
+            tracing::debug!("archive: not a directory: {:?}", path.as_ref().display());
+            return Err(reject::not_found());
+        }
+        Err(err) => {
+            tracing::debug!("archive: {:?}: {}", path.as_ref().display(), err);
+            return Err(reject::not_found());
+        }
+    }
+
+    // The `tar` and `zip` writers are blocking, so the archive is written on
+    // a blocking thread, and its chunks are sent over to the body.
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let base = path.0.clone();
+    tokio::task::spawn_blocking(move || {
+        let writer = io::BufWriter::with_capacity(DEFAULT_READ_BUF_SIZE, ChunkWriter(tx.clone()));
+        if let Err(err) = write_archive(&base, format, writer) {
+            tracing::debug!("archive: error: {}", err);
+            let _ = tx.blocking_send(Err(err));
+        }
+    });
+    let stream = stream::poll_fn(move |cx| rx.poll_recv(cx));
+
+    let body = match format {
+        #[cfg(feature = "compression-gzip")]
+        Format::TarGz => {
+            use async_compression::tokio::bufread::GzipEncoder;
+            use tokio_util::io::{ReaderStream, StreamReader};
+
+            Body::wrap_stream(ReaderStream::new(GzipEncoder::new(StreamReader::new(
+                stream,
+            ))))
+        }
+        _ => Body::wrap_stream(stream),
+    };
+
+    let name = path
+        .as_ref()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| name.chars().all(|c| c.is_ascii_graphic() && c != '"'))
+        .unwrap_or("archive");
+    let disposition = format!("attachment; filename=\"{}.{}\"", name, format.extension());
+
+    let mut resp = Response::new(body);
+    resp.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(format.content_type()),
+    );
+    resp.headers_mut().insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_str(&disposition).expect("filename is sanitized"),
+    );
+
+    Ok(Archive { resp })
+}
+
+// Sends each write as a chunk of the body. It is wrapped in a `BufWriter`,
+// so the chunks aren't as small as a single tar header.
+#[cfg(feature = "archive")]
+struct ChunkWriter(tokio::sync::mpsc::Sender<io::Result<Bytes>>);
+
+#[cfg(feature = "archive")]
+impl io::Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "archive body dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(feature = "archive")]
+struct ArchiveEntry {
+    // Relative to the archived directory, directories end with a `/`.
+    name: String,
+    path: PathBuf,
+    meta: Metadata,
+}
+
+#[cfg(feature = "archive")]
+fn write_archive(base: &Path, format: Format, mut out: impl io::Write) -> io::Result<()> {
+    use std::convert::TryFrom;
+
+    let entries = archive_entries(base)?;
+    match format {
+        Format::Zip => {
+            let mut zip = zip::ZipWriter::new_stream(&mut out);
+            for entry in entries {
+                let modified = entry
+                    .meta
+                    .modified()
+                    .ok()
+                    .and_then(|time| zip::DateTime::try_from(time::OffsetDateTime::from(time)).ok())
+                    .unwrap_or_default();
+                let options = zip::write::SimpleFileOptions::default()
+                    .compression_method(zip::CompressionMethod::Stored)
+                    .last_modified_time(modified)
+                    .large_file(entry.meta.len() > u64::from(u32::MAX));
+                if entry.meta.is_dir() {
+                    zip.add_directory(entry.name, options)?;
+                } else {
+                    let mut file = match std::fs::File::open(&entry.path) {
+                        Ok(file) => file,
+                        Err(err) => {
+                            tracing::warn!("archive: skipping {:?}: {}", entry.path, err);
+                            continue;
+                        }
+                    };
+                    zip.start_file(entry.name, options)?;
+                    io::copy(&mut file, &mut zip)?;
+                }
+            }
+            zip.finish()?;
+        }
+        _ => {
+            let mut tar = tar::Builder::new(&mut out);
+            for entry in entries {
+                if entry.meta.is_dir() {
+                    tar.append_dir(&entry.name, &entry.path)?;
+                } else if let Err(err) = tar.append_path_with_name(&entry.path, &entry.name) {
+                    tracing::warn!("archive: skipping {:?}: {}", entry.path, err);
+                }
+            }
+            tar.finish()?;
+        }
+    }
+    out.flush()
+}
+
+// Lists the entries of the directory at `base`, depth first, and sorted by
+// name within each directory.
+#[cfg(feature = "archive")]
+fn archive_entries(base: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![String::new()];
+    while let Some(dir) = dirs.pop() {
+        let mut children = Vec::new();
+        for dir_entry in std::fs::read_dir(base.join(&dir))? {
+            let dir_entry = dir_entry?;
+            let name = match dir_entry.file_name().into_string() {
+                Ok(name) => name,
+                Err(name) => {
+                    tracing::warn!("archive: skipping non UTF-8 name {:?}", name);
+                    continue;
+                }
+            };
+            // Follows symlinks, like `fs::dir` does.
+            let path = dir_entry.path();
+            let meta = match std::fs::metadata(&path) {
+                Ok(meta) => meta,
+                Err(err) => {
+                    tracing::warn!("archive: skipping {:?}: {}", path, err);
+                    continue;
+                }
+            };
+            if meta.is_dir() && dir_entry.file_type()?.is_symlink() {
+                tracing::debug!("archive: skipping symlink {:?}", path);
+                continue;
+            }
+            if !meta.is_dir() && !meta.is_file() {
+                continue;
+            }
+
+            let name = if meta.is_dir() {
+                format!("{}{}/", dir, name)
+            } else {
+                format!("{}{}", dir, name)
+            };
+            children.push(ArchiveEntry { name, path, meta });
+        }
+
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        // Subdirectories are listed after these entries.
+        for child in children.iter().rev() {
+            if child.meta.is_dir() {
+                dirs.push(child.name.clone());
+            }
+        }
+        entries.extend(children);
+    }
+    Ok(entries)
+}
+
 // ===== Rejections =====
 
 unit_error! {
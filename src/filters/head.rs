@@ -0,0 +1,234 @@
+//! HEAD Filters
+//!
+//! Filters to answer `HEAD` requests with the routes already written for
+//! `GET`.
+
+use std::convert::Infallible;
+
+use futures_util::future;
+
+use self::internal::DeriveFromGet;
+use crate::filter::{filter_fn_one, Filter, One, WrapSealed};
+use crate::reply::Reply;
+use crate::route::Route;
+
+/// Wrap a `GET` filter so that it answers `HEAD` requests too.
+///
+/// For a `HEAD` request, the filter is run as though the request were a
+/// `GET`, including any `warp::get()` inside of it. The reply keeps all of its
+/// headers, gaining a `Content-Length` if its body has a known size, but the
+/// body is dropped. Requests with any other method are passed to the filter
+/// unchanged.
+///
+/// Since the handler still runs, it can use [`is_head`] to skip building a
+/// body that would be thrown away.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let readme = warp::get()
+///     .and(warp::path("readme"))
+///     .map(|| "# warp\n");
+///
+/// // Answers both `GET /readme` and `HEAD /readme`.
+/// let route = warp::head::derive_from_get(readme);
+/// ```
+pub fn derive_from_get<F>(
+    filter: F,
+) -> impl Filter<Extract = One<impl Reply>, Error = F::Error> + Clone
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+{
+    DeriveFromGet { inner: filter }
+}
+
+/// Decorate a `GET` filter so that it answers `HEAD` requests too.
+///
+/// This is [`derive_from_get`] as a wrapper for [`Filter::with`], which reads
+/// better at the end of a chain of filters.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Answers both `GET /readme` and `HEAD /readme`.
+/// let route = warp::get()
+///     .and(warp::path("readme"))
+///     .map(|| "# warp\n")
+///     .with(warp::head::auto());
+/// ```
+pub fn auto() -> Auto {
+    Auto { _p: () }
+}
+
+/// Decorates a [`Filter`] to answer `HEAD` requests as a `GET`.
+///
+/// Created with [`auto`].
+#[derive(Clone, Copy, Debug)]
+pub struct Auto {
+    _p: (),
+}
+
+impl<F> WrapSealed<F> for Auto
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Extract: Reply,
+{
+    type Wrapped = DeriveFromGet<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        DeriveFromGet { inner: filter }
+    }
+}
+
+/// Creates a `Filter` that extracts whether the reply's body will be dropped.
+///
+/// This is `true` while a filter wrapped by [`derive_from_get`] answers a
+/// `HEAD` request. Handlers with expensive bodies can then reply with just
+/// the headers, as long as those don't depend on the body.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::http::header::CONTENT_LENGTH;
+///
+/// let report = warp::get()
+///     .and(warp::path("report"))
+///     .and(warp::head::is_head())
+///     .map(|is_head: bool| {
+///         let len = 1_000_000;
+///         let body = if is_head {
+///             String::new()
+///         } else {
+///             "x".repeat(len)
+///         };
+///         warp::reply::with_header(body, CONTENT_LENGTH, len)
+///     });
+///
+/// let route = warp::head::derive_from_get(report);
+/// ```
+pub fn is_head() -> impl Filter<Extract = One<bool>, Error = Infallible> + Copy {
+    filter_fn_one(|route| future::ok(is_derived(route)))
+}
+
+// Whether a `HEAD` request is being run as a `GET` by `derive_from_get`.
+pub(crate) fn is_derived(route: &Route) -> bool {
+    route.extensions().get::<internal::Derived>().is_some()
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_util::{ready, TryFuture};
+    use http::header::CONTENT_LENGTH;
+    use http::Method;
+    use hyper::body::HttpBody;
+    use hyper::Body;
+    use pin_project::pin_project;
+
+    use crate::filter::{Filter, FilterBase, Internal, One};
+    use crate::reply::{Reply, Response, SkippedBody};
+    use crate::route;
+
+    // Marks a `HEAD` request that is being run as a `GET`.
+    #[derive(Clone, Copy, Debug)]
+    pub(super) struct Derived;
+
+    #[derive(Clone, Debug)]
+    pub struct DeriveFromGet<F> {
+        pub(super) inner: F,
+    }
+
+    impl<F> FilterBase for DeriveFromGet<F>
+    where
+        F: Filter,
+        F::Extract: Send,
+        F::Future: Future,
+    {
+        type Extract = One<HeadReply<F::Extract>>;
+        type Error = F::Error;
+        type Future = HeadFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let head = route::with(|route| {
+                if route.method() != Method::HEAD {
+                    return false;
+                }
+                tracing::trace!("running GET filter for HEAD request");
+                route.set_method(Method::GET);
+                route.extensions_mut().insert(Derived);
+                true
+            });
+
+            HeadFuture {
+                head,
+                inner: self.inner.filter(Internal),
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    pub struct HeadReply<R> {
+        inner: R,
+        strip_body: bool,
+    }
+
+    impl<R: Reply> Reply for HeadReply<R> {
+        fn into_response(self) -> Response {
+            let mut res = self.inner.into_response();
+            if self.strip_body {
+                // A body skipped by `reply::lazy` is empty, but its length
+                // isn't known.
+                let skipped = res.extensions().get::<SkippedBody>().is_some();
+                if !skipped && !res.headers().contains_key(CONTENT_LENGTH) {
+                    if let Some(len) = res.body().size_hint().exact() {
+                        res.headers_mut().insert(CONTENT_LENGTH, len.into());
+                    }
+                }
+                *res.body_mut() = Body::empty();
+            }
+            res
+        }
+    }
+
+    #[pin_project]
+    #[derive(Debug)]
+    pub struct HeadFuture<F> {
+        head: bool,
+        #[pin]
+        inner: F,
+    }
+
+    impl<F> Future for HeadFuture<F>
+    where
+        F: TryFuture,
+    {
+        type Output = Result<One<HeadReply<F::Ok>>, F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let result = ready!(pin.inner.try_poll(cx));
+            let head = *pin.head;
+            if head {
+                // Filters after this one should see the request as it was.
+                route::with(|route| {
+                    route.set_method(Method::HEAD);
+                    route.extensions_mut().remove::<Derived>();
+                });
+            }
+            Poll::Ready(result.map(|inner| {
+                (HeadReply {
+                    inner,
+                    strip_body: head,
+                },)
+            }))
+        }
+    }
+}
@@ -4,12 +4,18 @@
 //! of them, like `exact` and `exact_ignore_case`, are just predicates,
 //! they don't extract any values. The `header` filter allows parsing
 //! a type from any header.
+use std::collections::HashMap;
 use std::convert::Infallible;
+use std::error::Error as StdError;
+use std::fmt;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
 use futures_util::future;
-use headers::{Header, HeaderMapExt};
-use http::header::HeaderValue;
+use headers::authorization::{Basic, Bearer};
+use headers::{Authorization, ContentRange, ETag, Header, HeaderMapExt};
+use http::header::{HeaderValue, ACCEPT, ACCEPT_ENCODING, CONTENT_RANGE};
 use http::HeaderMap;
 
 use crate::filter::{filter_fn, filter_fn_one, Filter, One};
@@ -20,6 +26,9 @@ use crate::reject::{self, Rejection};
 /// This `Filter` will look for a header with supplied name, and try to
 /// parse to a `T`, otherwise rejects the request.
 ///
+/// If the header is sent more than once, only the first occurrence is used.
+/// To get every one of them, use [`all`](all) or [`all_lenient`](all_lenient).
+///
 /// # Example
 ///
 /// ```
@@ -67,6 +76,8 @@ pub(crate) fn header2<T: Header + Send + 'static>(
 /// parse as a `T`, and if it fails, a invalid header rejection is return. If
 /// successful, the filter yields `Some(T)`.
 ///
+/// Like [`header`](header), only the first occurrence of the header is used.
+///
 /// # Example
 ///
 /// ```
@@ -97,6 +108,80 @@ where
     })
 }
 
+/// Create a `Filter` that parses every occurrence of the specified header.
+///
+/// Some headers, like `via` or `forwarded`, may legitimately be sent
+/// multiple times. This `Filter` will collect each value of the header with
+/// the supplied name, in the order received, parsing each into a `T`. If
+/// any of them fail to parse, the request is rejected.
+///
+/// Each occurrence is parsed whole, so a single header listing several
+/// comma-separated values is parsed as one `T`.
+///
+/// If the header does not exist, it yields an empty `Vec`.
+///
+/// # Example
+///
+/// ```
+/// // Collect each hop of a `via` chain.
+/// let via = warp::header::all::<String>("via");
+/// ```
+pub fn all<T>(name: &'static str) -> impl Filter<Extract = One<Vec<T>>, Error = Rejection> + Copy
+where
+    T: FromStr + Send + 'static,
+{
+    filter_fn_one(move |route| {
+        tracing::trace!("all({:?})", name);
+        let result = route
+            .headers()
+            .get_all(name)
+            .iter()
+            .map(|value| {
+                value
+                    .to_str()
+                    .map_err(|_| reject::invalid_header(name))?
+                    .parse::<T>()
+                    .map_err(|_| reject::invalid_header(name))
+            })
+            .collect::<Result<Vec<T>, _>>();
+        future::ready(result)
+    })
+}
+
+/// Create a `Filter` that parses every occurrence of the specified header,
+/// skipping any that fail to parse.
+///
+/// This is like [`all`](all), but instead of rejecting the request when
+/// one of the values is invalid, that value is simply left out.
+///
+/// Like `all`, each occurrence is parsed whole. A comma-separated list, such
+/// as an `x-forwarded-for: 10.0.0.1, 10.0.0.2`, doesn't parse as a single
+/// `IpAddr`, and so is left out entirely.
+///
+/// # Example
+///
+/// ```
+/// // Collect each `x-tenant-id` sent as a number, ignoring the others.
+/// let tenants = warp::header::all_lenient::<u64>("x-tenant-id");
+/// ```
+pub fn all_lenient<T>(
+    name: &'static str,
+) -> impl Filter<Extract = One<Vec<T>>, Error = Infallible> + Copy
+where
+    T: FromStr + Send + 'static,
+{
+    filter_fn_one(move |route| {
+        tracing::trace!("all_lenient({:?})", name);
+        let values = route
+            .headers()
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok()?.parse::<T>().ok())
+            .collect::<Vec<T>>();
+        future::ok(values)
+    })
+}
+
 pub(crate) fn optional2<T>() -> impl Filter<Extract = One<Option<T>>, Error = Infallible> + Copy
 where
     T: Header + Send + 'static,
@@ -104,6 +189,46 @@ where
     filter_fn_one(move |route| future::ready(Ok(route.headers().typed_get())))
 }
 
+/// Create a `Filter` that parses the `content-range` header of a request.
+///
+/// This is useful for endpoints accepting partial uploads, such as a `PUT`
+/// resuming an earlier transfer. The filter extracts the `(start, end, total)`
+/// of a `content-range: bytes start-end/total` header, where `end` is
+/// inclusive, and `total` is `None` if the complete length is unknown (`*`).
+///
+/// If the header is missing, is malformed, or describes a range outside of
+/// the complete length, the request is rejected with a `400 Bad Request`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let upload = warp::put()
+///     .and(warp::header::content_range())
+///     .map(|(start, end, total): (u64, u64, Option<u64>)| {
+///         format!("received bytes {}-{} of {:?}", start, end, total)
+///     });
+/// ```
+pub fn content_range(
+) -> impl Filter<Extract = One<(u64, u64, Option<u64>)>, Error = Rejection> + Copy {
+    filter_fn_one(move |route| {
+        tracing::trace!("content_range()");
+        let name = CONTENT_RANGE.as_str();
+        let route = route
+            .headers()
+            .typed_try_get::<ContentRange>()
+            .map_err(|_| reject::invalid_header(name))
+            .and_then(|opt| opt.ok_or_else(|| reject::missing_header(name)))
+            .and_then(|range| match (range.bytes_range(), range.bytes_len()) {
+                (Some((start, end)), Some(total)) if end < total => Ok((start, end, Some(total))),
+                (Some((start, end)), None) => Ok((start, end, None)),
+                _ => Err(reject::invalid_header(name)),
+            });
+        future::ready(route)
+    })
+}
+
 /* TODO
 pub fn exact2<T>(header: T) -> impl FilterClone<Extract=(), Error=Rejection>
 where
@@ -187,6 +312,287 @@ pub fn exact_ignore_case(
     })
 }
 
+/// Create a `Filter` that requires the `accept-encoding` header to allow one
+/// of the provided encodings.
+///
+/// This is useful for endpoints that only serve precompressed data. The
+/// request is rejected with a `406 Not Acceptable` if the header is missing,
+/// or if none of the encodings are included in it with a non-zero quality.
+/// A wildcard, `*`, accepts any encoding not otherwise listed.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // Only serve clients that can decompress gzip or brotli.
+/// let blobs = warp::header::require_accept_encoding(&["gzip", "br"])
+///     .and(warp::fs::dir("/www/compressed"));
+/// ```
+pub fn require_accept_encoding(
+    encodings: &'static [&'static str],
+) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        tracing::trace!("require_accept_encoding({:?})", encodings);
+        if accepts_encoding(route.headers(), encodings) {
+            future::ok(())
+        } else {
+            tracing::debug!("accept-encoding doesn't allow any of {:?}", encodings);
+            future::err(reject::not_acceptable())
+        }
+    })
+}
+
+// Whether the `accept-encoding` headers allow any of the `encodings`.
+pub(crate) fn accepts_encoding(headers: &HeaderMap, encodings: &[&str]) -> bool {
+    let accepted = headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(parse_coding)
+        .collect::<Vec<_>>();
+
+    let is_accepted = |encoding: &str| {
+        let quality = accepted
+            .iter()
+            .find(|(coding, _)| coding.eq_ignore_ascii_case(encoding))
+            .or_else(|| accepted.iter().find(|(coding, _)| *coding == "*"));
+        match quality {
+            Some((_, quality)) => *quality > 0.0,
+            None => false,
+        }
+    };
+
+    encodings.iter().any(|encoding| is_accepted(encoding))
+}
+
+// Parses a `coding;q=0.5` item, with a quality defaulting to 1.
+fn parse_coding(item: &str) -> Option<(&str, f32)> {
+    let mut parts = item.split(';');
+    let coding = parts.next()?.trim();
+    if coding.is_empty() {
+        return None;
+    }
+    let mut quality = 1.0;
+    for param in parts {
+        let mut param = param.splitn(2, '=');
+        if param.next()?.trim().eq_ignore_ascii_case("q") {
+            quality = param.next()?.trim().parse().ok()?;
+        }
+    }
+    Some((coding, quality))
+}
+
+/// Create a `Filter` that picks the best of the `available` media types for
+/// the `accept` header of the request.
+///
+/// Each media type is matched against the most specific range of the
+/// header that includes it, whether that's the exact type, like
+/// `application/json`, a `text/*` wildcard, or `*/*`, and the one with the
+/// highest quality is extracted. Ties go to the type listed first in
+/// `available`. Media types with a quality of `0` are never picked.
+///
+/// If the request has no `accept` header, the first of the `available` types
+/// is extracted. If none of them are acceptable, the request is rejected with
+/// a `406 Not Acceptable`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let user = warp::path("user")
+///     .and(warp::header::accepts(&[mime::APPLICATION_JSON, mime::TEXT_HTML]))
+///     .map(|mime: mime::Mime| {
+///         if mime == mime::TEXT_HTML {
+///             warp::reply::html("<b>sean</b>").into_response()
+///         } else {
+///             warp::reply::json(&"sean").into_response()
+///         }
+///     });
+/// # use warp::Reply;
+/// ```
+pub fn accepts(
+    available: &[mime::Mime],
+) -> impl Filter<Extract = One<mime::Mime>, Error = Rejection> + Clone {
+    let available: Arc<[mime::Mime]> = available.into();
+    filter_fn_one(|route| future::ok::<_, Infallible>(media_ranges(route.headers()))).and_then(
+        move |ranges: Vec<mime::Mime>| {
+            tracing::trace!("accepts({:?})", available);
+            let best = best_accepted(&ranges, &available).cloned();
+            future::ready(best.ok_or_else(|| {
+                tracing::debug!("accept doesn't allow any of {:?}", available);
+                reject::not_acceptable()
+            }))
+        },
+    )
+}
+
+// Parses the media ranges of the `accept` headers.
+fn media_ranges(headers: &HeaderMap) -> Vec<mime::Mime> {
+    headers
+        .get_all(ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|item| item.trim().parse::<mime::Mime>().ok())
+        .collect()
+}
+
+// The `available` media type with the highest quality in the `ranges`.
+fn best_accepted<'a>(ranges: &[mime::Mime], available: &'a [mime::Mime]) -> Option<&'a mime::Mime> {
+    if ranges.is_empty() {
+        return available.first();
+    }
+
+    let quality = |mime: &mime::Mime| {
+        let range = ranges
+            .iter()
+            .filter(|range| {
+                range.type_() == mime::STAR
+                    || (range.type_() == mime.type_()
+                        && (range.subtype() == mime::STAR || range.subtype() == mime.subtype()))
+            })
+            .max_by_key(|range| match (range.type_(), range.subtype()) {
+                (mime::STAR, _) => 0,
+                (_, mime::STAR) => 1,
+                _ => 2,
+            })?;
+        let quality = match range.get_param("q") {
+            Some(q) => q.as_str().parse::<f32>().ok()?,
+            None => 1.0,
+        };
+        Some(quality).filter(|quality| *quality > 0.0)
+    };
+
+    let mut best: Option<(&mime::Mime, f32)> = None;
+    for mime in available {
+        match (quality(mime), best) {
+            (Some(quality), Some((_, best_quality))) if quality <= best_quality => (),
+            (Some(quality), _) => best = Some((mime, quality)),
+            (None, _) => (),
+        }
+    }
+    best.map(|(mime, _)| mime)
+}
+
+/// Create a `Filter` that extracts the credentials of an
+/// `authorization: Basic` header, as a `(username, password)`.
+///
+/// The credentials are base64 decoded, and split at the first `:`. If the
+/// header is missing, isn't of the `Basic` scheme, or can't be decoded, the
+/// request is rejected with a `401 Unauthorized`, asking for basic
+/// credentials with a `www-authenticate` header.
+///
+/// Checking the credentials is left to the handler.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let admin = warp::path("admin")
+///     .and(warp::header::authorization_basic())
+///     .map(|username: String, _password: String| format!("hello {}", username));
+/// ```
+pub fn authorization_basic() -> impl Filter<Extract = (String, String), Error = Rejection> + Copy {
+    filter_fn(|route| {
+        tracing::trace!("authorization_basic");
+        let credentials = route
+            .headers()
+            .typed_get::<Authorization<Basic>>()
+            .map(|auth| (auth.username().to_owned(), auth.password().to_owned()))
+            .ok_or_else(|| {
+                tracing::debug!("missing or invalid basic credentials");
+                reject::known(Unauthorized {
+                    scheme: Scheme::Basic,
+                })
+            });
+        future::ready(credentials)
+    })
+}
+
+/// Create a `Filter` that extracts the token of an `authorization: Bearer`
+/// header.
+///
+/// If the header is missing, or isn't of the `Bearer` scheme, the request
+/// is rejected with a `401 Unauthorized`, asking for a bearer token with a
+/// `www-authenticate` header.
+///
+/// Checking the token is left to the handler. For JSON Web Tokens, see
+/// [`auth::jwt`](crate::auth::jwt).
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let api = warp::path("api")
+///     .and(warp::header::authorization_bearer())
+///     .map(|token: String| format!("token has {} bytes", token.len()));
+/// ```
+pub fn authorization_bearer() -> impl Filter<Extract = One<String>, Error = Rejection> + Copy {
+    filter_fn_one(|route| {
+        tracing::trace!("authorization_bearer");
+        let token = route
+            .headers()
+            .typed_get::<Authorization<Bearer>>()
+            .map(|auth| auth.token().to_owned())
+            .ok_or_else(|| {
+                tracing::debug!("missing or invalid bearer token");
+                reject::known(Unauthorized {
+                    scheme: Scheme::Bearer,
+                })
+            });
+        future::ready(token)
+    })
+}
+
+/// Create a `Filter` that rejects requests with too many headers, or headers
+/// too large in total.
+///
+/// Each value of a header counts as one header, and its size is the length
+/// of its name and value. If there are more than `max_count` headers, or
+/// their sizes add up to more than `max_total_bytes`, the request is
+/// rejected with a `431 Request Header Fields Too Large`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // At most 64 headers, of 16KB in total.
+/// let route = warp::header::limit(64, 16 * 1024)
+///     .and(warp::path("upload"))
+///     .map(warp::reply);
+/// ```
+pub fn limit(
+    max_count: usize,
+    max_total_bytes: usize,
+) -> impl Filter<Extract = (), Error = Rejection> + Copy {
+    filter_fn(move |route| {
+        let headers = route.headers();
+        let total_bytes = headers
+            .iter()
+            .map(|(name, value)| name.as_str().len() + value.len())
+            .sum::<usize>();
+        tracing::trace!(
+            "limit: {} headers, {} bytes (max {}, {} bytes)",
+            headers.len(),
+            total_bytes,
+            max_count,
+            max_total_bytes
+        );
+        if headers.len() > max_count || total_bytes > max_total_bytes {
+            tracing::debug!("request headers exceed limit");
+            future::err(reject::known(HeadersTooLarge { _p: () }))
+        } else {
+            future::ok(())
+        }
+    })
+}
+
 /// Create a `Filter` that gets a `HeaderValue` for the name.
 ///
 /// # Example
@@ -213,6 +619,141 @@ pub fn value(
     })
 }
 
+/// Create a `Filter` that requires requests of a session to be sent in order.
+///
+/// The session key is read from the `session_header`, and a sequence number
+/// from the `seq_header`. The sequence number must be greater than the last
+/// one seen for the session, as recorded in the `store`, otherwise the
+/// request is rejected with a `409 Conflict`. This rejects both duplicated
+/// and reordered requests.
+///
+/// If either header is missing or invalid, the request is rejected with a
+/// `400 Bad Request`. Otherwise, the sequence number is extracted.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::header::MemorySequenceStore;
+///
+/// let commands = warp::post()
+///     .and(warp::header::monotonic_seq(
+///         "x-session-id",
+///         "x-seq",
+///         MemorySequenceStore::new(),
+///     ))
+///     .map(|seq: u64| format!("applied command {}", seq));
+/// ```
+pub fn monotonic_seq<S>(
+    session_header: &'static str,
+    seq_header: &'static str,
+    store: S,
+) -> impl Filter<Extract = One<u64>, Error = Rejection> + Clone
+where
+    S: SequenceStore,
+{
+    let store = Arc::new(store);
+    header::<String>(session_header)
+        .and(header::<u64>(seq_header))
+        .and_then(move |session: String, seq: u64| {
+            let result = if store.advance(&session, seq) {
+                Ok(seq)
+            } else {
+                tracing::debug!("sequence {} out of order for session {:?}", seq, session);
+                Err(reject::known(SequenceOutOfOrder { _p: () }))
+            };
+            future::ready(result)
+        })
+}
+
+/// Create a `Filter` that checks the preconditions of a conditional write.
+///
+/// `current_etag` and `current_modified` describe the current version of
+/// the resource. The request is rejected with a `412 Precondition Failed`
+/// if its `if-match` header doesn't match `current_etag`, or, without an
+/// `if-match`, if its `if-unmodified-since` header is older than
+/// `current_modified`. Requests without either header pass. An
+/// `if-match: *` passes unless both are `None`, meaning the resource doesn't
+/// exist.
+///
+/// When the current version depends on the request, such as on a path
+/// parameter, use [`CacheValidators::precondition`] in the handler instead.
+///
+/// [`CacheValidators::precondition`]: crate::cache::CacheValidators::precondition
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let etag = "\"v42\"".parse().unwrap();
+/// let update = warp::put()
+///     .and(warp::path("config"))
+///     .and(warp::header::precondition(Some(etag), None))
+///     .map(|| "updated");
+/// ```
+pub fn precondition(
+    current_etag: Option<ETag>,
+    current_modified: Option<SystemTime>,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    crate::cache::validators()
+        .and_then(move |validators: crate::cache::CacheValidators| {
+            future::ready(validators.precondition(current_etag.as_ref(), current_modified))
+        })
+        .untuple_one()
+}
+
+/// A store of the last sequence number seen for each session.
+///
+/// Used by the [`monotonic_seq`] filter.
+pub trait SequenceStore: Send + Sync + 'static {
+    /// Record `seq` as the last sequence number of the `session`, if it's
+    /// greater than the last one recorded.
+    ///
+    /// Returns whether `seq` was recorded. The check and update must happen
+    /// atomically, since requests of a session may be handled concurrently.
+    fn advance(&self, session: &str, seq: u64) -> bool;
+}
+
+impl<S: SequenceStore> SequenceStore for Arc<S> {
+    fn advance(&self, session: &str, seq: u64) -> bool {
+        (**self).advance(session, seq)
+    }
+}
+
+/// A [`SequenceStore`] keeping sequence numbers in memory.
+///
+/// Sessions are never forgotten, so this is best suited to a bounded number
+/// of sessions, or to tests.
+#[derive(Debug, Default)]
+pub struct MemorySequenceStore {
+    sessions: Mutex<HashMap<String, u64>>,
+}
+
+impl MemorySequenceStore {
+    /// Create an empty `MemorySequenceStore`.
+    pub fn new() -> Self {
+        MemorySequenceStore::default()
+    }
+}
+
+impl SequenceStore for MemorySequenceStore {
+    fn advance(&self, session: &str, seq: u64) -> bool {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|err| err.into_inner());
+        match sessions.get_mut(session) {
+            Some(last) if *last >= seq => false,
+            Some(last) => {
+                *last = seq;
+                true
+            }
+            None => {
+                sessions.insert(session.to_owned(), seq);
+                true
+            }
+        }
+    }
+}
+
 /// Create a `Filter` that returns a clone of the request's `HeaderMap`.
 ///
 /// # Example
@@ -228,3 +769,46 @@ pub fn value(
 pub fn headers_cloned() -> impl Filter<Extract = One<HeaderMap>, Error = Infallible> + Copy {
     filter_fn_one(|route| future::ok(route.headers().clone()))
 }
+
+/// An error used to reject requests without valid `authorization` credentials.
+#[derive(Debug)]
+pub struct Unauthorized {
+    scheme: Scheme,
+}
+
+#[derive(Debug)]
+enum Scheme {
+    Basic,
+    Bearer,
+}
+
+impl Unauthorized {
+    // The `www-authenticate` challenge of the rejection's response.
+    pub(crate) fn challenge(&self) -> HeaderValue {
+        HeaderValue::from_static(match self.scheme {
+            Scheme::Basic => "Basic realm=\"warp\", charset=\"UTF-8\"",
+            Scheme::Bearer => "Bearer",
+        })
+    }
+}
+
+impl fmt::Display for Unauthorized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self.scheme {
+            Scheme::Basic => "Missing or invalid basic credentials",
+            Scheme::Bearer => "Missing or invalid bearer token",
+        })
+    }
+}
+
+impl StdError for Unauthorized {}
+
+unit_error! {
+    /// An error used to reject requests whose headers exceed a limit.
+    pub HeadersTooLarge: "Request header fields too large"
+}
+
+unit_error! {
+    /// An error used to reject requests with an out of order sequence number.
+    pub SequenceOutOfOrder: "Request sequence number is out of order"
+}
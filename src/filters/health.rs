@@ -0,0 +1,149 @@
+//! Health check filters
+//!
+//! Filters that report whether a service is alive and ready to accept
+//! traffic, such as for the liveness and readiness probes of Kubernetes.
+
+use std::borrow::Cow;
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+
+use http::StatusCode;
+
+use crate::filter::Filter;
+use crate::reject::Rejection;
+use crate::reply::{self, Reply};
+
+/// A single health check, run each time the health endpoint is requested.
+pub type HealthCheck = Box<dyn Fn() -> HealthStatus + Send + Sync>;
+
+/// The result of running a [`HealthCheck`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// The check passed.
+    Healthy,
+    /// The check failed, with a description of what failed.
+    Unhealthy(Cow<'static, str>),
+}
+
+impl HealthStatus {
+    /// Create an `Unhealthy` status with a description of the failure.
+    pub fn unhealthy(reason: impl Into<Cow<'static, str>>) -> Self {
+        HealthStatus::Unhealthy(reason.into())
+    }
+}
+
+/// Create a `Filter` that runs every health check and replies with the result.
+///
+/// If all checks pass, the reply is a `200 OK` with a JSON body of
+/// `{"status":"ok"}`. Otherwise, the reply is a `503 Service Unavailable`,
+/// listing the failures: `{"status":"unavailable","failing":[...]}`.
+///
+/// This filter doesn't match any path, so combine it with whichever path the
+/// service uses for health checks.
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use warp::Filter;
+/// use warp::health::{HealthCheck, HealthStatus};
+///
+/// let db_connected = Arc::new(AtomicBool::new(true));
+///
+/// let check: HealthCheck = Box::new(move || {
+///     if db_connected.load(Ordering::Relaxed) {
+///         HealthStatus::Healthy
+///     } else {
+///         HealthStatus::unhealthy("database")
+///     }
+/// });
+///
+/// let route = warp::path("healthz")
+///     .and(warp::health::endpoint(vec![check]));
+/// ```
+pub fn endpoint(
+    checks: Vec<HealthCheck>,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    let checks = Checks(Arc::new(checks));
+    crate::any().map(move || checks.run())
+}
+
+/// Create a `Filter` for a liveness probe at `GET /livez`.
+///
+/// This always replies with `200 OK`, signaling only that the process is up
+/// and able to respond to requests.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let routes = warp::health::liveness()
+///     .or(warp::any().map(|| "Hello, World!"));
+/// ```
+pub fn liveness() -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    crate::get()
+        .and(crate::path!("livez"))
+        .and(endpoint(Vec::new()))
+}
+
+/// Create a `Filter` for a readiness probe at `GET /readyz`.
+///
+/// This runs the provided checks, in the same way as [`endpoint`], to report
+/// whether the service is ready to receive traffic.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::health::HealthStatus;
+///
+/// let routes = warp::health::liveness()
+///     .or(warp::health::readiness(vec![Box::new(|| HealthStatus::Healthy)]))
+///     .or(warp::any().map(|| "Hello, World!"));
+/// ```
+pub fn readiness(
+    checks: Vec<HealthCheck>,
+) -> impl Filter<Extract = (impl Reply,), Error = Rejection> + Clone {
+    crate::get()
+        .and(crate::path!("readyz"))
+        .and(endpoint(checks))
+}
+
+#[derive(Clone)]
+struct Checks(Arc<Vec<HealthCheck>>);
+
+impl Checks {
+    fn run(&self) -> reply::WithStatus<reply::Json> {
+        let failing = self
+            .0
+            .iter()
+            .filter_map(|check| match check() {
+                HealthStatus::Healthy => None,
+                HealthStatus::Unhealthy(reason) => Some(reason),
+            })
+            .collect::<Vec<_>>();
+
+        if failing.is_empty() {
+            let body = serde_json::json!({ "status": "ok" });
+            reply::with_status(reply::json(&body), StatusCode::OK)
+        } else {
+            tracing::debug!("health checks failing: {:?}", failing);
+            let body = serde_json::json!({
+                "status": "unavailable",
+                "failing": failing,
+            });
+            reply::with_status(reply::json(&body), StatusCode::SERVICE_UNAVAILABLE)
+        }
+    }
+}
+
+impl fmt::Debug for Checks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Checks")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
@@ -4,14 +4,17 @@ use std::fmt;
 use std::net::SocketAddr;
 use std::time::{Duration, Instant};
 
+use bytes::Bytes;
+
 use http::{header, StatusCode};
 
 use crate::filter::{Filter, WrapSealed};
 use crate::reject::IsReject;
 use crate::reply::Reply;
 use crate::route::Route;
+use crate::trace::RequestId;
 
-use self::internal::WithLog;
+use self::internal::{WithLog, WithTapResponse};
 
 /// Create a wrapping [`Filter`](crate::Filter) with the specified `name` as the `target`.
 ///
@@ -36,7 +39,7 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info<'_>) + Copy> {
         // - response content length?
         log::info!(
             target: name,
-            "{} \"{} {} {:?}\" {} \"{}\" \"{}\" {:?}",
+            "{} \"{} {} {:?}\" {} \"{}\" \"{}\" {:?}{}",
             OptFmt(info.route.remote_addr()),
             info.method(),
             info.path(),
@@ -45,6 +48,7 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info<'_>) + Copy> {
             OptFmt(info.referer()),
             OptFmt(info.user_agent()),
             info.elapsed(),
+            IdFmt(info.request_id()),
         );
     };
     Log { func }
@@ -52,6 +56,10 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info<'_>) + Copy> {
 
 /// Create a wrapping [`Filter`](crate::Filter) that receives `warp::log::Info`.
 ///
+/// `func` is called once the wrapped filter has replied or rejected, with
+/// the details of the request and response, so access logs can be written
+/// in any format, such as JSON for a log aggregator.
+///
 /// # Example
 ///
 /// ```
@@ -60,10 +68,13 @@ pub fn log(name: &'static str) -> Log<impl Fn(Info<'_>) + Copy> {
 /// let log = warp::log::custom(|info| {
 ///     // Use a log macro, or slog, or println, or whatever!
 ///     eprintln!(
-///         "{} {} {}",
+///         "{} {} {} {} {:?} {:?}",
 ///         info.method(),
-///         info.path(),
+///         info.matched_pattern(),
 ///         info.status(),
+///         info.response_length().unwrap_or(0),
+///         info.elapsed(),
+///         info.request_id(),
 ///     );
 /// });
 /// let route = warp::any()
@@ -77,6 +88,70 @@ where
     Log { func }
 }
 
+/// Create a wrapping [`Filter`](crate::Filter) that calls `func` with the body
+/// of each response.
+///
+/// The body is still streamed to the client as it's produced, while a copy
+/// is kept. Once the body is complete, `func` is called with the copy. Bodies
+/// larger than the [limit](TapResponse::limit), 64KiB by default, are passed
+/// through without being kept, and `func` isn't called for them.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let audit = warp::log::tap_response(|body| {
+///     eprintln!("replied with {} bytes: {:?}", body.len(), body);
+/// });
+/// let route = warp::path("hello")
+///     .map(|| "Hello, World!")
+///     .with(audit);
+/// ```
+pub fn tap_response<F>(func: F) -> TapResponse<F>
+where
+    F: Fn(Bytes) + Clone + Send + Sync + 'static,
+{
+    TapResponse {
+        func,
+        limit: 64 * 1024,
+    }
+}
+
+/// Decorates a [`Filter`] to pass a copy of response bodies to a function.
+///
+/// Returned by [`tap_response`].
+#[derive(Clone, Copy, Debug)]
+pub struct TapResponse<F> {
+    func: F,
+    limit: usize,
+}
+
+impl<F> TapResponse<F> {
+    /// Sets the largest body, in bytes, that will be kept.
+    pub fn limit(mut self, max: usize) -> Self {
+        self.limit = max;
+        self
+    }
+}
+
+impl<FN, F> WrapSealed<F> for TapResponse<FN>
+where
+    FN: Fn(Bytes) + Clone + Send + Sync + 'static,
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithTapResponse<FN, F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithTapResponse {
+            filter,
+            tap: self.clone(),
+        }
+    }
+}
+
 /// Decorates a [`Filter`] to log requests and responses.
 #[derive(Clone, Copy, Debug)]
 pub struct Log<F> {
@@ -89,6 +164,7 @@ pub struct Info<'a> {
     route: &'a Route,
     start: Instant,
     status: StatusCode,
+    response_length: Option<u64>,
 }
 
 impl<FN, F> WrapSealed<F> for Log<FN>
@@ -134,6 +210,23 @@ impl<'a> Info<'a> {
         self.status
     }
 
+    /// View the length of the response body, in bytes.
+    ///
+    /// This is `None` when the length isn't known up front, such as for a
+    /// streaming body, and for rejections, whose response isn't made yet.
+    pub fn response_length(&self) -> Option<u64> {
+        self.response_length
+    }
+
+    /// View the pattern of the path filters that matched the request, such
+    /// as `/users/:param`.
+    ///
+    /// See [`path::matched_pattern`](crate::path::matched_pattern) for the
+    /// format. For a rejection, it's what was matched before rejecting.
+    pub fn matched_pattern(&self) -> String {
+        self.route.matched_pattern()
+    }
+
     /// View the referer of the request.
     pub fn referer(&self) -> Option<&str> {
         self.route
@@ -167,6 +260,15 @@ impl<'a> Info<'a> {
     pub fn request_headers(&self) -> &http::HeaderMap {
         self.route.headers()
     }
+
+    /// View the id of the request, if given one by
+    /// [`trace::request_id`](crate::trace::request_id).
+    pub fn request_id(&self) -> Option<&str> {
+        self.route
+            .extensions()
+            .get::<RequestId>()
+            .map(RequestId::as_str)
+    }
 }
 
 struct OptFmt<T>(Option<T>);
@@ -181,16 +283,32 @@ impl<T: fmt::Display> fmt::Display for OptFmt<T> {
     }
 }
 
+// Only written when there's a request id, keeping the default format otherwise.
+struct IdFmt<'a>(Option<&'a str>);
+
+impl fmt::Display for IdFmt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(id) => write!(f, " {}", id),
+            None => Ok(()),
+        }
+    }
+}
+
 mod internal {
     use std::future::Future;
     use std::pin::Pin;
     use std::task::{Context, Poll};
     use std::time::Instant;
 
-    use futures_util::{ready, TryFuture};
+    use bytes::{Bytes, BytesMut};
+    use futures_util::{ready, Stream, TryFuture};
+    use http::header::CONTENT_LENGTH;
+    use hyper::body::HttpBody;
+    use hyper::Body;
     use pin_project::pin_project;
 
-    use super::{Info, Log};
+    use super::{Info, Log, TapResponse};
     use crate::filter::{Filter, FilterBase, Internal};
     use crate::reject::IsReject;
     use crate::reply::{Reply, Response};
@@ -254,15 +372,16 @@ mod internal {
 
         fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
             let pin = self.as_mut().project();
-            let (result, status) = match ready!(pin.future.try_poll(cx)) {
+            let (result, status, response_length) = match ready!(pin.future.try_poll(cx)) {
                 Ok(reply) => {
                     let resp = reply.into_response();
                     let status = resp.status();
-                    (Poll::Ready(Ok((Logged(resp),))), status)
+                    let len = HttpBody::size_hint(resp.body()).exact();
+                    (Poll::Ready(Ok((Logged(resp),))), status, len)
                 }
                 Err(reject) => {
                     let status = reject.status();
-                    (Poll::Ready(Err(reject)), status)
+                    (Poll::Ready(Err(reject)), status, None)
                 }
             };
 
@@ -271,10 +390,123 @@ mod internal {
                     route,
                     start: self.started,
                     status,
+                    response_length,
                 });
             });
 
             result
         }
     }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithTapResponse<FN, F> {
+        pub(super) filter: F,
+        pub(super) tap: TapResponse<FN>,
+    }
+
+    impl<FN, F> FilterBase for WithTapResponse<FN, F>
+    where
+        FN: Fn(Bytes) + Clone + Send + Sync + 'static,
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Logged,);
+        type Error = F::Error;
+        type Future = WithTapResponseFuture<FN, F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            WithTapResponseFuture {
+                tap: self.tap.clone(),
+                future: self.filter.filter(Internal),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithTapResponseFuture<FN, F> {
+        tap: TapResponse<FN>,
+        #[pin]
+        future: F,
+    }
+
+    impl<FN, F> Future for WithTapResponseFuture<FN, F>
+    where
+        FN: Fn(Bytes) + Clone + Send + Sync + 'static,
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Logged,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let resp = ready!(pin.future.try_poll(cx))?.into_response();
+            let tap = pin.tap;
+            let (mut parts, body) = resp.into_parts();
+
+            let len = HttpBody::size_hint(&body).exact();
+            if len.is_some_and(|len| len > tap.limit as u64) {
+                tracing::trace!("tap_response body is over limit, passing through");
+                return Poll::Ready(Ok((Logged(Response::from_parts(parts, body)),)));
+            }
+            // The tee is a stream, so keep the length the body had.
+            if let Some(len) = len {
+                parts
+                    .headers
+                    .entry(CONTENT_LENGTH)
+                    .or_insert_with(|| len.into());
+            }
+
+            let tee = Tee {
+                body,
+                buf: Some(BytesMut::new()),
+                limit: tap.limit,
+                func: tap.func.clone(),
+            };
+            let body = Body::wrap_stream(tee);
+            Poll::Ready(Ok((Logged(Response::from_parts(parts, body)),)))
+        }
+    }
+
+    // Forwards the chunks of a body, keeping a copy to pass to `func` once
+    // it's complete.
+    #[pin_project]
+    struct Tee<FN> {
+        body: Body,
+        // None once the body is over the limit.
+        buf: Option<BytesMut>,
+        limit: usize,
+        func: FN,
+    }
+
+    impl<FN: Fn(Bytes)> Stream for Tee<FN> {
+        type Item = Result<Bytes, hyper::Error>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let pin = self.project();
+            let item = ready!(Pin::new(pin.body).poll_next(cx));
+            match item {
+                Some(Ok(ref chunk)) => match pin.buf {
+                    Some(buf) if buf.len() + chunk.len() <= *pin.limit => {
+                        buf.extend_from_slice(chunk);
+                    }
+                    Some(_) => {
+                        tracing::trace!("tap_response body is over limit, dropping copy");
+                        *pin.buf = None;
+                    }
+                    None => (),
+                },
+                Some(Err(_)) => *pin.buf = None,
+                None => {
+                    if let Some(buf) = pin.buf.take() {
+                        (pin.func)(buf.freeze());
+                    }
+                }
+            }
+            Poll::Ready(item)
+        }
+    }
 }
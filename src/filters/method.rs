@@ -9,7 +9,8 @@
 use futures_util::future;
 use http::Method;
 
-use crate::filter::{filter_fn, filter_fn_one, Filter, One};
+use self::internal::WithOverride;
+use crate::filter::{filter_fn, filter_fn_one, Filter, One, WrapSealed};
 use crate::reject::Rejection;
 use std::convert::Infallible;
 
@@ -106,7 +107,9 @@ pub fn patch() -> impl Filter<Extract = (), Error = Rejection> + Copy {
 
 /// Extract the `Method` from the request.
 ///
-/// This never rejects a request.
+/// This never rejects a request. Unlike [`get`], [`post`], and the others, it
+/// doesn't filter on the method, so a single handler can dispatch on it, such
+/// as for protocols with non-standard methods like WebDAV's `PROPFIND`.
 ///
 /// # Example
 ///
@@ -122,6 +125,66 @@ pub fn method() -> impl Filter<Extract = One<Method>, Error = Infallible> + Copy
     filter_fn_one(|route| future::ok::<_, Infallible>(route.method().clone()))
 }
 
+/// Wrap a `Filter` to let `POST` requests override their method with an
+/// `x-http-method-override` header.
+///
+/// HTML forms can only send `GET` and `POST`, so this lets them reach routes
+/// for other methods. While the wrapped filter runs, filters such as
+/// [`put`] or [`delete`] see the method named by the header instead of
+/// `POST`. Other filters see the request as it was.
+///
+/// Only `POST` requests can be overridden, so that safe methods like `GET`
+/// can't be turned into unsafe ones, and only to `PUT`, `PATCH` or `DELETE`
+/// by default, changed with [`MethodOverride::allow`]. Any other override is
+/// ignored, leaving the method as `POST`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let delete = warp::delete()
+///     .and(warp::path!("posts" / u32))
+///     .map(|id| format!("deleted post {}", id));
+///
+/// let route = delete.with(warp::method::with_override());
+/// ```
+pub fn with_override() -> MethodOverride {
+    MethodOverride {
+        allowed: vec![Method::PUT, Method::PATCH, Method::DELETE],
+    }
+}
+
+/// A wrapping filter that lets `POST` requests override their method.
+///
+/// Returned by [`with_override`].
+#[derive(Clone, Debug)]
+pub struct MethodOverride {
+    allowed: Vec<Method>,
+}
+
+impl MethodOverride {
+    /// Sets the methods that a `POST` request can be overridden to.
+    pub fn allow(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+        self.allowed = methods.into_iter().collect();
+        self
+    }
+}
+
+impl<F> WrapSealed<F> for MethodOverride
+where
+    F: Filter + Clone + Send + Sync + 'static,
+{
+    type Wrapped = WithOverride<F>;
+
+    fn wrap(&self, inner: F) -> Self::Wrapped {
+        WithOverride {
+            allowed: self.allowed.clone().into(),
+            inner,
+        }
+    }
+}
+
 // NOTE: This takes a static function instead of `&'static Method` directly
 // so that the `impl Filter` can be zero-sized. Moving it around should be
 // cheaper than holding a single static pointer (which would make it 1 word).
@@ -140,6 +203,93 @@ where
     })
 }
 
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll};
+
+    use futures_util::{ready, TryFuture};
+    use http::Method;
+    use pin_project::pin_project;
+
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::route;
+
+    const OVERRIDE_HEADER: &str = "x-http-method-override";
+
+    #[derive(Clone, Debug)]
+    pub struct WithOverride<F> {
+        pub(super) allowed: Arc<[Method]>,
+        pub(super) inner: F,
+    }
+
+    impl<F> FilterBase for WithOverride<F>
+    where
+        F: Filter,
+        F::Future: Future,
+    {
+        type Extract = F::Extract;
+        type Error = F::Error;
+        type Future = OverrideFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let overridden = route::with(|route| {
+                if route.method() != Method::POST {
+                    return false;
+                }
+                let method = match route
+                    .headers()
+                    .get(OVERRIDE_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(|value| {
+                        Method::from_bytes(value.trim().to_ascii_uppercase().as_bytes()).ok()
+                    }) {
+                    Some(method) => method,
+                    None => return false,
+                };
+                if !self.allowed.contains(&method) {
+                    tracing::debug!("method override to {} not allowed", method);
+                    return false;
+                }
+                tracing::trace!("overriding POST method with {}", method);
+                route.set_method(method);
+                true
+            });
+
+            OverrideFuture {
+                overridden,
+                inner: self.inner.filter(Internal),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct OverrideFuture<F> {
+        overridden: bool,
+        #[pin]
+        inner: F,
+    }
+
+    impl<F> Future for OverrideFuture<F>
+    where
+        F: TryFuture,
+    {
+        type Output = Result<F::Ok, F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let result = ready!(pin.inner.try_poll(cx));
+            if *pin.overridden {
+                // Filters after this one should see the request as it was.
+                route::with(|route| route.set_method(Method::POST));
+            }
+            Poll::Ready(result)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -0,0 +1,382 @@
+//! Metrics Filters
+//!
+//! Filters to count requests and time them, and to expose the results in
+//! the [Prometheus text format][format].
+//!
+//! The metrics are recorded into a [`Registry`], created once and cloned
+//! into every [`metrics()`] wrapper and [`exporter()`] that should share it.
+//!
+//! [format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, Method, StatusCode};
+
+use self::internal::WithMetrics;
+use crate::filter::{Filter, WrapSealed};
+use crate::reject::{IsReject, Rejection};
+use crate::reply::{Reply, Response};
+
+/// Create a wrapping [`Filter`](crate::Filter) that records metrics of the
+/// requests it replies to into the `registry`.
+///
+/// Two metrics are recorded, labeled by the request method and the route
+/// label set with [`Metrics::route`]:
+///
+/// - `warp_http_requests_total`, a counter also labeled by the class of the
+///   response status, such as `2xx`.
+/// - `warp_http_request_duration_seconds`, a histogram of the time taken to
+///   reply.
+///
+/// Methods other than the standard ones are all labeled `OTHER`, so that
+/// clients can't grow the number of series without bound.
+///
+/// Requests that the wrapped filter rejects aren't recorded, since another
+/// filter may still handle them. To also count those, wrap a filter that has
+/// been through [`recover`](crate::Filter::recover).
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::metrics::Registry;
+///
+/// let registry = Registry::new();
+///
+/// let users = warp::path("users")
+///     .map(|| "users")
+///     .with(warp::metrics(registry.clone()).route("users"));
+/// let posts = warp::path("posts")
+///     .map(|| "posts")
+///     .with(warp::metrics(registry.clone()).route("posts"));
+///
+/// let routes = users.or(posts).or(warp::metrics::exporter(registry));
+/// ```
+pub fn metrics(registry: Registry) -> Metrics {
+    Metrics {
+        registry,
+        route: "",
+    }
+}
+
+/// Creates a `Filter` that serves the metrics recorded into the `registry`
+/// at `GET /metrics`.
+///
+/// The reply is in the Prometheus text format, ready to be scraped.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::metrics::Registry;
+///
+/// let registry = Registry::new();
+///
+/// let api = warp::path("hello")
+///     .map(|| "world")
+///     .with(warp::metrics(registry.clone()).route("hello"));
+///
+/// let routes = api.or(warp::metrics::exporter(registry));
+/// ```
+pub fn exporter(
+    registry: Registry,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    crate::path("metrics")
+        .and(crate::path::end())
+        .and(crate::get())
+        .map(move || {
+            let mut res = Response::new(registry.render().into());
+            res.headers_mut().insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+            );
+            res
+        })
+}
+
+/// Decorates a [`Filter`] to record metrics of its replies.
+///
+/// Returned by [`metrics()`].
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    registry: Registry,
+    route: &'static str,
+}
+
+impl Metrics {
+    /// Set the `route` label of the recorded metrics.
+    ///
+    /// Use a fixed name for each route, like `"users"`, rather than anything
+    /// taken from the request, to keep the number of series small.
+    pub fn route(self, label: &'static str) -> Self {
+        Metrics {
+            route: label,
+            ..self
+        }
+    }
+}
+
+impl<F> WrapSealed<F> for Metrics
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithMetrics<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithMetrics {
+            filter,
+            metrics: self.clone(),
+        }
+    }
+}
+
+// Upper bounds, in seconds, of the latency histogram buckets.
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A registry of recorded metrics.
+///
+/// Create it once, and clone it into the [`metrics()`] wrappers and the
+/// [`exporter()`]. Separate registries, such as one per server or per test,
+/// don't see each other's metrics.
+#[derive(Clone, Default)]
+pub struct Registry {
+    inner: Arc<Mutex<Recorded>>,
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    fn record(&self, route: &'static str, method: &Method, status: StatusCode, elapsed: Duration) {
+        let method = method_label(method);
+        let class = status_class(status);
+        let secs = elapsed.as_secs_f64();
+
+        let mut recorded = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        *recorded.requests.entry((route, method, class)).or_insert(0) += 1;
+
+        let histogram = recorded.durations.entry((route, method)).or_default();
+        for (bucket, le) in histogram.buckets.iter_mut().zip(BUCKETS.iter()) {
+            if secs <= *le {
+                *bucket += 1;
+            }
+        }
+        histogram.sum += secs;
+        histogram.count += 1;
+    }
+
+    fn render(&self) -> String {
+        let recorded = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        // Writing to a `String` can't fail.
+        let _ = write_recorded(&recorded, &mut out);
+        out
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Registry").finish()
+    }
+}
+
+#[derive(Default)]
+struct Recorded {
+    // (route, method, status class) => count
+    requests: BTreeMap<(&'static str, &'static str, &'static str), u64>,
+    // (route, method) => histogram
+    durations: BTreeMap<(&'static str, &'static str), Histogram>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+fn write_recorded(recorded: &Recorded, out: &mut String) -> fmt::Result {
+    writeln!(
+        out,
+        "# HELP warp_http_requests_total Total number of HTTP requests replied to."
+    )?;
+    writeln!(out, "# TYPE warp_http_requests_total counter")?;
+    for ((route, method, class), count) in &recorded.requests {
+        writeln!(
+            out,
+            "warp_http_requests_total{{route=\"{}\",method=\"{}\",status=\"{}\"}} {}",
+            Escape(route),
+            method,
+            class,
+            count
+        )?;
+    }
+
+    writeln!(
+        out,
+        "# HELP warp_http_request_duration_seconds Time taken to reply to HTTP requests."
+    )?;
+    writeln!(out, "# TYPE warp_http_request_duration_seconds histogram")?;
+    for ((route, method), histogram) in &recorded.durations {
+        let labels = format!("route=\"{}\",method=\"{}\"", Escape(route), method);
+        for (count, le) in histogram.buckets.iter().zip(BUCKETS.iter()) {
+            writeln!(
+                out,
+                "warp_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels, le, count
+            )?;
+        }
+        writeln!(
+            out,
+            "warp_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+            labels, histogram.count
+        )?;
+        writeln!(
+            out,
+            "warp_http_request_duration_seconds_sum{{{}}} {}",
+            labels, histogram.sum
+        )?;
+        writeln!(
+            out,
+            "warp_http_request_duration_seconds_count{{{}}} {}",
+            labels, histogram.count
+        )?;
+    }
+    Ok(())
+}
+
+fn method_label(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "GET",
+        Method::POST => "POST",
+        Method::PUT => "PUT",
+        Method::DELETE => "DELETE",
+        Method::HEAD => "HEAD",
+        Method::OPTIONS => "OPTIONS",
+        Method::CONNECT => "CONNECT",
+        Method::PATCH => "PATCH",
+        Method::TRACE => "TRACE",
+        _ => "OTHER",
+    }
+}
+
+fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() / 100 {
+        1 => "1xx",
+        2 => "2xx",
+        3 => "3xx",
+        4 => "4xx",
+        _ => "5xx",
+    }
+}
+
+// Escapes a label value for the text format.
+struct Escape<'a>(&'a str);
+
+impl fmt::Display for Escape<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Instant;
+
+    use futures_util::{ready, TryFuture};
+    use pin_project::pin_project;
+
+    use super::{Metrics, Registry};
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::IsReject;
+    use crate::reply::{Reply, Response};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    pub struct Measured(pub(super) Response);
+
+    impl Reply for Measured {
+        #[inline]
+        fn into_response(self) -> Response {
+            self.0
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone)]
+    pub struct WithMetrics<F> {
+        pub(super) filter: F,
+        pub(super) metrics: Metrics,
+    }
+
+    impl<F> FilterBase for WithMetrics<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Measured,);
+        type Error = F::Error;
+        type Future = WithMetricsFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            WithMetricsFuture {
+                registry: self.metrics.registry.clone(),
+                route: self.metrics.route,
+                future: self.filter.filter(Internal),
+                started: tokio::time::Instant::now().into_std(),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithMetricsFuture<F> {
+        registry: Registry,
+        route: &'static str,
+        #[pin]
+        future: F,
+        started: Instant,
+    }
+
+    impl<F> Future for WithMetricsFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+        F::Error: IsReject,
+    {
+        type Output = Result<(Measured,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let resp = ready!(pin.future.try_poll(cx))?.into_response();
+            let elapsed = tokio::time::Instant::now().into_std() - *pin.started;
+            let label = *pin.route;
+            let registry = pin.registry;
+            route::with(|route| {
+                registry.record(label, route.method(), resp.status(), elapsed);
+            });
+            Poll::Ready(Ok((Measured(resp),)))
+        }
+    }
+}
@@ -5,25 +5,38 @@
 
 pub mod addr;
 pub mod any;
+pub mod auth;
 pub mod body;
+pub mod cache;
 #[cfg(any(feature = "compression-brotli", feature = "compression-gzip"))]
 pub mod compression;
 pub mod cookie;
 pub mod cors;
+pub mod csrf;
 pub mod ext;
 pub mod fs;
+pub mod head;
 pub mod header;
+pub mod health;
 pub mod host;
 pub mod log;
 pub mod method;
+pub mod metrics;
 #[cfg(feature = "multipart")]
 pub mod multipart;
 pub mod path;
 pub mod query;
 pub mod reply;
+pub mod shutdown;
+#[cfg(feature = "sign")]
+pub mod sign;
 pub mod sse;
+pub mod timeout;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod trace;
+pub mod upgrade;
 #[cfg(feature = "websocket")]
 pub mod ws;
 
-pub use crate::filter::BoxedFilter;
+pub use crate::filter::{BoxedFilter, DynFilter};
@@ -5,18 +5,28 @@
 use std::error::Error as StdError;
 use std::fmt::{Display, Formatter};
 use std::future::Future;
+use std::marker::PhantomData;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::{fmt, io};
 
 use bytes::{Buf, Bytes};
-use futures_util::{future, Stream};
+use futures_util::{future, Stream, StreamExt, TryStreamExt};
 use headers::ContentType;
 use hyper::Body;
 use mime::Mime;
-use multer::{Field as PartInner, Multipart as FormDataInner};
+use multer::{Constraints, Field as PartInner, Multipart as FormDataInner, SizeLimit};
+use serde::de::value::{Error as DeError, MapDeserializer, SeqDeserializer};
+use serde::de::{
+    self, DeserializeOwned, Deserializer, IgnoredAny, IntoDeserializer, MapAccess, Unexpected,
+    Visitor,
+};
+use serde::Deserialize;
 
 use crate::filter::{Filter, FilterBase, Internal};
+use crate::filters::body::BodyDeserializeError;
 use crate::reject::{self, Rejection};
 
 // If not otherwise configured, default to 2MB.
@@ -28,6 +38,8 @@ const DEFAULT_FORM_DATA_MAX_LENGTH: u64 = 1024 * 1024 * 2;
 #[derive(Debug, Clone)]
 pub struct FormOptions {
     max_length: Option<u64>,
+    max_part_size: Option<u64>,
+    max_parts: Option<usize>,
 }
 
 /// A `Stream` of multipart/form-data `Part`s.
@@ -35,6 +47,8 @@ pub struct FormOptions {
 /// Extracted with a `warp::multipart::form` filter.
 pub struct FormData {
     inner: FormDataInner<'static>,
+    // Parts left before `max_parts` is exceeded, if limited.
+    remaining_parts: Option<usize>,
 }
 
 /// A single "part" of a multipart/form-data body.
@@ -42,6 +56,30 @@ pub struct FormData {
 /// Yielded from the `FormData` stream.
 pub struct Part {
     part: PartInner<'static>,
+    // Set once the whole body is read, when driven by `try_for_each_part`.
+    finished: Option<Arc<AtomicBool>>,
+    // The chunk after the one last returned, read ahead to find the end.
+    peeked: Option<Result<Bytes, crate::Error>>,
+}
+
+/// A [`Filter`](crate::Filter) to deserialize a `multipart/form-data` body
+/// into a `T`.
+///
+/// Create with the `warp::multipart::typed()` function.
+pub struct TypedForm<T> {
+    form: FormOptions,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// A file uploaded in a multipart/form-data body.
+///
+/// Used as a field of a struct extracted with `warp::multipart::typed`, for
+/// parts that have a filename.
+#[derive(Debug)]
+pub struct UploadedFile {
+    filename: String,
+    content_type: Option<String>,
+    data: Bytes,
 }
 
 /// Create a [`Filter`](crate::Filter) to extract a `multipart/form-data` body from a request.
@@ -51,6 +89,47 @@ pub struct Part {
 pub fn form() -> FormOptions {
     FormOptions {
         max_length: Some(DEFAULT_FORM_DATA_MAX_LENGTH),
+        max_part_size: None,
+        max_parts: None,
+    }
+}
+
+/// Create a [`Filter`](crate::Filter) to deserialize a `multipart/form-data`
+/// body into a `T`.
+///
+/// Each part is read into memory and given to `T` as a field of its name.
+/// Text parts can be deserialized into strings, numbers, booleans or unit
+/// enums, and parts with a filename into an [`UploadedFile`]. A name sent more
+/// than once can be collected into a `Vec`.
+///
+/// A body that can't be deserialized, such as one missing a required field,
+/// is rejected with a `400 Bad Request`. Like [`form`], the body is limited to
+/// 2MB by default.
+///
+/// # Example
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use warp::multipart::UploadedFile;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// struct Upload {
+///     title: String,
+///     tags: Vec<String>,
+///     photo: UploadedFile,
+/// }
+///
+/// let route = warp::post()
+///     .and(warp::multipart::typed::<Upload>().max_length(1024 * 1024 * 10))
+///     .map(|upload: Upload| {
+///         format!("{} is {} bytes", upload.title, upload.photo.data().len())
+///     });
+/// ```
+pub fn typed<T: DeserializeOwned + Send + 'static>() -> TypedForm<T> {
+    TypedForm {
+        form: form(),
+        _marker: PhantomData,
     }
 }
 
@@ -65,6 +144,24 @@ impl FormOptions {
         self.max_length = max.into();
         self
     }
+
+    /// Set the maximum byte length allowed for each part.
+    ///
+    /// Reading a part past this length fails with a [`PartTooLarge`] error.
+    /// Not limited by default, besides the limit for the whole body.
+    pub fn max_part_size(mut self, max: u64) -> Self {
+        self.max_part_size = Some(max);
+        self
+    }
+
+    /// Set the maximum number of parts allowed in this body.
+    ///
+    /// The `FormData` stream fails with a [`TooManyParts`] error in place of
+    /// the first part past this count. Not limited by default.
+    pub fn max_parts(mut self, max: usize) -> Self {
+        self.max_parts = Some(max);
+        self
+    }
 }
 
 type FormFut = Pin<Box<dyn Future<Output = Result<(FormData,), Rejection>> + Send>>;
@@ -84,12 +181,19 @@ impl FilterBase for FormOptions {
             future::ready(mime)
         });
 
+        let max_part_size = self.max_part_size;
+        let max_parts = self.max_parts;
         let filt = boundary
             .and(super::body::body())
-            .map(|boundary: String, body| {
+            .map(move |boundary: String, body| {
                 let body = BodyIoError(body);
+                let mut constraints = Constraints::new();
+                if let Some(max) = max_part_size {
+                    constraints = constraints.size_limit(SizeLimit::new().per_field(max));
+                }
                 FormData {
-                    inner: FormDataInner::new(body, &boundary),
+                    inner: FormDataInner::with_constraints(body, &boundary, constraints),
+                    remaining_parts: max_parts,
                 }
             });
 
@@ -105,8 +209,179 @@ impl FilterBase for FormOptions {
     }
 }
 
+// ===== impl TypedForm =====
+
+impl<T> TypedForm<T> {
+    /// Set the maximum byte length allowed for this body.
+    ///
+    /// `max_length(None)` means that maximum byte length is not checked.
+    /// Defaults to 2MB.
+    pub fn max_length(mut self, max: impl Into<Option<u64>>) -> Self {
+        self.form = self.form.max_length(max);
+        self
+    }
+
+    /// Set the maximum byte length allowed for each part.
+    ///
+    /// A part past this length is rejected with a `413 Payload Too Large`.
+    /// Not limited by default, besides the limit for the whole body.
+    pub fn max_part_size(mut self, max: u64) -> Self {
+        self.form = self.form.max_part_size(max);
+        self
+    }
+
+    /// Set the maximum number of parts allowed in this body.
+    ///
+    /// A body with more parts is rejected with a `413 Payload Too Large`.
+    /// Not limited by default.
+    pub fn max_parts(mut self, max: usize) -> Self {
+        self.form = self.form.max_parts(max);
+        self
+    }
+}
+
+impl<T> Clone for TypedForm<T> {
+    fn clone(&self) -> Self {
+        TypedForm {
+            form: self.form.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TypedForm<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedForm")
+            .field("max_length", &self.form.max_length)
+            .field("max_part_size", &self.form.max_part_size)
+            .field("max_parts", &self.form.max_parts)
+            .finish()
+    }
+}
+
+type TypedFormFut<T> = Pin<Box<dyn Future<Output = Result<(T,), Rejection>> + Send>>;
+
+impl<T> FilterBase for TypedForm<T>
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    type Extract = (T,);
+    type Error = Rejection;
+    type Future = TypedFormFut<T>;
+
+    fn filter(&self, _: Internal) -> Self::Future {
+        let form = self.form.filter(Internal);
+        Box::pin(async move {
+            let (form,) = form.await?;
+            let fields = read_fields(form).await.map_err(|err| {
+                tracing::debug!("multipart body error: {}", err);
+                match err.source() {
+                    Some(cause) if cause.is::<PartTooLarge>() => {
+                        reject::known(PartTooLarge { _p: () })
+                    }
+                    Some(cause) if cause.is::<TooManyParts>() => {
+                        reject::known(TooManyParts { _p: () })
+                    }
+                    _ => reject::known(BodyDeserializeError { cause: err.into() }),
+                }
+            })?;
+            T::deserialize(MapDeserializer::new(fields.into_iter()))
+                .map(|val| (val,))
+                .map_err(|err| {
+                    tracing::debug!("multipart deserialize error: {}", err);
+                    reject::known(BodyDeserializeError { cause: err.into() })
+                })
+        })
+    }
+}
+
+// Reads every part into memory, grouping the values of repeated names.
+async fn read_fields(mut form: FormData) -> Result<Vec<(String, Values)>, crate::Error> {
+    let mut fields: Vec<(String, Values)> = Vec::new();
+    while let Some(mut part) = form.try_next().await? {
+        let mut data = Vec::new();
+        while let Some(chunk) = future::poll_fn(|cx| part.poll_next(cx)).await {
+            data.extend_from_slice(&chunk?);
+        }
+
+        let value = match part.filename() {
+            Some(filename) => Value::File(UploadedFile {
+                filename: filename.to_owned(),
+                content_type: part.content_type().map(ToOwned::to_owned),
+                data: data.into(),
+            }),
+            None => Value::Text(String::from_utf8(data).map_err(crate::Error::new)?),
+        };
+
+        let name = part.name();
+        match fields.iter_mut().find(|(n, _)| n == name) {
+            Some((_, values)) => values.0.push(value),
+            None => fields.push((name.to_owned(), Values(vec![value]))),
+        }
+    }
+    Ok(fields)
+}
+
 // ===== impl FormData =====
 
+impl FormData {
+    /// Handle each `Part` in turn, as it arrives.
+    ///
+    /// The next part is only read once `f` has finished with the previous
+    /// one, so at most one part is held at a time, and a large upload can be
+    /// written out as it streams in. `f` must read the whole body of each
+    /// part, with [`Part::data`] or [`Part::stream`]. If a part is dropped
+    /// before its body is read to the end, this stops with a
+    /// [`PartNotConsumed`] error, instead of silently skipping the rest of
+    /// it.
+    ///
+    /// A body is read to the end once `data` returns `None`, or the stream
+    /// ends. A small part that has fully arrived also counts as read once
+    /// its last chunk is returned, so a single call to `data` is enough for
+    /// it, but only reading until `None` is sure to work for any part.
+    ///
+    /// Stops at the first error, whether reading the form or from `f`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use futures_util::TryStreamExt;
+    /// use warp::multipart::{FormData, Part};
+    /// use warp::Filter;
+    ///
+    /// let route = warp::multipart::form().and_then(|form: FormData| async move {
+    ///     form.try_for_each_part(|part: Part| async move {
+    ///         let mut data = part.stream();
+    ///         while let Some(_buf) = data.try_next().await? {
+    ///             // write the buffer out, such as to a file...
+    ///         }
+    ///         Ok::<_, warp::Error>(())
+    ///     })
+    ///     .await
+    ///     .map_err(|_| warp::reject())?;
+    ///     Ok::<_, warp::Rejection>("uploaded")
+    /// });
+    /// ```
+    pub async fn try_for_each_part<F, Fut, E>(mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(Part) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+        E: From<crate::Error>,
+    {
+        while let Some(part) = self.next().await {
+            let mut part = part?;
+            let finished = Arc::new(AtomicBool::new(false));
+            part.finished = Some(finished.clone());
+            f(part).await?;
+            if !finished.load(Ordering::Acquire) {
+                tracing::debug!("multipart part dropped before its body was read");
+                return Err(crate::Error::new(PartNotConsumed { _p: () }).into());
+            }
+        }
+        Ok(())
+    }
+}
+
 impl fmt::Debug for FormData {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("FormData").finish()
@@ -120,14 +395,25 @@ impl Stream for FormData {
         match self.inner.poll_next_field(cx) {
             Poll::Pending => Poll::Pending,
             Poll::Ready(Ok(Some(part))) => {
+                match self.remaining_parts {
+                    Some(0) => {
+                        return Poll::Ready(Some(Err(crate::Error::new(TooManyParts { _p: () }))))
+                    }
+                    Some(ref mut remaining) => *remaining -= 1,
+                    None => (),
+                }
                 if part.name().is_some() || part.file_name().is_some() {
-                    Poll::Ready(Some(Ok(Part { part })))
+                    Poll::Ready(Some(Ok(Part {
+                        part,
+                        finished: None,
+                        peeked: None,
+                    })))
                 } else {
                     Poll::Ready(Some(Err(crate::Error::new(MultipartFieldMissingName))))
                 }
             }
             Poll::Ready(Ok(None)) => Poll::Ready(None),
-            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(crate::Error::new(err)))),
+            Poll::Ready(Err(err)) => Poll::Ready(Some(Err(multer_error(err)))),
         }
     }
 }
@@ -148,6 +434,9 @@ impl Part {
     }
 
     /// Get the content-type of this part, if present.
+    ///
+    /// This is the full type, such as `image/jpeg` or
+    /// `text/plain; charset=utf-8`, including the subtype and any parameters.
     pub fn content_type(&self) -> Option<&str> {
         let content_type = self.part.content_type();
         content_type.map(|t| t.as_ref())
@@ -164,11 +453,32 @@ impl Part {
     }
 
     fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, crate::Error>>> {
+        let item = match self.peeked.take() {
+            Some(item) => Poll::Ready(Some(item)),
+            None => self.poll_part(cx),
+        };
+        // When driven by `try_for_each_part`, read ahead if the next chunk is
+        // already buffered, so a body read to its last chunk counts as read
+        // even if it isn't polled again.
+        if let (Poll::Ready(Some(Ok(_))), Some(_)) = (&item, &self.finished) {
+            if let Poll::Ready(Some(next)) = self.poll_part(cx) {
+                self.peeked = Some(next);
+            }
+        }
+        item
+    }
+
+    fn poll_part(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<Bytes, crate::Error>>> {
         match Pin::new(&mut self.part).poll_next(cx) {
             Poll::Pending => Poll::Pending,
             Poll::Ready(Some(Ok(bytes))) => Poll::Ready(Some(Ok(bytes))),
-            Poll::Ready(None) => Poll::Ready(None),
-            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(crate::Error::new(err)))),
+            Poll::Ready(None) => {
+                if let Some(ref finished) = self.finished {
+                    finished.store(true, Ordering::Release);
+                }
+                Poll::Ready(None)
+            }
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(multer_error(err)))),
         }
     }
 }
@@ -190,6 +500,307 @@ impl fmt::Debug for Part {
     }
 }
 
+// ===== impl UploadedFile =====
+
+impl UploadedFile {
+    /// Get the filename the file was uploaded with.
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    /// Get the content-type of the file, if present.
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    /// Get the contents of the file.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Convert this `UploadedFile` into its contents.
+    pub fn into_data(self) -> Bytes {
+        self.data
+    }
+}
+
+impl<'de> Deserialize<'de> for UploadedFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FileVisitor;
+
+        // Accepts the file contents as an owned `Vec`, which converts back
+        // into `Bytes` without a copy. Getting that `Vec` out of the part's
+        // `Bytes` copies them, unless they were the only reference.
+        struct Data(Vec<u8>);
+
+        impl<'de> Deserialize<'de> for Data {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct DataVisitor;
+
+                impl<'de> Visitor<'de> for DataVisitor {
+                    type Value = Data;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str("file contents")
+                    }
+
+                    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Data, E> {
+                        Ok(Data(v.to_vec()))
+                    }
+
+                    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Data, E> {
+                        Ok(Data(v))
+                    }
+                }
+
+                deserializer.deserialize_byte_buf(DataVisitor)
+            }
+        }
+
+        impl<'de> Visitor<'de> for FileVisitor {
+            type Value = UploadedFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an uploaded file")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<UploadedFile, A::Error> {
+                let mut filename = None;
+                let mut content_type = None;
+                let mut data = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "filename" => filename = Some(map.next_value()?),
+                        "content_type" => content_type = Some(map.next_value()?),
+                        "data" => data = Some(map.next_value::<Data>()?.0),
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(UploadedFile {
+                    filename: filename.ok_or_else(|| de::Error::missing_field("filename"))?,
+                    content_type,
+                    data: data.ok_or_else(|| de::Error::missing_field("data"))?.into(),
+                })
+            }
+        }
+
+        deserializer.deserialize_map(FileVisitor)
+    }
+}
+
+// ===== Deserializing parts =====
+
+// All of the values sent with one name.
+struct Values(Vec<Value>);
+
+enum Value {
+    Text(String),
+    File(UploadedFile),
+}
+
+// A field of an `UploadedFile`, handed to its `Deserialize` impl.
+enum FileField {
+    Str(String),
+    Data(Vec<u8>),
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+macro_rules! parse_text {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+                match self {
+                    Value::Text(text) => match text.parse() {
+                        Ok(val) => visitor.$visit(val),
+                        Err(_) => Err(de::Error::invalid_value(Unexpected::Str(&text), &visitor)),
+                    },
+                    file => file.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+impl Values {
+    fn single(mut self) -> Result<Value, DeError> {
+        if self.0.len() == 1 {
+            Ok(self.0.remove(0))
+        } else {
+            Err(de::Error::custom("expected a single value"))
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Values {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Values {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        if self.0.len() == 1 {
+            self.single()?.deserialize_any(visitor)
+        } else {
+            self.deserialize_seq(visitor)
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.into_iter()))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_value! {
+        deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_f32 deserialize_f64
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            Value::Text(text) => visitor.visit_string(text),
+            Value::File(file) => {
+                let mut fields = vec![
+                    ("filename", FileField::Str(file.filename)),
+                    ("data", FileField::Data(file.data.into())),
+                ];
+                if let Some(content_type) = file.content_type {
+                    fields.push(("content_type", FileField::Str(content_type)));
+                }
+                visitor.visit_map(MapDeserializer::new(fields.into_iter()))
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self {
+            Value::Text(text) => text
+                .into_deserializer()
+                .deserialize_enum(name, variants, visitor),
+            file => file.deserialize_any(visitor),
+        }
+    }
+
+    parse_text! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    serde::forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple tuple_struct
+        map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, DeError> for FileField {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> Deserializer<'de> for FileField {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self {
+            FileField::Str(s) => visitor.visit_string(s),
+            FileField::Data(data) => visitor.visit_byte_buf(data),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        visitor.visit_some(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map
+        struct enum identifier ignored_any
+    }
+}
+
 struct PartStream(Part);
 
 impl Stream for PartStream {
@@ -217,6 +828,28 @@ impl Stream for BodyIoError {
     }
 }
 
+fn multer_error(err: multer::Error) -> crate::Error {
+    match err {
+        multer::Error::FieldSizeExceeded { .. } => crate::Error::new(PartTooLarge { _p: () }),
+        err => crate::Error::new(err),
+    }
+}
+
+unit_error! {
+    /// An error used when a multipart part is larger than `max_part_size`.
+    pub PartTooLarge: "Multipart part is too large"
+}
+
+unit_error! {
+    /// An error used when a multipart body has more than `max_parts` parts.
+    pub TooManyParts: "Multipart body has too many parts"
+}
+
+unit_error! {
+    /// An error used when a multipart part is dropped before its body is read.
+    pub PartNotConsumed: "Multipart part body was not fully read"
+}
+
 /// An error used when a multipart field is missing a name.
 #[derive(Debug)]
 struct MultipartFieldMissingName;
@@ -4,6 +4,7 @@
 //!
 //! - [`path`](./fn.path.html) matches a specific segment, like `/foo`.
 //! - [`param`](./fn.param.html) tries to parse a segment into a type, like `/:u16`.
+//! - [`param_in`](./fn.param_in.html) parses a segment into a number within a range.
 //! - [`end`](./fn.end.html) matches when the path end is found.
 //! - [`path!`](../../macro.path.html) eases combining multiple `path` and `param` filters.
 //!
@@ -131,11 +132,13 @@ use std::str::FromStr;
 
 use futures_util::future;
 use http::uri::PathAndQuery;
+use serde::de::value::SeqDeserializer;
+use serde::de::{self, DeserializeOwned, Deserializer, IntoDeserializer, Visitor};
 
-use self::internal::Opaque;
-use crate::filter::{filter_fn, one, Filter, FilterBase, Internal, One, Tuple};
-use crate::reject::{self, Rejection};
-use crate::route::{self, Route};
+use self::internal::{Opaque, RewriteFilter};
+use crate::filter::{filter_fn, one, Filter, FilterBase, Internal, One, Tuple, WrapSealed};
+use crate::reject::{self, CombineRejection, Rejection};
+use crate::route::{self, Route, Segment};
 
 /// Create an exact match path segment [`Filter`](crate::Filter).
 ///
@@ -208,7 +211,7 @@ where
     fn filter(&self, _: Internal) -> Self::Future {
         route::with(|route| {
             let p = self.0.as_ref();
-            future::ready(with_segment(route, |seg| {
+            future::ready(with_segment(route, Segment::Exact, |seg| {
                 tracing::trace!("{:?}?: {:?}", p, seg);
 
                 if seg == p {
@@ -265,7 +268,7 @@ pub fn end() -> impl Filter<Extract = (), Error = Rejection> + Copy {
 /// ```
 pub fn param<T: FromStr + Send + 'static>(
 ) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
-    filter_segment(|seg| {
+    filter_segment(Segment::Param, |seg| {
         tracing::trace!("param?: {:?}", seg);
         if seg.is_empty() {
             return Err(reject::not_found());
@@ -274,6 +277,118 @@ pub fn param<T: FromStr + Send + 'static>(
     })
 }
 
+/// Extract a parameter from a path segment, requiring it to be in a range.
+///
+/// This works like [`param`], and additionally checks that the value is
+/// between `min` and `max`, inclusive.
+///
+/// If the value could not be parsed, rejects with a `404 Not Found`. If it is
+/// outside of the range, rejects with a `400 Bad Request`, with a
+/// [`ParamOutOfRange`] describing the bounds.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::path("page")
+///     .and(warp::path::param_in(1u32, 100))
+///     .map(|page: u32| {
+///         format!("You asked for page {}", page)
+///     });
+/// ```
+pub fn param_in<T>(min: T, max: T) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy
+where
+    T: FromStr + PartialOrd + fmt::Display + Copy + Send + 'static,
+{
+    filter_segment(Segment::Param, move |seg| {
+        tracing::trace!("param_in?({}, {}): {:?}", min, max, seg);
+        if seg.is_empty() {
+            return Err(reject::not_found());
+        }
+        let value = T::from_str(seg).map_err(|_| reject::not_found())?;
+        if value < min || value > max {
+            tracing::debug!("param {} out of range {}..={}", seg, min, max);
+            return Err(reject::known(ParamOutOfRange {
+                min: min.to_string(),
+                max: max.to_string(),
+            }));
+        }
+        Ok(one(value))
+    })
+}
+
+/// Extract all of the remaining path segments by deserializing them into a
+/// `T`, such as a tuple or a struct.
+///
+/// Each segment is a value of the sequence, in order, so the fields of a
+/// struct are filled in the order they're declared, rather than by name.
+/// Segments are parsed like [`param`], with numbers, booleans and strings
+/// supported, as well as `Option`s and enums of unit variants.
+///
+/// If there are too many or too few segments, or one of them can't be
+/// parsed, rejects with a `404 Not Found`, with an [`InvalidPathParams`]
+/// describing the error.
+///
+/// # Example
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// struct PostPath {
+///     user_id: u64,
+///     post_id: u64,
+/// }
+///
+/// // GET /users/7/posts/42
+/// let route = warp::path("users")
+///     .and(warp::path::params())
+///     .map(|(user_id, _, post_id): (u64, String, u64)| {
+///         format!("post {} of user {}", post_id, user_id)
+///     });
+///
+/// // GET /posts/7/42
+/// let route = warp::path("posts")
+///     .and(warp::path::params())
+///     .map(|p: PostPath| format!("post {} of user {}", p.post_id, p.user_id));
+/// ```
+pub fn params<T>() -> impl Filter<Extract = One<T>, Error = Rejection> + Copy
+where
+    T: DeserializeOwned + Send + 'static,
+{
+    filter_fn(move |route| {
+        let path = route.path();
+        tracing::trace!("params?: {:?}", path);
+        let mut segments = path.split('/').collect::<Vec<_>>();
+        // A trailing slash doesn't make for another segment.
+        if segments.last() == Some(&"") {
+            segments.pop();
+        }
+        let lens = segments.iter().map(|seg| seg.len()).collect::<Vec<_>>();
+
+        let deserializer = SeqDeserializer::new(
+            segments
+                .into_iter()
+                .enumerate()
+                .map(|(index, value)| SegmentDeserializer { index, value }),
+        );
+        let result = T::deserialize(deserializer).map_err(|cause| {
+            tracing::debug!("path params error: {}", cause);
+            reject::known(InvalidPathParams { cause })
+        });
+        if result.is_ok() {
+            for len in lens {
+                let start = route.matched_path_index();
+                route.set_unmatched_path(len);
+                route.push_matched_segment(start, Segment::Param);
+            }
+        }
+        future::ready(result.map(one))
+    })
+}
+
 /// Extract the unmatched tail of the path.
 ///
 /// This will return a `Tail`, which allows access to the rest of the path
@@ -300,6 +415,7 @@ pub fn tail() -> impl Filter<Extract = One<Tail>, Error = Infallible> + Copy {
         // has been matched now.
         let end = path.path().len() - idx;
         route.set_unmatched_path(end);
+        route.push_matched_segment(idx, Segment::Tail);
 
         future::ok(one(Tail {
             path,
@@ -432,15 +548,248 @@ impl fmt::Debug for FullPath {
     }
 }
 
-fn filter_segment<F, U>(func: F) -> impl Filter<Extract = U, Error = Rejection> + Copy
+/// Returns the pattern of the path filters matched so far.
+///
+/// Exact segments appear as themselves, parameters as `:param`, and a
+/// [`tail()`] as `*`. Unlike the [`full()`] path, the pattern doesn't depend
+/// on the values of parameters, which makes it useful to label metrics.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::path!("users" / u32)
+///     .and(warp::path::matched_pattern())
+///     .map(|id: u32, pattern: String| {
+///         // GET /users/42 would have the pattern "/users/:param".
+///         format!("user {} matched {}", id, pattern)
+///     });
+/// ```
+pub fn matched_pattern() -> impl Filter<Extract = One<String>, Error = Infallible> + Copy {
+    filter_fn(move |route| future::ok(one(route.matched_pattern())))
+}
+
+/// Create a wrapping [`Filter`](crate::Filter) that rewrites the request path
+/// before the wrapped filter sees it.
+///
+/// The rewriter is given the unmatched part of the path, starting with a `/`,
+/// and returns the path that should replace it. The query string of the
+/// request is kept. If the rewriter returns `None`, the request is rejected
+/// with a `404 Not Found`.
+///
+/// The path is only rewritten for the wrapped filter. Once it has finished,
+/// the original path is put back, so other filters, such as those combined
+/// with `or`, see the request as it was.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// // `GET /old/:id` is served by `GET /items/:id`.
+/// let items = warp::path!("items" / u32).map(|id| format!("item {}", id));
+///
+/// let route = items.with(warp::path::rewrite(|path: &str| {
+///     match path.strip_prefix("/old/") {
+///         Some(id) => Some(format!("/items/{}", id)),
+///         None => Some(path.to_owned()),
+///     }
+/// }));
+/// ```
+pub fn rewrite<R>(rewriter: R) -> Rewrite<R>
+where
+    R: Fn(&str) -> Option<String>,
+{
+    Rewrite { rewriter }
+}
+
+/// A wrapping filter that rewrites the request path.
+///
+/// Returned by [`rewrite`].
+#[derive(Clone, Copy)]
+pub struct Rewrite<R> {
+    rewriter: R,
+}
+
+impl<F, R> WrapSealed<F> for Rewrite<R>
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Extract: Send,
+    F::Error: CombineRejection<Rejection>,
+    R: Fn(&str) -> Option<String> + Clone + Send + Sync + 'static,
+{
+    type Wrapped = RewriteFilter<F, R>;
+
+    fn wrap(&self, inner: F) -> Self::Wrapped {
+        RewriteFilter {
+            inner,
+            rewriter: self.rewriter.clone(),
+        }
+    }
+}
+
+impl<R> fmt::Debug for Rewrite<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rewrite").finish()
+    }
+}
+
+/// An error used in rejections when a path parameter is out of range.
+///
+/// Returned by [`param_in`].
+#[derive(Debug)]
+pub struct ParamOutOfRange {
+    min: String,
+    max: String,
+}
+
+impl ParamOutOfRange {
+    /// The minimum value allowed.
+    pub fn min(&self) -> &str {
+        &self.min
+    }
+
+    /// The maximum value allowed.
+    pub fn max(&self) -> &str {
+        &self.max
+    }
+}
+
+impl fmt::Display for ParamOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Path parameter out of range, must be between {} and {}",
+            self.min, self.max
+        )
+    }
+}
+
+impl std::error::Error for ParamOutOfRange {}
+
+/// An error used in rejections when path segments can't be deserialized.
+///
+/// Returned by [`params`].
+#[derive(Debug)]
+pub struct InvalidPathParams {
+    cause: serde::de::value::Error,
+}
+
+impl fmt::Display for InvalidPathParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid path parameters: {}", self.cause)
+    }
+}
+
+impl std::error::Error for InvalidPathParams {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.cause)
+    }
+}
+
+// Deserializes a single path segment, parsing it into the requested type.
+struct SegmentDeserializer<'a> {
+    index: usize,
+    value: &'a str,
+}
+
+impl<'a> SegmentDeserializer<'a> {
+    fn parse<T>(&self) -> Result<T, serde::de::value::Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.value.parse().map_err(|err| {
+            de::Error::custom(format_args!(
+                "segment {} ({:?}): {}",
+                self.index + 1,
+                self.value,
+                err
+            ))
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for SegmentDeserializer<'de> {
+    type Error = serde::de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value)
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.value.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de> for SegmentDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+fn filter_segment<F, U>(
+    kind: Segment,
+    func: F,
+) -> impl Filter<Extract = U, Error = Rejection> + Copy
 where
     F: Fn(&str) -> Result<U, Rejection> + Copy,
     U: Tuple + Send + 'static,
 {
-    filter_fn(move |route| future::ready(with_segment(route, func)))
+    filter_fn(move |route| future::ready(with_segment(route, kind, func)))
 }
 
-fn with_segment<F, U>(route: &mut Route, func: F) -> Result<U, Rejection>
+fn with_segment<F, U>(route: &mut Route, kind: Segment, func: F) -> Result<U, Rejection>
 where
     F: Fn(&str) -> Result<U, Rejection>,
 {
@@ -448,7 +797,9 @@ where
     let ret = func(seg);
     if ret.is_ok() {
         let idx = seg.len();
+        let start = route.matched_path_index();
         route.set_unmatched_path(idx);
+        route.push_matched_segment(start, kind);
     }
     ret
 }
@@ -607,6 +958,87 @@ macro_rules! __internal_path {
 fn _path_macro_compile_fail() {}
 
 mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_util::{future, ready, TryFuture};
+    use pin_project::pin_project;
+
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, CombineRejection, Rejection};
+    use crate::route;
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct RewriteFilter<F, R> {
+        pub(super) inner: F,
+        pub(super) rewriter: R,
+    }
+
+    impl<F, R> FilterBase for RewriteFilter<F, R>
+    where
+        F: Filter,
+        F::Extract: Send,
+        F::Error: CombineRejection<Rejection>,
+        R: Fn(&str) -> Option<String>,
+    {
+        type Extract = F::Extract;
+        type Error = <F::Error as CombineRejection<Rejection>>::One;
+        type Future = future::Either<
+            future::Ready<Result<Self::Extract, Self::Error>>,
+            RewriteFuture<F::Future>,
+        >;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let rewritten = route::with(|route| {
+                let path = format!("/{}", route.path());
+                let rewritten = (self.rewriter)(&path).ok_or_else(|| {
+                    tracing::debug!("path rewrite rejected {:?}", path);
+                    reject::not_found()
+                })?;
+                tracing::trace!("path rewrite {:?} to {:?}", path, rewritten);
+                route.rewrite_unmatched_path(&rewritten).map_err(|err| {
+                    tracing::warn!("path rewrite to {:?} is invalid: {}", rewritten, err);
+                    reject::not_found()
+                })
+            });
+
+            match rewritten {
+                Ok(original) => future::Either::Right(RewriteFuture {
+                    inner: self.inner.filter(Internal),
+                    original: Some(original),
+                }),
+                Err(rejection) => future::Either::Left(future::err(rejection.into())),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct RewriteFuture<F> {
+        #[pin]
+        inner: F,
+        original: Option<(http::Uri, usize)>,
+    }
+
+    impl<F> Future for RewriteFuture<F>
+    where
+        F: TryFuture,
+        F::Error: CombineRejection<Rejection>,
+    {
+        type Output = Result<F::Ok, <F::Error as CombineRejection<Rejection>>::One>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let result = ready!(pin.inner.try_poll(cx));
+            if let Some((uri, index)) = pin.original.take() {
+                route::with(|route| route.restore_uri(uri, index));
+            }
+            Poll::Ready(result.map_err(Into::into))
+        }
+    }
+
     // Used to prevent users from naming this type.
     //
     // For instance, `Exact<Opaque<String>>` means a user cannot depend
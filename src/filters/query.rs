@@ -1,14 +1,18 @@
 //! Query Filters
 
+use std::fmt;
+
 use futures_util::future;
-use serde::de::DeserializeOwned;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, MapAccess, Visitor};
 
 use crate::filter::{filter_fn_one, Filter, One};
-use crate::reject::{self, Rejection};
+use crate::reject::{self, InvalidQuery, Rejection};
 
 /// Creates a `Filter` that decodes query parameters to the type `T`.
 ///
 /// If cannot decode into a `T`, the request is rejected with a `400 Bad Request`.
+/// The [`InvalidQuery`](crate::reject::InvalidQuery) rejection describes the
+/// error, including the field that failed to decode.
 ///
 /// # Example
 ///
@@ -63,20 +67,304 @@ use crate::reject::{self, Rejection};
 /// [Serde]: https://docs.rs/serde
 pub fn query<T: DeserializeOwned + Send + 'static>(
 ) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
-    filter_fn_one(|route| {
+    query_with(Unknown::Deserialize)
+}
+
+/// Creates a `Filter` that decodes query parameters to the type `T`, ignoring
+/// parameters that aren't fields of `T`.
+///
+/// Unlike [`query()`], this accepts extra parameters, such as `utm_*`
+/// tracking parameters, even for a `T` with `#[serde(deny_unknown_fields)]`.
+///
+/// Fields can only be known for structs. For other types, such as maps or
+/// structs with a `#[serde(flatten)]` field, all parameters are decoded.
+///
+/// # Example
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// #[serde(deny_unknown_fields)]
+/// struct Search {
+///     q: String,
+/// }
+///
+/// // `/search?q=warp&utm_source=newsletter` is accepted.
+/// let route = warp::path("search")
+///     .and(warp::query::lenient::<Search>())
+///     .map(|search: Search| format!("searching for {}", search.q));
+/// ```
+pub fn lenient<T: DeserializeOwned + Send + 'static>(
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
+    query_with(Unknown::Ignore)
+}
+
+/// Creates a `Filter` that decodes query parameters to the type `T`,
+/// rejecting parameters that aren't fields of `T`.
+///
+/// Unlike [`query()`], this rejects extra parameters with a
+/// `400 Bad Request`, even for a `T` without
+/// `#[serde(deny_unknown_fields)]`. The
+/// [`InvalidQuery`](crate::reject::InvalidQuery) rejection names the first
+/// unknown parameter as its field.
+///
+/// Fields can only be known for structs. For other types, such as maps or
+/// structs with a `#[serde(flatten)]` field, all parameters are decoded.
+///
+/// # Example
+///
+/// ```
+/// use serde_derive::Deserialize;
+/// use warp::Filter;
+///
+/// #[derive(Deserialize)]
+/// struct Page {
+///     offset: u32,
+///     limit: u32,
+/// }
+///
+/// // `/items?offset=0&limit=10&limt=20` is rejected.
+/// let route = warp::path("items")
+///     .and(warp::query::strict::<Page>())
+///     .map(|page: Page| format!("items {}..{}", page.offset, page.offset + page.limit));
+/// ```
+pub fn strict<T: DeserializeOwned + Send + 'static>(
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
+    query_with(Unknown::Reject)
+}
+
+// What to do with parameters that aren't fields of the decoded type.
+#[derive(Clone, Copy, Debug)]
+enum Unknown {
+    // Leave them to the type's `Deserialize`.
+    Deserialize,
+    Ignore,
+    Reject,
+}
+
+fn query_with<T: DeserializeOwned + Send + 'static>(
+    unknown: Unknown,
+) -> impl Filter<Extract = One<T>, Error = Rejection> + Copy {
+    filter_fn_one(move |route| {
         let query_string = route.query().unwrap_or_else(|| {
             tracing::debug!("route was called without a query string, defaulting to empty");
             ""
         });
 
-        let query_encoded = serde_urlencoded::from_str(query_string).map_err(|e| {
+        let query_encoded = from_query(query_string, unknown).map_err(|(field, e)| {
             tracing::debug!("failed to decode query string '{}': {:?}", query_string, e);
-            reject::invalid_query()
+            reject::known(InvalidQuery::new(field, Box::new(e)))
         });
         future::ready(query_encoded)
     })
 }
 
+// Like `serde_urlencoded::from_str`, but also returns the key of the pair
+// whose value failed to deserialize, and handles `unknown` parameters.
+fn from_query<T: DeserializeOwned>(
+    query: &str,
+    unknown: Unknown,
+) -> Result<T, (Option<String>, serde_urlencoded::de::Error)> {
+    let filtered;
+    let fields = match unknown {
+        Unknown::Deserialize => None,
+        Unknown::Ignore | Unknown::Reject => struct_fields::<T>(),
+    };
+    let query = match (unknown, fields) {
+        (Unknown::Reject, Some(fields)) => {
+            let mut pairs = form_urlencoded::parse(query.as_bytes());
+            if let Some((key, _)) = pairs.find(|(key, _)| !fields.contains(&&**key)) {
+                let err = de::Error::custom(format_args!("unknown field `{}`", key));
+                return Err((Some(key.into_owned()), err));
+            }
+            query
+        }
+        (Unknown::Ignore, Some(fields)) => {
+            let pairs = form_urlencoded::parse(query.as_bytes());
+            filtered = form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(pairs.filter(|(key, _)| fields.contains(&&**key)))
+                .finish();
+            &filtered
+        }
+        _ => query,
+    };
+
+    let mut field = None;
+    let de = Track {
+        de: serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes())),
+        query,
+        field: &mut field,
+    };
+    T::deserialize(de).map_err(|e| (field, e))
+}
+
+struct Track<'a, D> {
+    de: D,
+    query: &'a str,
+    field: &'a mut Option<String>,
+}
+
+macro_rules! forward_to_inner {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, D::Error> {
+                self.de.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de, D: Deserializer<'de>> Deserializer<'de> for Track<'a, D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        let visitor = TrackVisitor {
+            visitor,
+            query: self.query,
+            field: self.field,
+        };
+        self.de.deserialize_any(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        let visitor = TrackVisitor {
+            visitor,
+            query: self.query,
+            field: self.field,
+        };
+        self.de.deserialize_map(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        let visitor = TrackVisitor {
+            visitor,
+            query: self.query,
+            field: self.field,
+        };
+        self.de.deserialize_struct(name, fields, visitor)
+    }
+
+    forward_to_inner! {
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32()
+        deserialize_i64() deserialize_i128() deserialize_u8() deserialize_u16()
+        deserialize_u32() deserialize_u64() deserialize_u128() deserialize_f32()
+        deserialize_f64() deserialize_char() deserialize_str() deserialize_string()
+        deserialize_bytes() deserialize_byte_buf() deserialize_option()
+        deserialize_unit() deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str) deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier() deserialize_ignored_any()
+    }
+}
+
+struct TrackVisitor<'a, V> {
+    visitor: V,
+    query: &'a str,
+    field: &'a mut Option<String>,
+}
+
+impl<'a, 'de, V: Visitor<'de>> Visitor<'de> for TrackVisitor<'a, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(f)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.visitor.visit_map(TrackMap {
+            map,
+            index: 0,
+            query: self.query,
+            field: self.field,
+        })
+    }
+}
+
+// Counts the pairs taken from the query, which are visited in order, to find
+// the key of a value that fails.
+struct TrackMap<'a, A> {
+    map: A,
+    index: usize,
+    query: &'a str,
+    field: &'a mut Option<String>,
+}
+
+impl<'a, 'de, A: MapAccess<'de>> MapAccess<'de> for TrackMap<'a, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        let key = self.map.next_key_seed(seed)?;
+        if key.is_some() {
+            self.index += 1;
+        }
+        Ok(key)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        let value = self.map.next_value_seed(seed);
+        if value.is_err() {
+            *self.field = form_urlencoded::parse(self.query.as_bytes())
+                .nth(self.index.saturating_sub(1))
+                .map(|(key, _)| key.into_owned());
+        }
+        value
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.map.size_hint()
+    }
+}
+
+// The fields of `T`, if it's deserialized as a struct.
+fn struct_fields<'de, T: de::Deserialize<'de>>() -> Option<&'static [&'static str]> {
+    let mut fields = None;
+    let _ = T::deserialize(Fields {
+        fields: &mut fields,
+    });
+    fields
+}
+
+// A deserializer that only records the fields of a struct, and fails.
+struct Fields<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'a, 'de> Deserializer<'de> for Fields<'a> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        _: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.fields = Some(fields);
+        Err(de::Error::custom("struct fields found"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
 /// Creates a `Filter` that returns the raw query string as type String.
 pub fn raw() -> impl Filter<Extract = One<String>, Error = Rejection> + Copy {
     filter_fn_one(|route| {
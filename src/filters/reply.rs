@@ -30,6 +30,9 @@ use crate::reply::Reply;
 
 /// Wrap a [`Filter`] that adds a header to the reply.
 ///
+/// Any header of the same name is replaced, except for `set-cookie`: each
+/// cookie needs its own header, so it is appended instead.
+///
 /// # Note
 ///
 /// This **only** adds a header if the underlying filter is successful, and
@@ -59,6 +62,9 @@ where
 
 /// Wrap a [`Filter`] that adds multiple headers to the reply.
 ///
+/// Like [`header`], `set-cookie` headers are appended rather than replacing
+/// the cookies already set.
+///
 /// # Note
 ///
 /// This **only** adds a header if the underlying filter is successful, and
@@ -91,6 +97,9 @@ pub fn headers(headers: HeaderMap) -> WithHeaders {
 /// Wrap a [`Filter`] that adds a header to the reply, if they
 /// aren't already set.
 ///
+/// A default `set-cookie` header is added unless a cookie of the same name
+/// is already set, whatever other cookies the reply sets.
+///
 /// # Note
 ///
 /// This **only** adds a header if the underlying filter is successful, and
@@ -196,6 +205,8 @@ where
 }
 
 mod sealed {
+    use http::header::{HeaderMap, HeaderName, HeaderValue, SET_COOKIE};
+
     use super::{WithDefaultHeader, WithHeader, WithHeaders};
     use crate::generic::{Func, One};
     use crate::reply::{Reply, Reply_};
@@ -211,9 +222,7 @@ mod sealed {
 
         fn call(&self, args: One<R>) -> Self::Output {
             let mut resp = args.0.into_response();
-            // Use "insert" to replace any set header...
-            resp.headers_mut()
-                .insert(&self.with.name, self.with.value.clone());
+            set_header(resp.headers_mut(), &self.with.name, &self.with.value);
             Reply_(resp)
         }
     }
@@ -230,7 +239,7 @@ mod sealed {
         fn call(&self, args: One<R>) -> Self::Output {
             let mut resp = args.0.into_response();
             for (name, value) in &*self.with.headers {
-                resp.headers_mut().insert(name, value.clone());
+                set_header(resp.headers_mut(), name, value);
             }
             Reply_(resp)
         }
@@ -247,11 +256,43 @@ mod sealed {
 
         fn call(&self, args: One<R>) -> Self::Output {
             let mut resp = args.0.into_response();
-            resp.headers_mut()
-                .entry(&self.with.name)
-                .or_insert_with(|| self.with.value.clone());
+            if self.with.name == SET_COOKIE {
+                let name = cookie_name(&self.with.value);
+                let headers = resp.headers_mut();
+                if !headers
+                    .get_all(SET_COOKIE)
+                    .iter()
+                    .any(|value| cookie_name(value) == name)
+                {
+                    headers.append(SET_COOKIE, self.with.value.clone());
+                }
+            } else {
+                resp.headers_mut()
+                    .entry(&self.with.name)
+                    .or_insert_with(|| self.with.value.clone());
+            }
 
             Reply_(resp)
         }
     }
+
+    fn set_header(headers: &mut HeaderMap, name: &HeaderName, value: &HeaderValue) {
+        // Each cookie needs its own `set-cookie` header, they can't be
+        // folded into one...
+        if name == SET_COOKIE {
+            headers.append(name, value.clone());
+        } else {
+            // Use "insert" to replace any set header...
+            headers.insert(name, value.clone());
+        }
+    }
+
+    fn cookie_name(value: &HeaderValue) -> &[u8] {
+        let value = value.as_bytes();
+        let end = value
+            .iter()
+            .position(|&b| b == b'=' || b == b';')
+            .unwrap_or(value.len());
+        &value[..end]
+    }
 }
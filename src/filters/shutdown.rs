@@ -0,0 +1,137 @@
+//! Shutdown Filters
+//!
+//! Filters to let long-lived handlers, like streams and websockets, know when
+//! the server has started to gracefully shut down, so they can finish on
+//! their own terms.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use futures_util::future;
+use tokio::sync::Notify;
+
+use crate::filter::{filter_fn_one, Filter, One};
+
+/// Creates a `Filter` that extracts the [`ShutdownToken`] of the server.
+///
+/// The token is triggered when a graceful shutdown begins, such as when the
+/// signal given to [`Server::bind_with_graceful_shutdown`] completes. The
+/// server then stops accepting connections, and waits for the ones in flight
+/// to finish. Handlers that stream responses can wait on the token to end
+/// their streams cleanly, instead of holding the shutdown up.
+///
+/// There is no separate `Server::graceful` method: the token is triggered by
+/// the signal of any of the existing `*_with_graceful_shutdown` methods, and
+/// is never triggered by the ones without a signal.
+///
+/// Requests not served by a `Server`, such as with [`warp::test`], get a
+/// token that is never triggered.
+///
+/// [`Server::bind_with_graceful_shutdown`]: crate::Server::bind_with_graceful_shutdown
+/// [`warp::test`]: crate::test
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use std::time::Duration;
+/// use futures_util::StreamExt;
+/// use warp::Filter;
+/// use warp::shutdown::ShutdownToken;
+///
+/// let route = warp::path("ticks")
+///     .and(warp::shutdown_token())
+///     .map(|token: ShutdownToken| {
+///         let ticks = tokio_stream::wrappers::IntervalStream::new(
+///             tokio::time::interval(Duration::from_secs(1)),
+///         )
+///         .map(|_| Ok::<_, Infallible>("tick\n"))
+///         .take_until(async move { token.triggered().await })
+///         .chain(futures_util::stream::once(async { Ok("bye\n") }));
+///         warp::reply::flushing_stream(ticks)
+///     });
+/// ```
+pub fn shutdown_token() -> impl Filter<Extract = One<ShutdownToken>, Error = Infallible> + Copy {
+    filter_fn_one(|route| {
+        future::ok(
+            route
+                .extensions()
+                .get::<ShutdownToken>()
+                .cloned()
+                .unwrap_or_else(ShutdownToken::new),
+        )
+    })
+}
+
+/// A token triggered when the server begins a graceful shutdown.
+///
+/// Extracted by the [`shutdown_token`] filter.
+#[derive(Clone)]
+pub struct ShutdownToken {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    triggered: AtomicBool,
+    notify: Notify,
+}
+
+impl ShutdownToken {
+    pub(crate) fn new() -> ShutdownToken {
+        ShutdownToken {
+            inner: Arc::new(Inner {
+                triggered: AtomicBool::new(false),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    /// Returns whether the graceful shutdown has begun.
+    pub fn is_triggered(&self) -> bool {
+        self.inner.triggered.load(Ordering::Acquire)
+    }
+
+    /// Waits until the graceful shutdown begins.
+    ///
+    /// Completes right away if it already has.
+    pub async fn triggered(&self) {
+        let notified = self.inner.notify.notified();
+        futures_util::pin_mut!(notified);
+        // Register for the notification before checking, so a trigger in
+        // between isn't missed.
+        notified.as_mut().enable();
+        if self.is_triggered() {
+            return;
+        }
+        notified.await;
+    }
+
+    pub(crate) fn trigger(&self) {
+        tracing::debug!("graceful shutdown triggered");
+        self.inner.triggered.store(true, Ordering::Release);
+        self.inner.notify.notify_waiters();
+    }
+
+    // Wraps a graceful shutdown signal to trigger this token when it completes.
+    pub(crate) fn on_signal(
+        &self,
+        signal: impl Future<Output = ()> + Send + 'static,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let token = self.clone();
+        async move {
+            signal.await;
+            token.trigger();
+        }
+    }
+}
+
+impl fmt::Debug for ShutdownToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownToken")
+            .field("triggered", &self.is_triggered())
+            .finish()
+    }
+}
@@ -0,0 +1,164 @@
+//! Request Signing Filters
+//!
+//! Filters to authenticate requests between services that share a secret,
+//! by verifying an HMAC signature over the request.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+use futures_util::future;
+use ring::hmac;
+
+use crate::filter::{filter_fn_one, Filter};
+use crate::reject::{self, Rejection};
+use crate::route::Route;
+
+/// Create a `Filter` that requires the request to be signed with `secret`.
+///
+/// The signature is an HMAC-SHA256, hex encoded in the `signature_header`,
+/// of the method, the path and query, the timestamp, and the body of the
+/// request, each followed by a newline except for the body:
+///
+/// ```text
+/// POST
+/// /orders?notify=true
+/// 1700000000
+/// {"item":"tea"}
+/// ```
+///
+/// The timestamp is in the `timestamp_header`, as seconds since the Unix
+/// epoch. Requests with a timestamp more than `max_skew` away from the
+/// server's clock are rejected, so that a captured request can't be replayed
+/// later on.
+///
+/// The request is rejected with a `401 Unauthorized` if either header is
+/// missing or invalid, the timestamp is out of the window, or the signature
+/// doesn't match. Signatures are compared in constant time.
+///
+/// The body is read to verify it, and then left in place for the filters that
+/// come after. It's rejected with a `413 Payload Too Large` as soon as it
+/// grows past `max` bytes, before the signature is checked.
+///
+/// *This function requires the `"sign"` feature.*
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let orders = warp::post()
+///     .and(warp::path("orders"))
+///     .and(warp::sign::verify(
+///         "shared secret",
+///         "x-timestamp",
+///         "x-signature",
+///         Duration::from_secs(300),
+///         1024 * 16,
+///     ))
+///     .and(warp::body::bytes())
+///     .map(|order: bytes::Bytes| format!("received {} bytes", order.len()));
+/// ```
+pub fn verify(
+    secret: impl AsRef<[u8]>,
+    timestamp_header: &'static str,
+    signature_header: &'static str,
+    max_skew: Duration,
+    max: u64,
+) -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_ref());
+
+    filter_fn_one(move |route| {
+        future::ready(signed_head(
+            route,
+            timestamp_header,
+            signature_header,
+            max_skew,
+        ))
+    })
+    .and(crate::body::buffered(max))
+    .and_then(move |(head, signature): (Vec<u8>, Vec<u8>), buf: Bytes| {
+        let key = key.clone();
+        async move {
+            let mut ctx = hmac::Context::with_key(&key);
+            ctx.update(&head);
+            ctx.update(&buf);
+            let expected = ctx.sign();
+            if !crate::csrf::constant_time_eq(expected.as_ref(), &signature) {
+                tracing::debug!("request signature doesn't match");
+                return Err(invalid());
+            }
+            Ok(())
+        }
+    })
+    .untuple_one()
+}
+
+// Checks the headers, and returns the signed bytes before the body, along
+// with the decoded signature.
+fn signed_head(
+    route: &Route,
+    timestamp_header: &'static str,
+    signature_header: &'static str,
+    max_skew: Duration,
+) -> Result<(Vec<u8>, Vec<u8>), Rejection> {
+    let timestamp = route
+        .headers()
+        .get(timestamp_header)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .ok_or_else(|| {
+            tracing::debug!("request timestamp missing or invalid");
+            invalid()
+        })?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let skew = now.abs_diff(timestamp);
+    if skew > max_skew.as_secs() {
+        tracing::debug!("request timestamp is {}s away from now", skew);
+        return Err(invalid());
+    }
+
+    let signature = route
+        .headers()
+        .get(signature_header)
+        .and_then(|value| decode_hex(value.as_bytes()))
+        .ok_or_else(|| {
+            tracing::debug!("request signature missing or invalid");
+            invalid()
+        })?;
+
+    let path = route
+        .uri()
+        .path_and_query()
+        .map(|path| path.as_str())
+        .unwrap_or("/");
+    let head = format!("{}\n{}\n{}\n", route.method(), path, timestamp);
+    Ok((head.into_bytes(), signature))
+}
+
+pub(crate) fn decode_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    let pairs = hex.chunks_exact(2);
+    if !pairs.remainder().is_empty() {
+        return None;
+    }
+    pairs
+        .map(|pair| {
+            let hi = (pair[0] as char).to_digit(16)?;
+            let lo = (pair[1] as char).to_digit(16)?;
+            Some((hi * 16 + lo) as u8)
+        })
+        .collect()
+}
+
+fn invalid() -> Rejection {
+    reject::known(InvalidSignature { _p: () })
+}
+
+unit_error! {
+    /// An error used to reject requests without a valid signature.
+    pub InvalidSignature: "Request signature missing or invalid"
+}
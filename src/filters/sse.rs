@@ -43,6 +43,7 @@
 
 use serde::Serialize;
 use std::borrow::Cow;
+use std::convert::TryFrom;
 use std::error::Error as StdError;
 use std::fmt::{self, Write};
 use std::future::Future;
@@ -51,8 +52,9 @@ use std::str::FromStr;
 use std::task::{Context, Poll};
 use std::time::Duration;
 
-use futures_util::{future, Stream, TryStream, TryStreamExt};
-use http::header::{HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use futures_util::{future, stream, Stream, StreamExt, TryStream, TryStreamExt};
+use http::header::{HeaderName, HeaderValue, CACHE_CONTROL, CONTENT_TYPE};
+use http::HeaderMap;
 use hyper::Body;
 use pin_project::pin_project;
 use serde_json::Error;
@@ -241,6 +243,9 @@ where
 /// - Header `content-type: text/event-stream`
 /// - Header `cache-control: no-cache`.
 ///
+/// More headers, and a default reconnection time, can be set with the
+/// methods of the returned [`SseReply`].
+///
 /// # Example
 ///
 /// ```
@@ -310,17 +315,87 @@ where
 ///     );
 /// };
 /// ```
-pub fn reply<S>(event_stream: S) -> impl Reply
+pub fn reply<S>(event_stream: S) -> SseReply<S>
 where
     S: TryStream<Ok = Event> + Send + 'static,
     S::Error: StdError + Send + Sync + 'static,
 {
-    SseReply { event_stream }
+    SseReply {
+        event_stream,
+        retry: None,
+        headers: HeaderMap::new(),
+    }
 }
 
+/// A server-sent events reply.
+///
+/// Returned by `warp::sse::reply`.
 #[allow(missing_debug_implementations)]
-struct SseReply<S> {
+pub struct SseReply<S> {
     event_stream: S,
+    retry: Option<Duration>,
+    headers: HeaderMap,
+}
+
+impl<S> SseReply<S> {
+    /// Set the time the client waits before reconnecting, if the connection
+    /// is lost.
+    ///
+    /// This is sent as an event of its own, before any event of the stream.
+    /// Events with their own [`retry`](Event::retry) change it afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    /// use std::time::Duration;
+    /// use futures_util::stream::iter;
+    /// use warp::{Filter, sse::Event};
+    ///
+    /// let app = warp::path("sse").map(|| {
+    ///     let events = iter(vec![Ok::<_, Infallible>(Event::default().data("hi"))]);
+    ///     warp::sse::reply(events)
+    ///         .retry(Duration::from_secs(5))
+    ///         .header("x-accel-buffering", "no")
+    /// });
+    /// ```
+    pub fn retry(mut self, duration: Duration) -> Self {
+        self.retry = Some(duration);
+        self
+    }
+
+    /// Add a header to the reply.
+    ///
+    /// This can replace the default `content-type` and `cache-control`. A
+    /// common one is `x-accel-buffering: no`, which stops nginx from
+    /// buffering the events when it's used as a reverse proxy.
+    ///
+    /// If the name or value is invalid, the header is skipped, and an error
+    /// is logged.
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        match <HeaderName as TryFrom<K>>::try_from(name) {
+            Ok(name) => match <HeaderValue as TryFrom<V>>::try_from(value) {
+                Ok(value) => {
+                    self.headers.append(name, value);
+                }
+                Err(err) => {
+                    let err = err.into();
+                    tracing::error!("sse::reply header value error: {}", err);
+                }
+            },
+            Err(err) => {
+                let err = err.into();
+                tracing::error!("sse::reply header name error: {}", err);
+            }
+        }
+        self
+    }
 }
 
 impl<S> Reply for SseReply<S>
@@ -339,6 +414,10 @@ where
             })
             .into_stream()
             .and_then(|event| future::ready(Ok(event.to_string())));
+        let retry = self
+            .retry
+            .map(|duration| Ok(Event::default().retry(duration).to_string()));
+        let body_stream = stream::iter(retry).chain(body_stream);
 
         let mut res = Response::new(Body::wrap_stream(body_stream));
         // Set appropriate content type
@@ -347,6 +426,7 @@ where
         // Disable response body caching
         res.headers_mut()
             .insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+        res.headers_mut().extend(self.headers);
         res
     }
 }
@@ -0,0 +1,144 @@
+//! Timeout Filters
+
+use std::fmt;
+use std::time::Duration;
+
+use self::internal::WithTimeout;
+use crate::filter::{Filter, WrapSealed};
+use crate::reject::{CombineRejection, Rejection};
+
+/// Create a wrapping filter that limits how long the wrapped filter may take.
+///
+/// If the wrapped filter, including its handler, hasn't finished within
+/// `duration`, the request is rejected with a [`RequestTimeout`], which
+/// replies with a `504 Gateway Timeout`. The timer is dropped as soon as the
+/// wrapped filter finishes.
+///
+/// The timeout only applies until the reply is produced. Writing the reply,
+/// such as a long stream, or a websocket connection after the upgrade, isn't
+/// limited by it.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+///
+/// let route = warp::any()
+///     .then(|| async {
+///         // Something that might take too long...
+///         "done"
+///     })
+///     .with(warp::timeout(Duration::from_secs(5)));
+/// ```
+pub fn timeout(duration: Duration) -> Timeout {
+    Timeout { duration }
+}
+
+/// A wrapping filter that limits how long the wrapped filter may take.
+///
+/// Returned by [`timeout`].
+#[derive(Clone, Copy)]
+pub struct Timeout {
+    duration: Duration,
+}
+
+impl<F> WrapSealed<F> for Timeout
+where
+    F: Filter + Clone + Send + Sync + 'static,
+    F::Error: CombineRejection<Rejection>,
+{
+    type Wrapped = WithTimeout<F>;
+
+    fn wrap(&self, inner: F) -> Self::Wrapped {
+        WithTimeout {
+            inner,
+            duration: self.duration,
+        }
+    }
+}
+
+impl fmt::Debug for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("duration", &self.duration)
+            .finish()
+    }
+}
+
+unit_error! {
+    /// An error used in rejections when a filter wrapped with [`timeout`]
+    /// took too long.
+    pub RequestTimeout: "Request handling timed out"
+}
+
+mod internal {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    use futures_util::TryFuture;
+    use pin_project::pin_project;
+    use tokio::time::Sleep;
+
+    use super::RequestTimeout;
+    use crate::filter::{Filter, FilterBase, Internal};
+    use crate::reject::{self, CombineRejection, Rejection};
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithTimeout<F> {
+        pub(super) inner: F,
+        pub(super) duration: Duration,
+    }
+
+    impl<F> FilterBase for WithTimeout<F>
+    where
+        F: Filter,
+        F::Error: CombineRejection<Rejection>,
+    {
+        type Extract = F::Extract;
+        type Error = <F::Error as CombineRejection<Rejection>>::One;
+        type Future = WithTimeoutFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            WithTimeoutFuture {
+                inner: self.inner.filter(Internal),
+                sleep: tokio::time::sleep(self.duration),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithTimeoutFuture<F> {
+        #[pin]
+        inner: F,
+        #[pin]
+        sleep: Sleep,
+    }
+
+    impl<F> Future for WithTimeoutFuture<F>
+    where
+        F: TryFuture,
+        F::Error: CombineRejection<Rejection>,
+    {
+        type Output = Result<F::Ok, <F::Error as CombineRejection<Rejection>>::One>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            if let Poll::Ready(result) = pin.inner.try_poll(cx) {
+                return Poll::Ready(result.map_err(Into::into));
+            }
+            match pin.sleep.poll(cx) {
+                Poll::Ready(()) => {
+                    tracing::debug!("request timed out");
+                    let rejection: Rejection = reject::known(RequestTimeout { _p: () });
+                    Poll::Ready(Err(rejection.into()))
+                }
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+}
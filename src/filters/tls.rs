@@ -0,0 +1,179 @@
+//! TLS Filters
+//!
+//! Filters to inspect the TLS connection a request arrived on, when served
+//! with [`Server::tls`](crate::Server::tls).
+//!
+//! *This module requires the `"tls"` feature.*
+
+use std::convert::Infallible;
+
+use futures_util::future;
+use x509_parser::extensions::GeneralName;
+use x509_parser::objects::oid_registry;
+
+use crate::filter::{filter_fn_one, Filter, One};
+use crate::reject::{self, Rejection};
+use crate::tls::ClientCertificates;
+
+/// Creates a `Filter` that extracts the identity of the TLS client.
+///
+/// The identity is read from the certificate the client authenticated with,
+/// so the server must be configured to ask for one, with any of the
+/// `client_auth_` methods of [`TlsServer`](crate::TlsServer). Requests whose
+/// connection didn't present a client certificate are rejected with a
+/// `401 Unauthorized`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::filters::tls::ClientIdentity;
+///
+/// let route = warp::filters::tls::client_identity()
+///     .map(|id: ClientIdentity| {
+///         format!("hello {}", id.subject())
+///     });
+/// ```
+pub fn client_identity() -> impl Filter<Extract = One<ClientIdentity>, Error = Rejection> + Copy {
+    filter_fn_one(|route| {
+        let identity = route
+            .extensions()
+            .get::<ClientCertificates>()
+            .and_then(|certs| certs.0.first())
+            .and_then(|cert| ClientIdentity::from_der(cert));
+        match identity {
+            Some(identity) => future::ok(identity),
+            None => {
+                tracing::debug!("request has no client certificate");
+                future::err(reject::known(MissingClientCertificate { _p: () }))
+            }
+        }
+    })
+}
+
+/// Creates a `Filter` that extracts the certificate chain of the TLS client.
+///
+/// Each certificate is DER-encoded, starting with the client's own, followed
+/// by any intermediates it sent. It's `None` if the connection didn't
+/// present a client certificate, such as when the server doesn't ask for
+/// one, or when it isn't served with TLS at all.
+///
+/// Use [`client_identity`] for the already parsed subject and alternative
+/// names of the client.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::filters::tls::peer_certificates()
+///     .map(|certs: Option<Vec<Vec<u8>>>| match certs {
+///         Some(certs) => format!("client sent {} certificates", certs.len()),
+///         None => "anonymous client".to_owned(),
+///     });
+/// ```
+pub fn peer_certificates(
+) -> impl Filter<Extract = One<Option<Vec<Vec<u8>>>>, Error = Infallible> + Copy {
+    filter_fn_one(|route| {
+        let certs = route
+            .extensions()
+            .get::<ClientCertificates>()
+            .map(|certs| certs.0.iter().map(|cert| cert.to_vec()).collect());
+        future::ok(certs)
+    })
+}
+
+/// The identity of an authenticated TLS client.
+///
+/// Extracted by the [`client_identity`] filter.
+#[derive(Clone, Debug)]
+pub struct ClientIdentity {
+    subject: String,
+    dns_names: Vec<String>,
+    uris: Vec<String>,
+    emails: Vec<String>,
+}
+
+impl ClientIdentity {
+    /// The Subject of the client certificate, such as
+    /// `"C=US, O=Example, CN=client.example"`.
+    ///
+    /// Attributes are in the order of the certificate, and ones without a
+    /// well-known short name are written with their dotted OID.
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// The DNS names in the Subject Alternative Names of the certificate.
+    pub fn dns_names(&self) -> &[String] {
+        &self.dns_names
+    }
+
+    /// The URIs in the Subject Alternative Names of the certificate, such as
+    /// SPIFFE IDs.
+    pub fn uris(&self) -> &[String] {
+        &self.uris
+    }
+
+    /// The email addresses in the Subject Alternative Names of the
+    /// certificate.
+    pub fn emails(&self) -> &[String] {
+        &self.emails
+    }
+
+    fn from_der(cert: &[u8]) -> Option<ClientIdentity> {
+        let (_, cert) = x509_parser::parse_x509_certificate(cert).ok()?;
+        let mut identity = ClientIdentity {
+            subject: cert
+                .subject()
+                .to_string_with_registry(oid_registry())
+                .ok()?,
+            dns_names: Vec::new(),
+            uris: Vec::new(),
+            emails: Vec::new(),
+        };
+
+        let alt_names = cert.subject_alternative_name().ok()?;
+        for name in alt_names.iter().flat_map(|ext| &ext.value.general_names) {
+            match *name {
+                GeneralName::DNSName(name) => identity.dns_names.push(name.to_owned()),
+                GeneralName::URI(uri) => identity.uris.push(uri.to_owned()),
+                GeneralName::RFC822Name(email) => identity.emails.push(email.to_owned()),
+                _ => (),
+            }
+        }
+
+        Some(identity)
+    }
+}
+
+unit_error! {
+    /// An error used to reject requests without a TLS client certificate.
+    pub MissingClientCertificate: "Request has no TLS client certificate"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClientIdentity;
+
+    #[test]
+    fn client_identity_from_der() {
+        let pem = include_str!("../../examples/tls/client.pem");
+        let cert = rustls_pemfile::certs(&mut pem.as_bytes())
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let id = ClientIdentity::from_der(&cert).unwrap();
+        assert_eq!(id.subject(), "C=US, O=Warp, OU=Tests, CN=client.test");
+        assert_eq!(id.dns_names(), ["client.test", "*.client.test"]);
+        assert_eq!(id.uris(), ["spiffe://warp.test/client"]);
+        assert_eq!(id.emails(), ["client@warp.test"]);
+    }
+
+    #[test]
+    fn client_identity_from_invalid_der() {
+        assert!(ClientIdentity::from_der(b"").is_none());
+        assert!(ClientIdentity::from_der(&[0x30, 0x82, 0xff]).is_none());
+    }
+}
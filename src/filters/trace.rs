@@ -10,16 +10,19 @@
 //! [`Spans`]: https://docs.rs/tracing/latest/tracing/#spans
 use tracing::Span;
 
+use std::fmt;
 use std::net::SocketAddr;
 
-use http::header;
+use http::header::{self, HeaderName, HeaderValue};
 
 use crate::filter::{Filter, WrapSealed};
 use crate::reject::IsReject;
 use crate::reply::Reply;
 use crate::route::Route;
 
-use self::internal::WithTrace;
+use self::internal::{WithRequestId, WithTrace};
+
+const X_REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
 
 /// Create a wrapping filter that instruments every request with a `tracing`
 /// [`Span`] at the [`INFO`] level, containing a summary of the request.
@@ -49,6 +52,7 @@ pub fn request() -> Trace<impl Fn(Info<'_>) -> Span + Clone> {
             path = %info.path(),
             version = ?info.route.version(),
             referer = Empty,
+            request.id = Empty,
         );
 
         // Record optional fields.
@@ -56,6 +60,10 @@ pub fn request() -> Trace<impl Fn(Info<'_>) -> Span + Clone> {
             span.record("remote.addr", &display(remote_addr));
         }
 
+        if let Some(id) = info.request_id() {
+            span.record("request.id", display(id));
+        }
+
         if let Some(referer) = info.referer() {
             span.record("referer", &display(referer));
         }
@@ -123,6 +131,110 @@ pub fn named(name: &'static str) -> Trace<impl Fn(Info<'_>) -> Span + Copy> {
     trace(move |_| tracing::debug_span!("context", "{}", name,))
 }
 
+/// Create a wrapping filter that gives every request an id.
+///
+/// The id is taken from the `x-request-id` header of the request, if it has
+/// a reasonable one, so that it can be followed across services. Otherwise,
+/// a random UUID is generated. The id is added as a [`RequestId`] to the
+/// extensions of the request, where handlers can get it with
+/// [`warp::ext::get`](crate::ext::get), and the access logs of
+/// [`request`] and [`warp::log`](crate::log()) include it. Replies get it
+/// in their `x-request-id` header.
+///
+/// Rejections pass through unchanged, so wrap a filter that already
+/// recovered from them to give every response the header. Since the
+/// [`request`] span is created before the wrapped filter runs, this must
+/// wrap it, rather than the other way around.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::trace::RequestId;
+///
+/// let route = warp::ext::get::<RequestId>()
+///     .map(|id: RequestId| format!("handling request {}", id))
+///     .with(warp::trace::request())
+///     .with(warp::trace::request_id());
+/// ```
+pub fn request_id() -> RequestIds {
+    RequestIds { _p: () }
+}
+
+/// Decorates a [`Filter`] to give every request an id.
+///
+/// Created with [`request_id`].
+#[derive(Clone, Copy, Debug)]
+pub struct RequestIds {
+    _p: (),
+}
+
+impl<F> WrapSealed<F> for RequestIds
+where
+    F: Filter + Clone + Send,
+    F::Extract: Reply,
+    F::Error: IsReject,
+{
+    type Wrapped = WithRequestId<F>;
+
+    fn wrap(&self, filter: F) -> Self::Wrapped {
+        WithRequestId { filter }
+    }
+}
+
+/// The id of a request.
+///
+/// Added to the extensions of a request by [`request_id`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestId(String);
+
+impl RequestId {
+    /// View the id as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // Reuses a reasonable id sent by the client, or else makes a new one.
+    fn from_request(headers: &http::HeaderMap) -> RequestId {
+        let sent = headers
+            .get(&X_REQUEST_ID)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= 128)
+            .filter(|id| id.bytes().all(|b| b.is_ascii_graphic()));
+        match sent {
+            Some(id) => RequestId(id.to_owned()),
+            None => RequestId::generate(),
+        }
+    }
+
+    // A random (version 4) UUID.
+    fn generate() -> RequestId {
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).expect("request id randomness");
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let mut id = String::with_capacity(36);
+        for (i, b) in bytes.iter().enumerate() {
+            if i == 4 || i == 6 || i == 8 || i == 10 {
+                id.push('-');
+            }
+            id.push_str(&format!("{:02x}", b));
+        }
+        RequestId(id)
+    }
+
+    fn header_value(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.0).expect("request id is a valid header value")
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Decorates a [`Filter`] to create a [`tracing`] [span] for
 /// requests and responses.
 ///
@@ -205,12 +317,25 @@ impl<'a> Info<'a> {
     pub fn request_headers(&self) -> &http::HeaderMap {
         self.route.headers()
     }
+
+    /// View the id of the request, if given one by [`request_id`].
+    pub fn request_id(&self) -> Option<&str> {
+        self.route
+            .extensions()
+            .get::<RequestId>()
+            .map(RequestId::as_str)
+    }
 }
 
 mod internal {
-    use futures_util::{future::Inspect, future::MapOk, FutureExt, TryFutureExt};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_util::{future::Inspect, future::MapOk, ready, FutureExt, TryFuture, TryFutureExt};
+    use pin_project::pin_project;
 
-    use super::{Info, Trace};
+    use super::{Info, RequestId, Trace, X_REQUEST_ID};
     use crate::filter::{Filter, FilterBase, Internal};
     use crate::reject::IsReject;
     use crate::reply::Reply;
@@ -306,4 +431,57 @@ mod internal {
                 .instrument(span.clone())
         }
     }
+
+    #[allow(missing_debug_implementations)]
+    #[derive(Clone, Copy)]
+    pub struct WithRequestId<F> {
+        pub(super) filter: F,
+    }
+
+    impl<F> FilterBase for WithRequestId<F>
+    where
+        F: Filter + Clone + Send,
+        F::Extract: Reply,
+        F::Error: IsReject,
+    {
+        type Extract = (Traced,);
+        type Error = F::Error;
+        type Future = WithRequestIdFuture<F::Future>;
+
+        fn filter(&self, _: Internal) -> Self::Future {
+            let id = route::with(|route| {
+                let id = RequestId::from_request(route.headers());
+                route.extensions_mut().insert(id.clone());
+                id
+            });
+            WithRequestIdFuture {
+                id: Some(id),
+                future: self.filter.filter(Internal),
+            }
+        }
+    }
+
+    #[allow(missing_debug_implementations)]
+    #[pin_project]
+    pub struct WithRequestIdFuture<F> {
+        id: Option<RequestId>,
+        #[pin]
+        future: F,
+    }
+
+    impl<F> Future for WithRequestIdFuture<F>
+    where
+        F: TryFuture,
+        F::Ok: Reply,
+    {
+        type Output = Result<(Traced,), F::Error>;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let pin = self.project();
+            let mut res = ready!(pin.future.try_poll(cx))?.into_response();
+            let id = pin.id.take().expect("polled after complete");
+            res.headers_mut().insert(X_REQUEST_ID, id.header_value());
+            Poll::Ready(Ok((Traced(res),)))
+        }
+    }
 }
@@ -0,0 +1,164 @@
+//! HTTP Upgrade Filters
+//!
+//! Filters to take over the connection of a request once the response has
+//! been sent, for protocols that "upgrade" from HTTP. Websockets are one such
+//! protocol, and the [`ws`](crate::ws) filter is built on these. Others, like
+//! tunnels from `CONNECT` requests, can be built directly on [`upgrade`].
+
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_util::{future, ready};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::filter::{filter_fn_one, Filter, One};
+use crate::reject::{self, Rejection};
+
+/// Creates a `Filter` that extracts the pending upgrade of the connection.
+///
+/// The request is rejected with a `400 Bad Request` if its connection can't
+/// be upgraded, such as when it isn't an HTTP/1 request asking to upgrade
+/// with a `connection: upgrade` header, or a `CONNECT` request.
+///
+/// The upgrade completes after the response has been sent, so the reply
+/// must agree to it, such as with a `101 Switching Protocols` status. The
+/// yielded `OnUpgrade` is a `Future` of the upgraded connection, usually
+/// spawned as its own task.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::http::StatusCode;
+/// use warp::hyper::Body;
+/// use warp::upgrade::OnUpgrade;
+///
+/// let route = warp::header::exact_ignore_case("upgrade", "echo")
+///     .and(warp::upgrade())
+///     .map(|on_upgrade: OnUpgrade| {
+///         tokio::spawn(async move {
+///             if let Ok(upgraded) = on_upgrade.await {
+///                 let (mut rd, mut wr) = tokio::io::split(upgraded);
+///                 let _ = tokio::io::copy(&mut rd, &mut wr).await;
+///             }
+///         });
+///         warp::http::Response::builder()
+///             .status(StatusCode::SWITCHING_PROTOCOLS)
+///             .header("connection", "upgrade")
+///             .header("upgrade", "echo")
+///             .body(Body::empty())
+///     });
+/// ```
+pub fn upgrade() -> impl Filter<Extract = One<OnUpgrade>, Error = Rejection> + Copy {
+    optional().and_then(|on_upgrade: Option<OnUpgrade>| match on_upgrade {
+        Some(on_upgrade) => future::ok(on_upgrade),
+        None => {
+            tracing::debug!("request doesn't have a pending upgrade");
+            future::err(reject::known(MissingUpgrade { _p: () }))
+        }
+    })
+}
+
+// Extracts the OnUpgrade state from the route, if there is one.
+pub(crate) fn optional() -> impl Filter<Extract = One<Option<OnUpgrade>>, Error = Rejection> + Copy
+{
+    filter_fn_one(|route| {
+        future::ok(
+            route
+                .extensions_mut()
+                .remove::<hyper::upgrade::OnUpgrade>()
+                .map(|inner| OnUpgrade { inner }),
+        )
+    })
+}
+
+/// A `Future` of the upgraded connection of a request.
+///
+/// Extracted by the [`upgrade`] filter.
+pub struct OnUpgrade {
+    inner: hyper::upgrade::OnUpgrade,
+}
+
+impl Future for OnUpgrade {
+    type Output = Result<Upgraded, crate::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = ready!(Pin::new(&mut self.inner).poll(cx)).map_err(crate::Error::new)?;
+        tracing::trace!("upgrade complete");
+        Poll::Ready(Ok(Upgraded::new(inner)))
+    }
+}
+
+impl fmt::Debug for OnUpgrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnUpgrade").finish()
+    }
+}
+
+/// An upgraded connection, to read and write with any protocol.
+///
+/// Any bytes the client sent after the request, but that were read along
+/// with it, are read first.
+pub struct Upgraded {
+    inner: hyper::upgrade::Upgraded,
+}
+
+impl Upgraded {
+    pub(crate) fn new(inner: hyper::upgrade::Upgraded) -> Self {
+        Upgraded { inner }
+    }
+}
+
+impl AsyncRead for Upgraded {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Upgraded {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl fmt::Debug for Upgraded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Upgraded").finish()
+    }
+}
+
+unit_error! {
+    /// An error used to reject requests whose connection can't be upgraded.
+    pub MissingUpgrade: "Request connection can't be upgraded"
+}
@@ -1,18 +1,20 @@
 //! Websockets Filters
 
 use std::borrow::Cow;
+use std::convert::Infallible;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use super::header;
-use crate::filter::{filter_fn_one, Filter, One};
-use crate::reject::Rejection;
+use crate::filter::{filter_fn, filter_fn_one, Either, Filter, One};
+use crate::reject::{CombineRejection, Rejection};
 use crate::reply::{Reply, Response};
+use crate::upgrade::{self, OnUpgrade, Upgraded};
 use futures_util::{future, ready, FutureExt, Sink, Stream, TryFutureExt};
 use headers::{Connection, HeaderMapExt, SecWebsocketAccept, SecWebsocketKey, Upgrade};
-use hyper::upgrade::OnUpgrade;
+use http::header::{HeaderValue, SEC_WEBSOCKET_PROTOCOL};
 use tokio_tungstenite::{
     tungstenite::protocol::{self, WebSocketConfig},
     WebSocketStream,
@@ -39,6 +41,8 @@ use tokio_tungstenite::{
 /// - Header `connection: upgrade`
 /// - Header `upgrade: websocket`
 /// - Header `sec-websocket-accept` with the hash value of the received key.
+/// - Header `sec-websocket-protocol`, if a protocol was negotiated with
+///   [`Ws::protocols`].
 pub fn ws() -> impl Filter<Extract = One<Ws>, Error = Rejection> + Copy {
     let connection_has_upgrade = header::header2()
         .and_then(|conn: ::headers::Connection| {
@@ -57,20 +61,144 @@ pub fn ws() -> impl Filter<Extract = One<Ws>, Error = Rejection> + Copy {
         //.and(header::exact2(Upgrade::websocket()))
         //.and(header::exact2(SecWebsocketVersion::V13))
         .and(header::header2::<SecWebsocketKey>())
-        .and(on_upgrade())
+        .and(requested_protocols())
+        .and(upgrade::optional())
         .map(
-            move |key: SecWebsocketKey, on_upgrade: Option<OnUpgrade>| Ws {
+            move |key: SecWebsocketKey,
+                  requested_protocols: Vec<String>,
+                  on_upgrade: Option<OnUpgrade>| Ws {
                 config: None,
                 key,
+                requested_protocols,
+                protocol: None,
+                require_protocol: false,
                 on_upgrade,
             },
         )
 }
 
+// Extracts the protocols of all `sec-websocket-protocol` headers, in the
+// client's order of preference.
+fn requested_protocols() -> impl Filter<Extract = One<Vec<String>>, Error = Infallible> + Copy {
+    filter_fn_one(|route| {
+        let protocols = route
+            .headers()
+            .get_all(SEC_WEBSOCKET_PROTOCOL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .filter(|protocol| !protocol.is_empty())
+            .map(str::to_owned)
+            .collect();
+        future::ok(protocols)
+    })
+}
+
+/// Creates a Websocket Filter that falls back to another filter when the
+/// request isn't a websocket handshake.
+///
+/// This allows serving a websocket and a regular HTTP response (such as the
+/// page using that websocket) from the same path.
+///
+/// If the request has an `upgrade: websocket` header, this behaves exactly
+/// like [`ws`], rejecting if the rest of the handshake is invalid. Otherwise,
+/// the `fallback` filter is used, and the websocket requirements can't leak
+/// any rejections into its result.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::ws::UpgradeOr;
+///
+/// let page = warp::any().map(|| warp::reply::html("<script>/* ... */</script>"));
+///
+/// let route = warp::path("chat")
+///     .and(warp::ws::upgrade_or(page))
+///     .map(|upgrade: UpgradeOr<_>| {
+///         upgrade.on_upgrade(|websocket| async {
+///             // Do something with the websocket...
+///             drop(websocket);
+///         })
+///     });
+/// ```
+pub fn upgrade_or<F, T>(
+    fallback: F,
+) -> impl Filter<Extract = One<UpgradeOr<T>>, Error = Rejection> + Clone
+where
+    F: Filter<Extract = (T,)> + Clone + Send,
+    F::Error: CombineRejection<Rejection, One = Rejection>,
+    T: Send,
+{
+    let handshake = filter_fn(|route| {
+        if is_handshake(route) {
+            future::ok(())
+        } else {
+            future::err(crate::reject::not_found())
+        }
+    });
+    let not_handshake = filter_fn(|route| {
+        if is_handshake(route) {
+            future::err(crate::reject::not_found())
+        } else {
+            future::ok(())
+        }
+    });
+
+    handshake
+        .and(ws())
+        .map(UpgradeOr::Upgrade)
+        .or(not_handshake.and(fallback).map(UpgradeOr::Fallback))
+        .unify()
+}
+
+fn is_handshake(route: &crate::route::Route) -> bool {
+    match route.headers().get(http::header::UPGRADE) {
+        Some(value) => value.as_bytes().eq_ignore_ascii_case(b"websocket"),
+        None => false,
+    }
+}
+
+/// Extracted by the [`upgrade_or`] filter.
+#[derive(Debug)]
+pub enum UpgradeOr<T> {
+    /// The request was a websocket handshake.
+    Upgrade(Ws),
+    /// The request wasn't a websocket handshake, and the fallback matched.
+    Fallback(T),
+}
+
+impl<T: Reply> UpgradeOr<T> {
+    /// Finish the upgrade if the request was a websocket handshake, or else
+    /// reply with the fallback.
+    ///
+    /// See [`Ws::on_upgrade`].
+    pub fn on_upgrade<F, U>(self, func: F) -> impl Reply
+    where
+        F: FnOnce(WebSocket) -> U + Send + 'static,
+        U: Future<Output = ()> + Send + 'static,
+    {
+        match self {
+            UpgradeOr::Upgrade(ws) => Either::A(ws.on_upgrade(func)),
+            UpgradeOr::Fallback(reply) => Either::B(reply),
+        }
+    }
+}
+
 /// Extracted by the [`ws`] filter, and used to finish an upgrade.
+///
+/// # Compression
+///
+/// The `permessage-deflate` extension isn't supported by the underlying
+/// websocket implementation, so it is never accepted in the handshake. Clients
+/// that offer it fall back to uncompressed messages.
 pub struct Ws {
     config: Option<WebSocketConfig>,
     key: SecWebsocketKey,
+    requested_protocols: Vec<String>,
+    protocol: Option<String>,
+    require_protocol: bool,
     on_upgrade: Option<OnUpgrade>,
 }
 
@@ -89,6 +217,51 @@ impl Ws {
         }
     }
 
+    // protocols
+
+    /// Negotiate a subprotocol from the ones supported by the server.
+    ///
+    /// The first protocol of the `sec-websocket-protocol` request header that
+    /// is in `supported` is chosen, and echoed back in the handshake response.
+    /// If none match, the upgrade still happens without a protocol, unless
+    /// [`require_protocol`](Ws::require_protocol) is set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// let route = warp::ws().map(|ws: warp::ws::Ws| {
+    ///     ws.protocols(&["graphql-transport-ws", "graphql-ws"])
+    ///         .require_protocol(true)
+    ///         .on_upgrade(|websocket| async move {
+    ///             println!("protocol: {:?}", websocket.protocol());
+    ///         })
+    /// });
+    /// ```
+    pub fn protocols(mut self, supported: &[&str]) -> Self {
+        self.protocol = self
+            .requested_protocols
+            .iter()
+            .find(|requested| supported.contains(&requested.as_str()))
+            .cloned();
+        self
+    }
+
+    /// Set whether the upgrade requires a protocol to be negotiated.
+    ///
+    /// If set and no protocol was chosen by [`protocols`](Ws::protocols), the
+    /// handshake is answered with a `400 Bad Request` instead of upgrading.
+    pub fn require_protocol(mut self, required: bool) -> Self {
+        self.require_protocol = required;
+        self
+    }
+
+    /// The protocol chosen by [`protocols`](Ws::protocols), if any.
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
     // config
 
     /// Does nothing.
@@ -128,7 +301,9 @@ impl Ws {
 
 impl fmt::Debug for Ws {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Ws").finish()
+        f.debug_struct("Ws")
+            .field("protocol", &self.protocol)
+            .finish()
     }
 }
 
@@ -144,13 +319,34 @@ where
     U: Future<Output = ()> + Send + 'static,
 {
     fn into_response(self) -> Response {
+        if self.ws.require_protocol && self.ws.protocol.is_none() {
+            tracing::debug!(
+                "ws protocols {:?} aren't supported",
+                self.ws.requested_protocols
+            );
+            let mut res = http::Response::default();
+            *res.status_mut() = http::StatusCode::BAD_REQUEST;
+            return res;
+        }
+
+        let protocol_header = self
+            .ws
+            .protocol
+            .as_deref()
+            .and_then(|protocol| HeaderValue::from_str(protocol).ok());
+
         if let Some(on_upgrade) = self.ws.on_upgrade {
             let on_upgrade_cb = self.on_upgrade;
             let config = self.ws.config;
+            let chosen = self.ws.protocol;
             let fut = on_upgrade
                 .and_then(move |upgraded| {
-                    tracing::trace!("websocket upgrade complete");
-                    WebSocket::from_raw_socket(upgraded, protocol::Role::Server, config).map(Ok)
+                    WebSocket::from_raw_socket(upgraded, protocol::Role::Server, config).map(
+                        move |mut socket| {
+                            socket.protocol = chosen;
+                            Ok(socket)
+                        },
+                    )
                 })
                 .and_then(move |socket| on_upgrade_cb(socket).map(Ok))
                 .map(|result| {
@@ -171,16 +367,14 @@ where
         res.headers_mut().typed_insert(Upgrade::websocket());
         res.headers_mut()
             .typed_insert(SecWebsocketAccept::from(self.ws.key));
+        if let Some(value) = protocol_header {
+            res.headers_mut().insert(SEC_WEBSOCKET_PROTOCOL, value);
+        }
 
         res
     }
 }
 
-// Extracts OnUpgrade state from the route.
-fn on_upgrade() -> impl Filter<Extract = (Option<OnUpgrade>,), Error = Rejection> + Copy {
-    filter_fn_one(|route| future::ready(Ok(route.extensions_mut().remove::<OnUpgrade>())))
-}
-
 /// A websocket `Stream` and `Sink`, provided to `ws` filters.
 ///
 /// Ping messages sent from the client will be handled internally by replying with a Pong message.
@@ -191,20 +385,29 @@ fn on_upgrade() -> impl Filter<Extract = (Option<OnUpgrade>,), Error = Rejection
 /// Due to rust futures nature, pings won't be handled until read part of `WebSocket` is polled
 
 pub struct WebSocket {
-    inner: WebSocketStream<hyper::upgrade::Upgraded>,
+    inner: WebSocketStream<Upgraded>,
+    protocol: Option<String>,
 }
 
 impl WebSocket {
     pub(crate) async fn from_raw_socket(
-        upgraded: hyper::upgrade::Upgraded,
+        upgraded: Upgraded,
         role: protocol::Role,
         config: Option<protocol::WebSocketConfig>,
     ) -> Self {
         WebSocketStream::from_raw_socket(upgraded, role, config)
-            .map(|inner| WebSocket { inner })
+            .map(|inner| WebSocket {
+                inner,
+                protocol: None,
+            })
             .await
     }
 
+    /// The subprotocol negotiated with [`Ws::protocols`], if any.
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
     /// Gracefully close this websocket.
     pub async fn close(mut self) -> Result<(), crate::Error> {
         future::poll_fn(|cx| Pin::new(&mut self).poll_close(cx)).await
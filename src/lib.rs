@@ -112,6 +112,9 @@ pub use self::filters::compression;
 #[cfg(feature = "multipart")]
 #[doc(hidden)]
 pub use self::filters::multipart;
+#[cfg(feature = "sign")]
+#[doc(hidden)]
+pub use self::filters::sign;
 #[cfg(feature = "websocket")]
 #[doc(hidden)]
 pub use self::filters::ws;
@@ -120,33 +123,53 @@ pub use self::filters::{
     addr,
     // any() function
     any::any,
+    auth,
     body,
+    cache,
     cookie,
     // cookie() function
     cookie::cookie,
     cors,
     // cors() function
     cors::cors,
+    csrf,
     ext,
     fs,
+    head,
     header,
     // header() function
     header::header,
+    health,
     host,
     log,
     // log() function
     log::log,
+    // tap_response() function
+    log::tap_response,
+    method,
     method::{delete, get, head, method, options, patch, post, put},
+    metrics,
+    // metrics() function
+    metrics::metrics,
     path,
     // path() function and macro
     path::path,
     query,
     // query() function
     query::query,
+    shutdown,
+    // shutdown_token() function
+    shutdown::shutdown_token,
     sse,
+    timeout,
+    // timeout() function
+    timeout::timeout,
     trace,
     // trace() function
     trace::trace,
+    upgrade,
+    // upgrade() function
+    upgrade::upgrade,
 };
 // ws() function
 pub use self::filter::wrap_fn;
@@ -162,7 +185,7 @@ pub use self::reject::{reject, Rejection};
 pub use self::reply::{reply, Reply};
 #[cfg(feature = "tls")]
 pub use self::server::TlsServer;
-pub use self::server::{serve, Server};
+pub use self::server::{serve, AcceptErrorAction, Server};
 pub use self::service::service;
 #[doc(hidden)]
 pub use http;
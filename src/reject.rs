@@ -16,6 +16,12 @@
 //! filters can be handled using [`Filter::recover`](../trait.Filter.html#method.recover).
 //! This is a convenient way to map rejections into a [`Reply`](../reply/trait.Reply.html).
 //!
+//! When the rejections of several routes are combined, the response uses the
+//! most specific one: a `404 Not Found` is used only if there's nothing else,
+//! then a `405 Method Not Allowed`, and otherwise the highest status. Routes
+//! composed with [`Filter::and_strict`](../trait.Filter.html#method.and_strict)
+//! take precedence over all of these, once their first filter matched.
+//!
 //! For a more complete example see the
 //! [Rejection Example](https://github.com/seanmonstar/warp/blob/master/examples/rejections.rs)
 //! from the repository.
@@ -62,7 +68,7 @@ use std::error::Error as StdError;
 use std::fmt;
 
 use http::{
-    header::{HeaderValue, CONTENT_TYPE},
+    header::{HeaderValue, CONTENT_TYPE, WWW_AUTHENTICATE},
     StatusCode,
 };
 use hyper::Body;
@@ -86,7 +92,10 @@ pub fn not_found() -> Rejection {
 // 400 Bad Request
 #[inline]
 pub(crate) fn invalid_query() -> Rejection {
-    known(InvalidQuery { _p: () })
+    known(InvalidQuery {
+        field: None,
+        cause: None,
+    })
 }
 
 // 400 Bad Request
@@ -113,6 +122,12 @@ pub(crate) fn method_not_allowed() -> Rejection {
     known(MethodNotAllowed { _p: () })
 }
 
+// 406 Not Acceptable
+#[inline]
+pub(crate) fn not_acceptable() -> Rejection {
+    known(NotAcceptable { _p: () })
+}
+
 // 411 Length Required
 #[inline]
 pub(crate) fn length_required() -> Rejection {
@@ -134,6 +149,14 @@ pub(crate) fn unsupported_media_type() -> Rejection {
     known(UnsupportedMediaType { _p: () })
 }
 
+// 500 Internal Server Error
+//
+// Used by `and_then_blocking` if its handler panicked or was cancelled.
+#[inline]
+pub(crate) fn blocking_handler_failed() -> Rejection {
+    known(BlockingHandlerFailed { _p: () })
+}
+
 /// Rejects a request with a custom cause.
 ///
 /// A [`recover`][] filter should convert this `Rejection` into a `Reply`,
@@ -214,6 +237,8 @@ enum Rejections {
     Known(Known),
     Custom(Box<dyn Cause>),
     Combined(Box<Rejections>, Box<Rejections>),
+    // From `Filter::and_strict`, to win over rejections of other routes.
+    Committed(Reason),
 }
 
 macro_rules! enum_known {
@@ -272,22 +297,49 @@ macro_rules! enum_known {
 
 enum_known! {
     MethodNotAllowed(MethodNotAllowed),
+    NotAcceptable(NotAcceptable),
     InvalidHeader(InvalidHeader),
     MissingHeader(MissingHeader),
     MissingCookie(MissingCookie),
+    InvalidCookie(crate::cookie::InvalidCookie),
+    #[cfg(feature = "sign")]
+    TamperedCookie(crate::cookie::TamperedCookie),
     InvalidQuery(InvalidQuery),
+    ParamOutOfRange(crate::path::ParamOutOfRange),
+    InvalidPathParams(crate::path::InvalidPathParams),
     LengthRequired(LengthRequired),
     PayloadTooLarge(PayloadTooLarge),
     UnsupportedMediaType(UnsupportedMediaType),
     FileOpenError(crate::fs::FileOpenError),
     FilePermissionError(crate::fs::FilePermissionError),
     BodyReadError(crate::body::BodyReadError),
+    BodyReadTimeout(crate::body::BodyReadTimeout),
+    RequestTimeout(crate::timeout::RequestTimeout),
     BodyDeserializeError(crate::body::BodyDeserializeError),
+    InvalidCharset(crate::body::InvalidCharset),
+    #[cfg(feature = "compression-gzip")]
+    BodyDecompressError(crate::body::BodyDecompressError),
     CorsForbidden(crate::cors::CorsForbidden),
+    CsrfForbidden(crate::csrf::CsrfForbidden),
+    InvalidJwt(crate::auth::InvalidJwt),
+    SequenceOutOfOrder(crate::header::SequenceOutOfOrder),
+    HeadersTooLarge(crate::header::HeadersTooLarge),
+    Unauthorized(crate::header::Unauthorized),
+    PreconditionFailed(crate::cache::PreconditionFailed),
+    #[cfg(feature = "multipart")]
+    PartTooLarge(crate::multipart::PartTooLarge),
+    #[cfg(feature = "multipart")]
+    TooManyParts(crate::multipart::TooManyParts),
+    MissingUpgrade(crate::upgrade::MissingUpgrade),
+    #[cfg(feature = "tls")]
+    MissingClientCertificate(crate::filters::tls::MissingClientCertificate),
+    #[cfg(feature = "sign")]
+    InvalidSignature(crate::sign::InvalidSignature),
     #[cfg(feature = "websocket")]
     MissingConnectionUpgrade(crate::ws::MissingConnectionUpgrade),
     MissingExtension(crate::ext::MissingExtension),
     BodyConsumedMultipleTimes(crate::body::BodyConsumedMultipleTimes),
+    BlockingHandlerFailed(BlockingHandlerFailed),
 }
 
 impl Rejection {
@@ -303,6 +355,34 @@ impl Rejection {
         }
     }
 
+    pub(crate) fn commit(self) -> Self {
+        if self.is_committed() {
+            return self;
+        }
+        Rejection {
+            reason: Reason::Other(Box::new(Rejections::Committed(self.reason))),
+        }
+    }
+
+    fn is_committed(&self) -> bool {
+        match self.reason {
+            Reason::Other(ref rejections) => matches!(**rejections, Rejections::Committed(_)),
+            Reason::NotFound => false,
+        }
+    }
+
+    fn uncommit(self) -> Self {
+        match self.reason {
+            Reason::Other(rejections) => match *rejections {
+                Rejections::Committed(reason) => Rejection { reason },
+                rejections => Rejection {
+                    reason: Reason::Other(Box::new(rejections)),
+                },
+            },
+            Reason::NotFound => self,
+        }
+    }
+
     /// Searches this `Rejection` for a specific cause.
     ///
     /// A `Rejection` will accumulate causes over a `Filter` chain. This method
@@ -339,7 +419,7 @@ impl Rejection {
     /// assert!(rejection.is_not_found());
     /// ```
     pub fn is_not_found(&self) -> bool {
-        matches!(self.reason, Reason::NotFound)
+        self.reason.is_not_found()
     }
 }
 
@@ -369,21 +449,11 @@ impl IsReject for Infallible {
 
 impl IsReject for Rejection {
     fn status(&self) -> StatusCode {
-        match self.reason {
-            Reason::NotFound => StatusCode::NOT_FOUND,
-            Reason::Other(ref other) => other.status(),
-        }
+        self.reason.status()
     }
 
     fn into_response(&self) -> crate::reply::Response {
-        match self.reason {
-            Reason::NotFound => {
-                let mut res = http::Response::default();
-                *res.status_mut() = StatusCode::NOT_FOUND;
-                res
-            }
-            Reason::Other(ref other) => other.into_response(),
-        }
+        self.reason.into_response()
     }
 }
 
@@ -406,11 +476,46 @@ impl fmt::Debug for Reason {
                     b.debug_list(&mut list);
                     list.finish()
                 }
+                Rejections::Committed(ref reason) => fmt::Debug::fmt(reason, f),
             },
         }
     }
 }
 
+// ===== Reason =====
+
+impl Reason {
+    fn is_not_found(&self) -> bool {
+        match *self {
+            Reason::NotFound => true,
+            Reason::Other(ref other) => match **other {
+                Rejections::Committed(ref reason) => reason.is_not_found(),
+                _ => false,
+            },
+        }
+    }
+}
+
+impl IsReject for Reason {
+    fn status(&self) -> StatusCode {
+        match *self {
+            Reason::NotFound => StatusCode::NOT_FOUND,
+            Reason::Other(ref other) => other.status(),
+        }
+    }
+
+    fn into_response(&self) -> crate::reply::Response {
+        match *self {
+            Reason::NotFound => {
+                let mut res = http::Response::default();
+                *res.status_mut() = StatusCode::NOT_FOUND;
+                res
+            }
+            Reason::Other(ref other) => other.into_response(),
+        }
+    }
+}
+
 // ===== Rejections =====
 
 impl Rejections {
@@ -418,24 +523,51 @@ impl Rejections {
         match *self {
             Rejections::Known(ref k) => match *k {
                 Known::MethodNotAllowed(_) => StatusCode::METHOD_NOT_ALLOWED,
+                #[cfg(feature = "tls")]
+                Known::MissingClientCertificate(_) => StatusCode::UNAUTHORIZED,
+                Known::InvalidJwt(_) => StatusCode::UNAUTHORIZED,
+                Known::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+                #[cfg(feature = "sign")]
+                Known::InvalidSignature(_) => StatusCode::UNAUTHORIZED,
+                Known::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
                 Known::InvalidHeader(_)
                 | Known::MissingHeader(_)
                 | Known::MissingCookie(_)
+                | Known::InvalidCookie(_)
                 | Known::InvalidQuery(_)
+                | Known::ParamOutOfRange(_)
                 | Known::BodyReadError(_)
-                | Known::BodyDeserializeError(_) => StatusCode::BAD_REQUEST,
+                | Known::BodyDeserializeError(_)
+                | Known::InvalidCharset(_) => StatusCode::BAD_REQUEST,
+                #[cfg(feature = "compression-gzip")]
+                Known::BodyDecompressError(_) => StatusCode::BAD_REQUEST,
                 #[cfg(feature = "websocket")]
                 Known::MissingConnectionUpgrade(_) => StatusCode::BAD_REQUEST,
+                Known::MissingUpgrade(_) => StatusCode::BAD_REQUEST,
+                Known::InvalidPathParams(_) => StatusCode::NOT_FOUND,
+                #[cfg(feature = "sign")]
+                Known::TamperedCookie(_) => StatusCode::BAD_REQUEST,
+                Known::BodyReadTimeout(_) => StatusCode::REQUEST_TIMEOUT,
+                Known::RequestTimeout(_) => StatusCode::GATEWAY_TIMEOUT,
                 Known::LengthRequired(_) => StatusCode::LENGTH_REQUIRED,
                 Known::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+                #[cfg(feature = "multipart")]
+                Known::PartTooLarge(_) | Known::TooManyParts(_) => StatusCode::PAYLOAD_TOO_LARGE,
                 Known::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
-                Known::FilePermissionError(_) | Known::CorsForbidden(_) => StatusCode::FORBIDDEN,
+                Known::SequenceOutOfOrder(_) => StatusCode::CONFLICT,
+                Known::HeadersTooLarge(_) => StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE,
+                Known::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+                Known::FilePermissionError(_)
+                | Known::CorsForbidden(_)
+                | Known::CsrfForbidden(_) => StatusCode::FORBIDDEN,
                 Known::FileOpenError(_)
                 | Known::MissingExtension(_)
-                | Known::BodyConsumedMultipleTimes(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                | Known::BodyConsumedMultipleTimes(_)
+                | Known::BlockingHandlerFailed(_) => StatusCode::INTERNAL_SERVER_ERROR,
             },
             Rejections::Custom(..) => StatusCode::INTERNAL_SERVER_ERROR,
             Rejections::Combined(..) => self.preferred().status(),
+            Rejections::Committed(ref reason) => reason.status(),
         }
     }
 
@@ -448,6 +580,9 @@ impl Rejections {
                     CONTENT_TYPE,
                     HeaderValue::from_static("text/plain; charset=utf-8"),
                 );
+                if let Known::Unauthorized(ref e) = *e {
+                    res.headers_mut().insert(WWW_AUTHENTICATE, e.challenge());
+                }
                 res
             }
             Rejections::Custom(ref e) => {
@@ -465,6 +600,7 @@ impl Rejections {
                 res
             }
             Rejections::Combined(..) => self.preferred().into_response(),
+            Rejections::Committed(ref reason) => reason.into_response(),
         }
     }
 
@@ -473,6 +609,8 @@ impl Rejections {
             Rejections::Known(ref e) => e.inner_as_any().downcast_ref(),
             Rejections::Custom(ref e) => e.downcast_ref(),
             Rejections::Combined(ref a, ref b) => a.find().or_else(|| b.find()),
+            Rejections::Committed(Reason::Other(ref other)) => other.find(),
+            Rejections::Committed(Reason::NotFound) => None,
         }
     }
 
@@ -488,16 +626,19 @@ impl Rejections {
                 a.debug_list(f);
                 b.debug_list(f);
             }
+            Rejections::Committed(ref reason) => {
+                f.entry(reason);
+            }
         }
     }
 
     fn preferred(&self) -> &Rejections {
         match self {
-            Rejections::Known(_) | Rejections::Custom(_) => self,
+            Rejections::Known(_) | Rejections::Custom(_) | Rejections::Committed(_) => self,
             Rejections::Combined(a, b) => {
                 let a = a.preferred();
                 let b = b.preferred();
-                // Now both a and b are known or custom, so it is safe
+                // Now both a and b are known, custom or committed, so it is safe
                 // to get status
                 // Compare status codes, with this priority:
                 // - NOT_FOUND is lowest
@@ -517,9 +658,45 @@ impl Rejections {
     }
 }
 
-unit_error! {
-    /// Invalid query
-    pub InvalidQuery: "Invalid query string"
+/// Invalid query
+#[derive(Debug)]
+pub struct InvalidQuery {
+    field: Option<String>,
+    cause: Option<Box<dyn StdError + Send + Sync>>,
+}
+
+impl InvalidQuery {
+    pub(crate) fn new(field: Option<String>, cause: Box<dyn StdError + Send + Sync>) -> Self {
+        InvalidQuery {
+            field,
+            cause: Some(cause),
+        }
+    }
+
+    /// Retrieve the name of the field whose value couldn't be decoded, if known.
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+}
+
+impl fmt::Display for InvalidQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Invalid query string")?;
+        match (&self.field, &self.cause) {
+            (Some(field), Some(cause)) => write!(f, ": field `{}`: {}", field, cause),
+            (None, Some(cause)) => write!(f, ": {}", cause),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+impl StdError for InvalidQuery {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self.cause {
+            Some(ref cause) => Some(cause.as_ref()),
+            None => None,
+        }
+    }
 }
 
 unit_error! {
@@ -527,6 +704,11 @@ unit_error! {
     pub MethodNotAllowed: "HTTP method not allowed"
 }
 
+unit_error! {
+    /// None of the representations the request accepts are available
+    pub NotAcceptable: "None of the request's acceptable representations are available"
+}
+
 unit_error! {
     /// A content-length header is required
     pub LengthRequired: "A content-length header is required"
@@ -542,6 +724,11 @@ unit_error! {
     pub UnsupportedMediaType: "The request's content-type is not supported"
 }
 
+unit_error! {
+    /// A blocking handler panicked, or was cancelled
+    pub BlockingHandlerFailed: "Blocking handler failed"
+}
+
 /// Missing request header
 #[derive(Debug)]
 pub struct MissingHeader {
@@ -659,6 +846,14 @@ mod sealed {
         type Combined = Rejection;
 
         fn combine(self, other: Rejection) -> Self::Combined {
+            // A committed rejection wins over ones that aren't.
+            match (self.is_committed(), other.is_committed()) {
+                (true, true) => return self.uncommit().combine(other.uncommit()).commit(),
+                (true, false) => return self,
+                (false, true) => return other,
+                (false, false) => (),
+            }
+
             let reason = match (self.reason, other.reason) {
                 (Reason::Other(left), Reason::Other(right)) => {
                     Reason::Other(Box::new(Rejections::Combined(left, right)))
@@ -723,6 +918,7 @@ mod tests {
             method_not_allowed().status(),
             StatusCode::METHOD_NOT_ALLOWED
         );
+        assert_eq!(not_acceptable().status(), StatusCode::NOT_ACCEPTABLE);
         assert_eq!(length_required().status(), StatusCode::LENGTH_REQUIRED);
         assert_eq!(payload_too_large().status(), StatusCode::PAYLOAD_TOO_LARGE);
         assert_eq!(
@@ -12,6 +12,8 @@
 //! - `String`
 //! - `&'static str`
 //! - `http::StatusCode`
+//! - `(http::StatusCode, impl Reply)`
+//! - `(http::StatusCode, http::HeaderMap, impl Reply)`
 //!
 //! # Example
 //!
@@ -34,12 +36,32 @@
 //! ```
 
 use std::borrow::Cow;
-use std::convert::TryFrom;
+use std::convert::{Infallible, TryFrom};
+use std::error::Error as StdError;
+use std::fmt;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
 
+use crate::filter::{Filter, FilterClone};
+use crate::filters::cache::{CacheValidators, Cond};
 use crate::generic::{Either, One};
-use http::header::{HeaderName, HeaderValue, CONTENT_TYPE};
-use http::StatusCode;
+use bytes::Bytes;
+use futures_util::{future, ready, Stream, StreamExt, TryStream, TryStreamExt};
+use headers::{
+    AcceptRanges, ContentLength, ContentRange, ETag, Header, HeaderMapExt, IfModifiedSince,
+    IfNoneMatch, LastModified, Range,
+};
+use http::header::{
+    HeaderName, HeaderValue, CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE, ETAG, IF_RANGE,
+    SET_COOKIE,
+};
+use http::{HeaderMap, Method, StatusCode};
+use hyper::body::HttpBody;
 use hyper::Body;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use pin_project::pin_project;
 use serde::Serialize;
 
 // This re-export just looks weird in docs...
@@ -181,6 +203,786 @@ where
     }
 }
 
+/// Reply with a file to download once, removing it after it has been sent.
+///
+/// This is useful for files generated for a single download, like reports or
+/// exports. The file is removed once its contents have been sent, or if the
+/// reply is dropped before that, such as when the client disconnects.
+///
+/// The file is opened asynchronously, so this returns a `Future` of the
+/// reply, to use with [`Filter::then`](crate::Filter::then) or in an
+/// `async` handler. The reply has a `content-type` guessed from the file
+/// extension, and a `content-disposition` suggesting to save it with its
+/// file name. If the file doesn't exist, the reply is a `404 Not Found`.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let export = warp::path("export").then(|| {
+///     // ... write the export to a temporary file ...
+///     warp::reply::file_once("/tmp/export-1234.csv")
+/// });
+/// ```
+pub async fn file_once(path: impl Into<PathBuf>) -> FileOnce {
+    let path = path.into();
+    let opened = async {
+        let file = tokio::fs::File::open(&path).await?;
+        let len = file.metadata().await?.len();
+        Ok::<_, std::io::Error>((file, len))
+    };
+    let inner = match opened.await {
+        Ok((file, len)) => Ok(OnceFile {
+            reader: tokio_util::io::ReaderStream::new(file),
+            len,
+            guard: RemoveOnDrop(path),
+        }),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            tracing::debug!("file_once not found: {:?}", path);
+            Err(StatusCode::NOT_FOUND)
+        }
+        Err(err) => {
+            tracing::error!("file_once open error (path={:?}): {}", path, err);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    };
+    FileOnce { inner }
+}
+
+/// A reply with a file that is removed after being sent.
+///
+/// Returned by `warp::reply::file_once`.
+#[derive(Debug)]
+pub struct FileOnce {
+    inner: Result<OnceFile, StatusCode>,
+}
+
+impl Reply for FileOnce {
+    fn into_response(self) -> Response {
+        let file = match self.inner {
+            Ok(file) => file,
+            Err(status) => return status.into_response(),
+        };
+
+        let path = &file.guard.0;
+        let mime = mime_guess::from_path(path).first_or_octet_stream();
+        let disposition = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|name| name.chars().all(|c| c.is_ascii_graphic() && c != '"'))
+            .and_then(|name| {
+                HeaderValue::from_str(&format!("attachment; filename=\"{}\"", name)).ok()
+            })
+            .unwrap_or_else(|| HeaderValue::from_static("attachment"));
+        let len = file.len;
+
+        let body = futures_util::stream::unfold(file, |mut file| async {
+            let chunk = file.reader.next().await?;
+            Some((chunk, file))
+        });
+
+        let mut res = Response::new(Body::wrap_stream(body));
+        res.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_str(mime.as_ref()).expect("valid mime"),
+        );
+        res.headers_mut()
+            .insert(CONTENT_LENGTH, HeaderValue::from(len));
+        res.headers_mut().insert(CONTENT_DISPOSITION, disposition);
+        res
+    }
+}
+
+// Fields are dropped in order, so the file is closed before it's removed.
+#[derive(Debug)]
+struct OnceFile {
+    reader: tokio_util::io::ReaderStream<tokio::fs::File>,
+    len: u64,
+    guard: RemoveOnDrop,
+}
+
+#[derive(Debug)]
+struct RemoveOnDrop(PathBuf);
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let path = std::mem::take(&mut self.0);
+        let remove = move || match std::fs::remove_file(&path) {
+            Ok(()) => tracing::trace!("file_once removed {:?}", path),
+            Err(err) => tracing::warn!("file_once remove error (path={:?}): {}", path, err),
+        };
+        // Keep the blocking removal off of the runtime's threads.
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => drop(handle.spawn_blocking(remove)),
+            Err(_) => remove(),
+        }
+    }
+}
+
+/// Reply with a streaming body.
+///
+/// The body is sent with chunked transfer encoding, writing items as the
+/// stream yields them. The stream is only polled when the connection is
+/// ready for more, so a slow client holds back the producer instead of
+/// items piling up in memory.
+///
+/// If the stream yields an error, it's logged at `debug` level and the
+/// connection is aborted, so the client can tell the body is incomplete.
+///
+/// No `content-type` is set, so wrap this in a reply that sets one if the
+/// client needs it.
+///
+/// # Example
+///
+/// ```
+/// use futures_util::TryStreamExt;
+/// use warp::Filter;
+///
+/// let route = warp::path("download").map(|| {
+///     let file = tokio::fs::File::open("archive.tar.gz");
+///     let chunks = futures_util::stream::once(file)
+///         .map_ok(tokio_util::io::ReaderStream::new)
+///         .try_flatten();
+///     warp::reply::with_header(
+///         warp::reply::stream(chunks),
+///         "content-type",
+///         "application/gzip",
+///     )
+/// });
+/// ```
+pub fn stream<S>(stream: S) -> Streaming<S>
+where
+    S: TryStream + Send + 'static,
+    S::Ok: Into<Bytes>,
+    S::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    Streaming { stream }
+}
+
+/// A reply with a streaming body.
+///
+/// Returned by `warp::reply::stream`.
+#[allow(missing_debug_implementations)]
+pub struct Streaming<S> {
+    stream: S,
+}
+
+impl<S> Reply for Streaming<S>
+where
+    S: TryStream + Send + 'static,
+    S::Ok: Into<Bytes>,
+    S::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    fn into_response(self) -> Response {
+        let stream = self.stream.map_err(|err| {
+            let err: Box<dyn StdError + Send + Sync> = err.into();
+            tracing::debug!("reply::stream error: {}", err);
+            err
+        });
+        Response::new(Body::wrap_stream(stream))
+    }
+}
+
+/// Reply with a streaming body, flushing each item to the client as soon as
+/// it's yielded.
+///
+/// Each item of the stream is sent as its own chunk. Normally, items that are
+/// ready together may be buffered and written at once, which is more
+/// efficient for large bodies. With this reply, the connection is flushed
+/// after every item, so the client sees each one without delay, at the cost
+/// of more, smaller writes. Prefer it for interactive streams, like tailing
+/// logs or live updates, where each item matters on its own, and
+/// [`stream`] for bulk transfers.
+///
+/// The flush relies on how hyper writes bodies: it buffers chunks while the
+/// stream keeps yielding them, and flushes the connection once the stream
+/// isn't ready. So after each item, this stream reports it isn't ready once
+/// before polling the inner one again.
+///
+/// No `content-type` is set, so wrap this in a reply that sets one if the
+/// client needs it.
+///
+/// # Example
+///
+/// ```
+/// use std::convert::Infallible;
+/// use std::time::Duration;
+/// use futures_util::StreamExt;
+/// use warp::Filter;
+///
+/// let route = warp::path("tail").map(|| {
+///     let lines = tokio_stream::wrappers::IntervalStream::new(
+///         tokio::time::interval(Duration::from_secs(1)),
+///     )
+///     .enumerate()
+///     .map(|(n, _)| Ok::<_, Infallible>(format!("line {}\n", n)));
+///     warp::reply::flushing_stream(lines)
+/// });
+/// ```
+pub fn flushing_stream<S>(stream: S) -> FlushingStream<S>
+where
+    S: TryStream + Send + 'static,
+    S::Ok: Into<Bytes>,
+    S::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    FlushingStream { stream }
+}
+
+/// A streaming reply that flushes after each item.
+///
+/// Returned by `warp::reply::flushing_stream`.
+#[allow(missing_debug_implementations)]
+pub struct FlushingStream<S> {
+    stream: S,
+}
+
+impl<S> Reply for FlushingStream<S>
+where
+    S: TryStream + Send + 'static,
+    S::Ok: Into<Bytes>,
+    S::Error: Into<Box<dyn StdError + Send + Sync>>,
+{
+    fn into_response(self) -> Response {
+        Response::new(Body::wrap_stream(Flushing {
+            stream: self.stream,
+            yielded: false,
+        }))
+    }
+}
+
+// After each item, returns `Pending` once (waking itself right away). hyper
+// keeps writing body chunks to its buffer while they're ready, and flushes
+// the connection when the body isn't, so this flushes each item on its own.
+#[pin_project]
+struct Flushing<S> {
+    #[pin]
+    stream: S,
+    yielded: bool,
+}
+
+impl<S: TryStream> Stream for Flushing<S> {
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let pin = self.project();
+        if *pin.yielded {
+            *pin.yielded = false;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        let item = ready!(pin.stream.try_poll_next(cx));
+        *pin.yielded = item.is_some();
+        Poll::Ready(item)
+    }
+}
+
+/// Reply with a `multipart` body made of `parts`.
+///
+/// The body is streamed, each part's headers followed by its body, delimited
+/// by a random boundary. The `content-type` is set to `multipart/mixed` with
+/// that boundary, or to another subtype with [`Multipart::subtype`], such as
+/// `byteranges` for many ranges of one representation.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::reply::BodyPart;
+///
+/// let route = warp::path("bundle").map(|| {
+///     warp::reply::multipart(vec![
+///         BodyPart::new("hello").header("content-type", "text/plain"),
+///         BodyPart::new(r#"{"hello":"world"}"#).header("content-type", "application/json"),
+///     ])
+/// });
+/// ```
+pub fn multipart<I>(parts: I) -> Multipart
+where
+    I: IntoIterator<Item = BodyPart>,
+{
+    Multipart {
+        subtype: "mixed",
+        parts: parts.into_iter().collect(),
+    }
+}
+
+/// A `multipart` reply.
+///
+/// Returned by `warp::reply::multipart`.
+#[allow(missing_debug_implementations)]
+pub struct Multipart {
+    subtype: &'static str,
+    parts: Vec<BodyPart>,
+}
+
+impl Multipart {
+    /// Set the subtype of the `content-type`, which is `mixed` by default.
+    ///
+    /// If the subtype isn't valid in a header, the error is logged at the
+    /// `error` level, and the reply will be an empty
+    /// `500 Internal Server Error` response.
+    pub fn subtype(mut self, subtype: &'static str) -> Self {
+        self.subtype = subtype;
+        self
+    }
+}
+
+impl Reply for Multipart {
+    fn into_response(self) -> Response {
+        let boundary = multipart_boundary();
+        let content_type = match HeaderValue::try_from(format!(
+            "multipart/{}; boundary={}",
+            self.subtype, boundary
+        )) {
+            Ok(value) => value,
+            Err(err) => {
+                tracing::error!("reply::multipart subtype error: {}", err);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        };
+
+        let close = Bytes::from(format!("--{}--\r\n", boundary));
+        let parts = futures_util::stream::iter(self.parts).flat_map(move |part| {
+            let head = part.head(&boundary);
+            futures_util::stream::once(future::ready(Ok(head)))
+                .chain(part.body)
+                .chain(futures_util::stream::once(future::ready(Ok(
+                    Bytes::from_static(b"\r\n"),
+                ))))
+        });
+        let body = parts.chain(futures_util::stream::once(future::ready(Ok(close))));
+
+        let mut res = Response::new(Body::wrap_stream(body));
+        res.headers_mut().insert(CONTENT_TYPE, content_type);
+        res
+    }
+}
+
+// 32 random hex digits, which won't be found in any part.
+fn multipart_boundary() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("multipart boundary randomness");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A part of a [`multipart`] reply.
+#[allow(missing_debug_implementations)]
+pub struct BodyPart {
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl BodyPart {
+    /// Create a part with `body` and no headers.
+    pub fn new(body: impl Into<Body>) -> Self {
+        BodyPart {
+            headers: HeaderMap::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Add a header to this part.
+    ///
+    /// If the name or value isn't valid, the error is logged at the `error`
+    /// level, and the header is skipped.
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        match <HeaderName as TryFrom<K>>::try_from(name) {
+            Ok(name) => match <HeaderValue as TryFrom<V>>::try_from(value) {
+                Ok(value) => {
+                    self.headers.append(name, value);
+                }
+                Err(err) => {
+                    let err = err.into();
+                    tracing::error!("reply::multipart header value error: {}", err);
+                }
+            },
+            Err(err) => {
+                let err = err.into();
+                tracing::error!("reply::multipart header name error: {}", err);
+            }
+        }
+        self
+    }
+
+    // The delimiter and headers before the body of this part.
+    fn head(&self, boundary: &str) -> Bytes {
+        let mut head = format!("--{}\r\n", boundary).into_bytes();
+        for (name, value) in &self.headers {
+            head.extend_from_slice(name.as_str().as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+        head.extend_from_slice(b"\r\n");
+        Bytes::from(head)
+    }
+}
+
+/// Create a reply with `headers` that only builds its body if it's needed.
+///
+/// When created in a handler, this checks the request the handler is
+/// replying to. The `body` function isn't called, and the reply has no
+/// body, if either:
+///
+/// - The request is a `HEAD`, including one answered by a `GET` filter with
+///   [`head::derive_from_get`](crate::head::derive_from_get).
+/// - The request is a `GET` or `HEAD` with an `if-none-match` header
+///   matching the `etag` in `headers`, or else an `if-modified-since`
+///   header that isn't older than the `last-modified` in `headers`. The reply
+///   is then a `304 Not Modified`.
+///
+/// Otherwise, the reply of `body` is used, with `headers` added to it.
+///
+/// Since a skipped body has no length, include a `content-length` in
+/// `headers` if it's cheap to know.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::http::header::{HeaderMap, HeaderValue, ETAG};
+///
+/// fn render_report() -> String {
+///     // Something expensive...
+///     "the report".into()
+/// }
+///
+/// let route = warp::path("report").map(|| {
+///     let mut headers = HeaderMap::new();
+///     headers.insert(ETAG, HeaderValue::from_static("\"v42\""));
+///     warp::reply::lazy(headers, || warp::reply::html(render_report()))
+/// });
+/// ```
+pub fn lazy<F, R>(headers: HeaderMap, body: F) -> Lazy<F>
+where
+    F: FnOnce() -> R + Send,
+    R: Reply,
+{
+    let skip = if crate::route::is_set() {
+        crate::route::with(|route| lazy_skip(route, &headers))
+    } else {
+        None
+    };
+    Lazy {
+        headers,
+        body,
+        skip,
+    }
+}
+
+/// A reply that builds its body only if needed.
+///
+/// Returned by `warp::reply::lazy`.
+#[allow(missing_debug_implementations)]
+pub struct Lazy<F> {
+    headers: HeaderMap,
+    body: F,
+    // The status to reply with, without a body.
+    skip: Option<StatusCode>,
+}
+
+impl<F, R> Reply for Lazy<F>
+where
+    F: FnOnce() -> R + Send,
+    R: Reply,
+{
+    fn into_response(self) -> Response {
+        let mut res = match self.skip {
+            Some(status) => {
+                let mut res = status.into_response();
+                res.extensions_mut().insert(SkippedBody);
+                res
+            }
+            None => (self.body)().into_response(),
+        };
+        res.headers_mut().extend(self.headers);
+        res
+    }
+}
+
+// Marks a response whose body `Lazy` didn't build.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct SkippedBody;
+
+fn lazy_skip(route: &crate::route::Route, headers: &HeaderMap) -> Option<StatusCode> {
+    let method = route.method();
+    if method != Method::GET && method != Method::HEAD {
+        return None;
+    }
+
+    // `if-modified-since` is only checked without an `if-none-match`.
+    let not_modified = match route.headers().typed_get::<IfNoneMatch>() {
+        Some(if_none_match) => headers
+            .typed_get::<ETag>()
+            .map(|etag| !if_none_match.precondition_passes(&etag))
+            .unwrap_or(false),
+        None => match (
+            route.headers().typed_get::<IfModifiedSince>(),
+            headers.typed_get::<LastModified>(),
+        ) {
+            (Some(since), Some(modified)) => !since.is_modified(modified.into()),
+            _ => false,
+        },
+    };
+
+    if not_modified {
+        tracing::trace!("lazy reply not modified");
+        Some(StatusCode::NOT_MODIFIED)
+    } else if method == Method::HEAD || crate::head::is_derived(route) {
+        tracing::trace!("lazy reply for HEAD");
+        Some(StatusCode::OK)
+    } else {
+        None
+    }
+}
+
+/// Reply with `bytes`, or the part of them asked for by a `range` header.
+///
+/// `headers` are the headers of the request. A satisfiable `range` is
+/// replied with a `206 Partial Content` and a `content-range`, and one that
+/// can't be satisfied with a `416 Range Not Satisfiable`. Without a `range`,
+/// all of `bytes` are replied with a `200 OK`. Only the first range of a
+/// `range` with many is used.
+///
+/// Since the bytes have no validators to compare it with, a request with an
+/// `if-range` is always replied with all of them. To also support `if-range`
+/// and conditional requests, see [`bytes_cacheable`].
+///
+/// # Example
+///
+/// ```
+/// use warp::http::HeaderMap;
+/// use warp::Filter;
+///
+/// fn render_pdf() -> Vec<u8> {
+///     // Something generating a document...
+///     b"%PDF-1.7".to_vec()
+/// }
+///
+/// let route = warp::path("report.pdf")
+///     .and(warp::header::headers_cloned())
+///     .map(|headers: HeaderMap| warp::reply::with_ranges(render_pdf(), &headers));
+/// ```
+pub fn with_ranges(bytes: impl Into<Bytes>, headers: &HeaderMap) -> Response {
+    let bytes = bytes.into();
+    let range = if headers.contains_key(IF_RANGE) {
+        tracing::trace!("if-range without validators, replying with all bytes");
+        None
+    } else {
+        headers.typed_get::<Range>()
+    };
+    range_response(range, bytes.len() as u64, |(start, end)| {
+        Body::from(bytes.slice(start as usize..end as usize))
+    })
+}
+
+/// Creates a `Filter` that serves in-memory bytes, like
+/// [`warp::fs::file`](crate::fs::file) serves a file.
+///
+/// This is useful for assets included with `include_bytes!`, or generated
+/// and cached data. The same conditional and range requests as for files are
+/// supported, responding with `304 Not Modified` when an `if-none-match` or
+/// `if-modified-since` header shows the client has the data already, or
+/// `206 Partial Content` for a `range`.
+///
+/// The `etag` can be given with or without its surrounding quotes, and should
+/// change whenever the data does.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let logo = warp::path("logo.png").and(warp::reply::bytes_cacheable(
+///     &b"\x89PNG..."[..],
+///     "image/png",
+///     Some("logo-v1"),
+///     None,
+/// ));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `content_type` isn't a valid header value, or `etag` isn't a
+/// valid entity tag.
+pub fn bytes_cacheable(
+    data: impl Into<Bytes>,
+    content_type: &str,
+    etag: Option<&str>,
+    last_modified: Option<SystemTime>,
+) -> impl FilterClone<Extract = One<CachedBytes>, Error = Infallible> {
+    let content_type = HeaderValue::from_str(content_type).expect("invalid content-type");
+    let etag = etag.map(|etag| {
+        let etag = if etag.starts_with('"') || etag.starts_with("W/\"") {
+            etag.to_owned()
+        } else {
+            format!("\"{}\"", etag)
+        };
+        etag.parse::<ETag>().expect("invalid etag")
+    });
+    let cached = Cacheable {
+        data: data.into(),
+        content_type,
+        etag,
+        last_modified: last_modified.map(LastModified::from),
+    };
+
+    crate::filters::cache::validators().map(move |conditionals| {
+        tracing::trace!("bytes_cacheable: {} bytes", cached.data.len());
+        cached.clone().conditional(conditionals)
+    })
+}
+
+/// A response of in-memory bytes.
+///
+/// Returned by `warp::reply::bytes_cacheable`.
+#[derive(Debug)]
+pub struct CachedBytes {
+    resp: Response,
+}
+
+impl Reply for CachedBytes {
+    fn into_response(self) -> Response {
+        self.resp
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Cacheable {
+    data: Bytes,
+    content_type: HeaderValue,
+    etag: Option<ETag>,
+    last_modified: Option<LastModified>,
+}
+
+impl Cacheable {
+    fn conditional(self, conditionals: CacheValidators) -> CachedBytes {
+        let Cacheable {
+            data,
+            content_type,
+            etag,
+            last_modified,
+        } = self;
+
+        let mut resp = match conditionals.check(last_modified, etag.as_ref()) {
+            Cond::NoBody(resp) => resp,
+            Cond::WithBody(range) => {
+                let mut resp = range_response(range, data.len() as u64, |(start, end)| {
+                    Body::from(data.slice(start as usize..end as usize))
+                });
+                if resp.status().is_success() {
+                    resp.headers_mut().insert(CONTENT_TYPE, content_type);
+                }
+                resp
+            }
+        };
+
+        if resp.status().is_success() || resp.status() == StatusCode::NOT_MODIFIED {
+            if let Some(etag) = etag {
+                resp.headers_mut().typed_insert(etag);
+            }
+            if let Some(last_modified) = last_modified {
+                resp.headers_mut().typed_insert(last_modified);
+            }
+        }
+        CachedBytes { resp }
+    }
+}
+
+// Builds the response for a representation of `len` bytes, with `body`
+// producing the body for the satisfiable byte range.
+pub(crate) fn range_response<F>(range: Option<Range>, len: u64, body: F) -> Response
+where
+    F: FnOnce((u64, u64)) -> Body,
+{
+    bytes_range(range, len)
+        .map(|(start, end)| {
+            let sub_len = end - start;
+            let mut resp = Response::new(body((start, end)));
+
+            if sub_len != len {
+                *resp.status_mut() = StatusCode::PARTIAL_CONTENT;
+                resp.headers_mut().typed_insert(
+                    ContentRange::bytes(start..end, len).expect("valid ContentRange"),
+                );
+            }
+
+            resp.headers_mut().typed_insert(ContentLength(sub_len));
+            resp.headers_mut().typed_insert(AcceptRanges::bytes());
+            resp
+        })
+        .unwrap_or_else(|BadRange| {
+            // bad byte range
+            let mut resp = Response::new(Body::empty());
+            *resp.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            resp.headers_mut()
+                .typed_insert(ContentRange::unsatisfied_bytes(len));
+            resp
+        })
+}
+
+struct BadRange;
+
+fn bytes_range(range: Option<Range>, max_len: u64) -> Result<(u64, u64), BadRange> {
+    use std::ops::Bound;
+
+    let range = if let Some(range) = range {
+        range
+    } else {
+        return Ok((0, max_len));
+    };
+
+    let ret = range
+        .iter()
+        .map(|(start, end)| {
+            let start = match start {
+                Bound::Unbounded => 0,
+                Bound::Included(s) => s,
+                Bound::Excluded(s) => s + 1,
+            };
+
+            let end = match end {
+                Bound::Unbounded => max_len,
+                Bound::Included(s) => {
+                    // For the special case where s == the file size
+                    if s == max_len {
+                        s
+                    } else {
+                        s + 1
+                    }
+                }
+                Bound::Excluded(s) => s,
+            };
+
+            if start < end && end <= max_len {
+                Ok((start, end))
+            } else {
+                tracing::trace!("unsatisfiable byte range: {}-{}/{}", start, end, max_len);
+                Err(BadRange)
+            }
+        })
+        .next()
+        .unwrap_or(Ok((0, max_len)));
+    ret
+}
+
+/*
+TODO: `103 Early Hints`
+
+It'd be nice to have something like `reply::early_hints(links)`, sending
+an informational response with `link: <..>; rel=preload` headers before
+the final response. However, hyper 0.14 has no way for a server to send a
+1xx response (it treats such a status as a user error and replies with a
+500 instead), so this needs to wait until hyper supports it.
+*/
+
 /// Types that can be converted into a `Response`.
 ///
 /// This trait is implemented for the following:
@@ -217,63 +1019,49 @@ pub trait Reply: BoxedReply + Send {
     /// [`Response`]: type.Response.html
     fn into_response(self) -> Response;
 
-    /*
-    TODO: Currently unsure about having trait methods here, as it
-    requires returning an exact type, which I'd rather not commit to.
-    Additionally, it doesn't work great with `Box<Reply>`.
-
-    A possible alternative is to have wrappers, like
-
-    - `WithStatus<R: Reply>(StatusCode, R)`
-
-
     /// Change the status code of this `Reply`.
-    fn with_status(self, status: StatusCode) -> Reply_
+    ///
+    /// The same as [`warp::reply::with_status`](with_status).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::http::StatusCode;
+    /// use warp::Reply;
+    ///
+    /// let reply = warp::reply().with_status(StatusCode::CREATED);
+    /// ```
+    fn with_status(self, status: StatusCode) -> WithStatus<Self>
     where
         Self: Sized,
     {
-        let mut res = self.into_response();
-        *res.status_mut() = status;
-        Reply_(res)
+        with_status(self, status)
     }
 
     /// Add a header to this `Reply`.
     ///
+    /// The same as [`warp::reply::with_header`](with_header).
+    ///
     /// # Example
     ///
-    /// ```rust
+    /// ```
+    /// use warp::http::StatusCode;
     /// use warp::Reply;
     ///
     /// let reply = warp::reply()
+    ///     .with_status(StatusCode::CREATED)
     ///     .with_header("x-foo", "bar");
     /// ```
-    fn with_header<K, V>(self, name: K, value: V) -> Reply_
+    fn with_header<K, V>(self, name: K, value: V) -> WithHeader<Self>
     where
         Self: Sized,
         HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
         HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
     {
-        match <HeaderName as TryFrom<K>>::try_from(name) {
-            Ok(name) => match <HeaderValue as TryFrom<V>>::try_from(value) {
-                Ok(value) => {
-                    let mut res = self.into_response();
-                    res.headers_mut().append(name, value);
-                    Reply_(res)
-                },
-                Err(err) => {
-                    tracing::error!("with_header value error: {}", err.into());
-                    Reply_(::reject::server_error()
-                        .into_response())
-                }
-            },
-            Err(err) => {
-                tracing::error!("with_header name error: {}", err.into());
-                Reply_(::reject::server_error()
-                    .into_response())
-            }
-        }
+        with_header(self, name, value)
     }
-    */
 }
 
 impl<T: Reply + ?Sized> Reply for Box<T> {
@@ -359,6 +1147,28 @@ where
     WithHeader { header, reply }
 }
 
+/// Wrap an `impl Reply` to add an `etag` header.
+///
+/// To also reply with a `304 Not Modified` when the client's copy is still
+/// fresh, see [`CacheValidators::conditional`](crate::cache::CacheValidators::conditional).
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::any().map(|| {
+///     let etag = "W/\"v42\"".parse().unwrap();
+///     warp::reply::with_etag(warp::reply::json(&"sean"), etag)
+/// });
+/// ```
+pub fn with_etag<T: Reply>(reply: T, etag: ETag) -> WithHeader<T> {
+    let mut values = Vec::with_capacity(1);
+    etag.encode(&mut values);
+    let header = values.pop().map(|value| (ETAG, value));
+    WithHeader { header, reply }
+}
+
 /// Wraps an `impl Reply` and adds a header when rendering.
 ///
 /// Returned by `warp::reply::with_header`.
@@ -372,12 +1182,333 @@ impl<T: Reply> Reply for WithHeader<T> {
     fn into_response(self) -> Response {
         let mut res = self.reply.into_response();
         if let Some((name, value)) = self.header {
-            res.headers_mut().insert(name, value);
+            // Each cookie needs its own `set-cookie` header, they can't be
+            // folded into one.
+            if name == SET_COOKIE {
+                res.headers_mut().append(name, value);
+            } else {
+                res.headers_mut().insert(name, value);
+            }
         }
         res
     }
 }
 
+/// Wrap an `impl Reply` to set several cookies.
+///
+/// Each item is the value of a `set-cookie` header, such as
+/// `"id=a3fWa; Max-Age=2592000; HttpOnly"`, and gets a header of its own.
+/// Cookies already set by the reply are kept. Invalid header values are
+/// logged and skipped.
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+///
+/// let route = warp::path("login")
+///     .map(warp::reply)
+///     .map(|reply| {
+///         warp::reply::with_cookies(reply, vec![
+///             "session=38afes7a8; Path=/; HttpOnly",
+///             "theme=dark; Path=/",
+///         ])
+///     });
+/// ```
+pub fn with_cookies<T: Reply, I>(reply: T, cookies: I) -> WithCookies<T>
+where
+    I: IntoIterator,
+    HeaderValue: TryFrom<I::Item>,
+    <HeaderValue as TryFrom<I::Item>>::Error: Into<http::Error>,
+{
+    let cookies = cookies
+        .into_iter()
+        .filter_map(
+            |cookie| match <HeaderValue as TryFrom<I::Item>>::try_from(cookie) {
+                Ok(value) => Some(value),
+                Err(err) => {
+                    let err = err.into();
+                    tracing::error!("with_cookies value error: {}", err);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    WithCookies { cookies, reply }
+}
+
+/// Wraps an `impl Reply` and adds `set-cookie` headers when rendering.
+///
+/// Returned by `warp::reply::with_cookies`.
+#[derive(Debug)]
+pub struct WithCookies<T> {
+    cookies: Vec<HeaderValue>,
+    reply: T,
+}
+
+impl<T: Reply> Reply for WithCookies<T> {
+    fn into_response(self) -> Response {
+        let mut res = self.reply.into_response();
+        for cookie in self.cookies {
+            res.headers_mut().append(SET_COOKIE, cookie);
+        }
+        res
+    }
+}
+
+/// Wrap an `impl Reply` to set a [`Cookie`](./struct.Cookie.html).
+///
+/// The cookie gets a `set-cookie` header of its own, so cookies already set
+/// by the reply, or by other calls to `with_cookie`, are kept. A cookie with
+/// an invalid name is logged and skipped.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use warp::Filter;
+/// use warp::reply::{Cookie, SameSite};
+///
+/// let route = warp::path("login")
+///     .map(warp::reply)
+///     .map(|reply| {
+///         let session = Cookie::new("session", "38afes7a8")
+///             .path("/")
+///             .http_only(true)
+///             .secure(true)
+///             .same_site(SameSite::Lax)
+///             .max_age(Duration::from_secs(60 * 60 * 24));
+///         warp::reply::with_cookie(reply, session)
+///     });
+/// ```
+pub fn with_cookie<T: Reply>(reply: T, cookie: Cookie) -> WithCookies<T> {
+    if !is_cookie_name(&cookie.name) {
+        tracing::error!("with_cookie invalid name: {:?}", cookie.name);
+        return with_cookies(reply, None::<String>);
+    }
+    with_cookies(reply, Some(cookie.to_string()))
+}
+
+// Names are tokens (RFC 6265), so they can't contain separators.
+fn is_cookie_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+}
+
+// Characters outside of `cookie-octet` (RFC 6265), plus `%` so that encoded
+// values can be decoded without ambiguity.
+const COOKIE_VALUE: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b',')
+    .add(b';')
+    .add(b'\\')
+    .add(b'%');
+
+// Attribute values only end at a `;`.
+const COOKIE_ATTRIBUTE: &AsciiSet = &CONTROLS.add(b';');
+
+/// A cookie to set with [`with_cookie`](./fn.with_cookie.html).
+///
+/// The value is percent-encoded where it contains characters that aren't
+/// allowed in a `set-cookie` header, so any string can be used. Read it back
+/// with [`cookie::decoded`](crate::cookie::decoded), which decodes it again.
+///
+/// The name must be a token, without spaces or separators like `=` and
+/// `;`, since it's used as it is.
+#[derive(Clone, Debug)]
+pub struct Cookie {
+    pub(crate) name: String,
+    pub(crate) value: String,
+    path: Option<String>,
+    domain: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+    max_age: Option<Duration>,
+    expires: Option<SystemTime>,
+}
+
+/// The `SameSite` attribute of a [`Cookie`](./struct.Cookie.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    /// The cookie is only sent with requests from the same site.
+    Strict,
+    /// The cookie is also sent when navigating to the site from elsewhere.
+    Lax,
+    /// The cookie is sent with all requests. Browsers require it to be
+    /// `Secure` too.
+    None,
+}
+
+impl Cookie {
+    /// Creates a cookie with a name and value, and no attributes.
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Cookie {
+        Cookie {
+            name: name.into(),
+            value: value.into(),
+            path: None,
+            domain: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+            max_age: None,
+            expires: None,
+        }
+    }
+
+    /// Sets the `Path` attribute.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Sets the `Domain` attribute.
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Sets whether the cookie is only sent over secure connections.
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// Sets whether the cookie is hidden from scripts.
+    pub fn http_only(mut self, http_only: bool) -> Self {
+        self.http_only = http_only;
+        self
+    }
+
+    /// Sets the `SameSite` attribute.
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// Sets the `Max-Age` attribute, in whole seconds.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Sets the `Expires` attribute.
+    pub fn expires(mut self, expires: SystemTime) -> Self {
+        self.expires = Some(expires);
+        self
+    }
+}
+
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}={}",
+            self.name,
+            utf8_percent_encode(&self.value, COOKIE_VALUE),
+        )?;
+        if let Some(ref path) = self.path {
+            write!(f, "; Path={}", utf8_percent_encode(path, COOKIE_ATTRIBUTE))?;
+        }
+        if let Some(ref domain) = self.domain {
+            write!(
+                f,
+                "; Domain={}",
+                utf8_percent_encode(domain, COOKIE_ATTRIBUTE)
+            )?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(f, "; Max-Age={}", max_age.as_secs())?;
+        }
+        if let Some(expires) = self.expires {
+            let mut values = Vec::with_capacity(1);
+            headers::Expires::from(expires).encode(&mut values);
+            for value in values {
+                // Dates are formatted as ASCII.
+                write!(f, "; Expires={}", value.to_str().map_err(|_| fmt::Error)?)?;
+            }
+        }
+        if self.secure {
+            f.write_str("; Secure")?;
+        }
+        if self.http_only {
+            f.write_str("; HttpOnly")?;
+        }
+        match self.same_site {
+            Some(SameSite::Strict) => f.write_str("; SameSite=Strict"),
+            Some(SameSite::Lax) => f.write_str("; SameSite=Lax"),
+            Some(SameSite::None) => f.write_str("; SameSite=None"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Wrap an `impl Reply` to send `trailers` once its body is complete.
+///
+/// Trailers are headers sent after the body, such as the `grpc-status` of a
+/// streamed response. They're sent over HTTP/2, and are dropped on
+/// connections that can't carry them, such as HTTP/1.1.
+///
+/// The body is forwarded by a task spawned on the tokio runtime, so the reply
+/// must be rendered within one.
+///
+/// # Example
+///
+/// ```
+/// use warp::http::header::{HeaderMap, HeaderValue};
+/// use warp::Filter;
+///
+/// let route = warp::path("status")
+///     .map(|| {
+///         let mut trailers = HeaderMap::new();
+///         trailers.insert("grpc-status", HeaderValue::from_static("0"));
+///         warp::reply::with_trailers("done", trailers)
+///     });
+/// ```
+pub fn with_trailers<T: Reply>(reply: T, trailers: HeaderMap) -> WithTrailers<T> {
+    WithTrailers { reply, trailers }
+}
+
+/// Wraps an `impl Reply` and sends trailers after its body.
+///
+/// Returned by `warp::reply::with_trailers`.
+#[derive(Debug)]
+pub struct WithTrailers<T> {
+    reply: T,
+    trailers: HeaderMap,
+}
+
+impl<T: Reply> Reply for WithTrailers<T> {
+    fn into_response(self) -> Response {
+        let (parts, mut body) = self.reply.into_response().into_parts();
+        let trailers = self.trailers;
+        let (mut tx, chan) = Body::channel();
+        tokio::spawn(async move {
+            while let Some(chunk) = body.data().await {
+                match chunk {
+                    Ok(chunk) => {
+                        if tx.send_data(chunk).await.is_err() {
+                            // the response was dropped
+                            return;
+                        }
+                    }
+                    Err(err) => {
+                        tracing::debug!("with_trailers body error: {}", err);
+                        tx.abort();
+                        return;
+                    }
+                }
+            }
+            let _ = tx.send_trailers(trailers).await;
+        });
+        Response::from_parts(parts, chan)
+    }
+}
+
 impl<T: Send> Reply for ::http::Response<T>
 where
     Body: From<T>,
@@ -397,6 +1528,41 @@ impl Reply for ::http::StatusCode {
     }
 }
 
+/// Sets the status of the inner reply, like [`with_status`].
+///
+/// # Example
+///
+/// ```
+/// use warp::Filter;
+/// use warp::http::StatusCode;
+///
+/// let route = warp::post()
+///     .map(|| (StatusCode::CREATED, warp::reply::json(&"created")));
+/// ```
+impl<T: Reply> Reply for (StatusCode, T) {
+    #[inline]
+    fn into_response(self) -> Response {
+        let (status, reply) = self;
+        let mut res = reply.into_response();
+        *res.status_mut() = status;
+        res
+    }
+}
+
+/// Sets the status and headers of the inner reply.
+///
+/// Headers replace any of the same name the reply already had.
+impl<T: Reply> Reply for (StatusCode, HeaderMap, T) {
+    #[inline]
+    fn into_response(self) -> Response {
+        let (status, headers, reply) = self;
+        let mut res = reply.into_response();
+        *res.status_mut() = status;
+        res.headers_mut().extend(headers);
+        res
+    }
+}
+
 impl Reply for ::http::Error {
     #[inline]
     fn into_response(self) -> Response {
@@ -33,6 +33,22 @@ pub(crate) struct Route {
     remote_addr: Option<SocketAddr>,
     req: Request,
     segments_index: usize,
+    matched: Vec<MatchedSegment>,
+}
+
+// A path segment matched by a path filter, recorded for `matched_pattern`.
+#[derive(Debug)]
+struct MatchedSegment {
+    start: usize,
+    end: usize,
+    kind: Segment,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Segment {
+    Exact,
+    Param,
+    Tail,
 }
 
 #[derive(Debug)]
@@ -55,6 +71,7 @@ impl Route {
             remote_addr,
             req,
             segments_index,
+            matched: Vec::new(),
         })
     }
 
@@ -62,6 +79,10 @@ impl Route {
         self.req.method()
     }
 
+    pub(crate) fn set_method(&mut self, method: http::Method) {
+        *self.req.method_mut() = method;
+    }
+
     pub(crate) fn headers(&self) -> &http::HeaderMap {
         self.req.headers()
     }
@@ -74,7 +95,6 @@ impl Route {
         self.req.extensions()
     }
 
-    #[cfg(feature = "websocket")]
     pub(crate) fn extensions_mut(&mut self) -> &mut http::Extensions {
         self.req.extensions_mut()
     }
@@ -105,6 +125,34 @@ impl Route {
         }
     }
 
+    // Records that the path from `start` up to the current matched index was
+    // matched by a path filter of this kind.
+    pub(crate) fn push_matched_segment(&mut self, start: usize, kind: Segment) {
+        self.matched.push(MatchedSegment {
+            start,
+            end: self.segments_index,
+            kind,
+        });
+    }
+
+    // The pattern of the path filters matched so far, such as
+    // `/users/:param`.
+    pub(crate) fn matched_pattern(&self) -> String {
+        let path = self.req.uri().path();
+        let mut pattern = String::from("/");
+        for (i, seg) in self.matched.iter().enumerate() {
+            if i > 0 {
+                pattern.push('/');
+            }
+            match seg.kind {
+                Segment::Exact => pattern.push_str(path[seg.start..seg.end].trim_end_matches('/')),
+                Segment::Param => pattern.push_str(":param"),
+                Segment::Tail => pattern.push('*'),
+            }
+        }
+        pattern
+    }
+
     pub(crate) fn query(&self) -> Option<&str> {
         self.req.uri().query()
     }
@@ -121,6 +169,42 @@ impl Route {
             index,
         );
         self.segments_index = index;
+        self.matched.retain(|seg| seg.end <= index);
+    }
+
+    // Replaces the unmatched part of the path, keeping the query.
+    //
+    // Returns the previous URI and matched index, to be put back with
+    // `restore_uri`.
+    pub(crate) fn rewrite_unmatched_path(
+        &mut self,
+        unmatched: &str,
+    ) -> Result<(http::Uri, usize), http::Error> {
+        let mut path = self.req.uri().path()[..self.segments_index].to_owned();
+        let unmatched = unmatched.trim_start_matches('/');
+        if !path.ends_with('/') && (path.is_empty() || !unmatched.is_empty()) {
+            path.push('/');
+        }
+        let segments_index = path.len();
+        path.push_str(unmatched);
+        if let Some(query) = self.req.uri().query() {
+            path.push('?');
+            path.push_str(query);
+        }
+
+        let mut parts = self.req.uri().clone().into_parts();
+        parts.path_and_query = Some(path.parse()?);
+        let uri = http::Uri::from_parts(parts)?;
+
+        let prev = mem::replace(self.req.uri_mut(), uri);
+        let prev_index = mem::replace(&mut self.segments_index, segments_index);
+        Ok((prev, prev_index))
+    }
+
+    pub(crate) fn restore_uri(&mut self, uri: http::Uri, index: usize) {
+        *self.req.uri_mut() = uri;
+        self.segments_index = index;
+        self.matched.retain(|seg| seg.end <= index);
     }
 
     pub(crate) fn remote_addr(&self) -> Option<SocketAddr> {
@@ -137,4 +221,11 @@ impl Route {
             BodyState::Taken => None,
         }
     }
+
+    // Puts back a body taken with `take_body`, such as one wrapped by a
+    // filter, for the next filters to take.
+    pub(crate) fn set_body(&mut self, body: Body) {
+        *self.req.body_mut() = body;
+        self.body = BodyState::Ready;
+    }
 }
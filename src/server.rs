@@ -2,22 +2,30 @@
 use crate::tls::TlsConfigBuilder;
 use std::convert::Infallible;
 use std::error::Error as StdError;
+use std::fmt;
 use std::future::Future;
 use std::net::SocketAddr;
-#[cfg(feature = "tls")]
+#[cfg(any(unix, feature = "tls"))]
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 use futures_util::{future, FutureExt, TryFuture, TryStream, TryStreamExt};
+use http::header::{HeaderValue, ALT_SVC, SERVER};
+use http::{Method, StatusCode};
 use hyper::server::conn::AddrIncoming;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::Server as HyperServer;
 use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use tracing::Instrument;
 
+use crate::filter::service::UnhandledNotFound;
 use crate::filter::Filter;
 use crate::reject::IsReject;
-use crate::reply::Reply;
-use crate::transport::Transport;
+use crate::reply::{Reply, Response};
+use crate::shutdown::ShutdownToken;
+use crate::transport::{AcceptErrors, LimitedAccept, OnAcceptErrorFn, Transport};
 
 /// Create a `Server` with the provided `Filter`.
 pub fn serve<F>(filter: F) -> Server<F>
@@ -27,7 +35,17 @@ where
     F::Error: IsReject,
 {
     Server {
+        alt_svc: None,
+        server_header: None,
+        map_response: None,
+        not_found: None,
+        concurrency: None,
+        shed_load: false,
+        max_connections: None,
         pipeline: false,
+        protocol: Protocol::Auto,
+        on_accept_error: None,
+        shutdown: ShutdownToken::new(),
         filter,
     }
 }
@@ -35,7 +53,17 @@ where
 /// A Warp Server ready to filter requests.
 #[derive(Debug)]
 pub struct Server<F> {
+    alt_svc: Option<HeaderValue>,
+    server_header: Option<HeaderValue>,
+    map_response: Option<MapResponse>,
+    not_found: Option<NotFound>,
+    concurrency: Option<Arc<Semaphore>>,
+    shed_load: bool,
+    max_connections: Option<Arc<Semaphore>>,
     pipeline: bool,
+    protocol: Protocol,
+    on_accept_error: Option<OnAcceptError>,
+    shutdown: ShutdownToken,
     filter: F,
 }
 
@@ -48,25 +76,226 @@ pub struct TlsServer<F> {
     tls: TlsConfigBuilder,
 }
 
+/// What to do after a server fails to accept a connection.
+///
+/// Returned by the handler set with [`Server::on_accept_error`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AcceptErrorAction {
+    /// Accept the next connection right away.
+    Continue,
+    /// Wait for a while before accepting the next connection.
+    Sleep(Duration),
+    /// Stop accepting connections, ending the server.
+    Stop,
+}
+
+// Decides what to do after an accept error, set with
+// `Server::on_accept_error`.
+#[derive(Clone)]
+struct OnAcceptError(Arc<OnAcceptErrorFn>);
+
+impl fmt::Debug for OnAcceptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnAcceptError").finish()
+    }
+}
+
+// A function applied to every response, set with `Server::map_response`.
+#[derive(Clone)]
+struct MapResponse(Arc<dyn Fn(Response) -> Response + Send + Sync>);
+
+impl fmt::Debug for MapResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapResponse").finish()
+    }
+}
+
+// Builds the reply for requests no route matched, set with `Server::not_found`.
+#[derive(Clone)]
+struct NotFound(Arc<NotFoundFn>);
+
+type NotFoundFn = dyn Fn(&Method, &str) -> Response + Send + Sync;
+
+impl fmt::Debug for NotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotFound").finish()
+    }
+}
+
+// Limits the requests served at the same time, set with
+// `Server::max_concurrency`.
+#[derive(Clone, Debug)]
+struct Concurrency {
+    semaphore: Arc<Semaphore>,
+    shed: bool,
+}
+
+impl Concurrency {
+    // Waits for a permit, or returns `None` right away when shedding load.
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        if self.shed {
+            self.semaphore.clone().try_acquire_owned().ok()
+        } else {
+            self.semaphore.clone().acquire_owned().await.ok()
+        }
+    }
+}
+
+// Binds a `UnixListener`, replacing a stale socket file, and returns its
+// connections along with a guard removing the socket file on drop.
+#[cfg(unix)]
+fn bind_unix(
+    path: &Path,
+) -> std::io::Result<(
+    impl futures_util::Stream<Item = std::io::Result<tokio::net::UnixStream>> + Send,
+    UnixSocketFile,
+)> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if meta.file_type().is_socket() {
+            // Only a socket that nothing listens on anymore is stale.
+            match std::os::unix::net::UnixStream::connect(path) {
+                Ok(_) => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::AddrInUse,
+                        "a server is already listening on the socket",
+                    ));
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::ConnectionRefused => {
+                    tracing::debug!("removing stale socket file {:?}", path);
+                    std::fs::remove_file(path)?;
+                }
+                Err(err) => {
+                    tracing::debug!("socket file {:?} connect error: {}", path, err);
+                }
+            }
+        }
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    let incoming = futures_util::stream::poll_fn(move |cx| {
+        listener
+            .poll_accept(cx)
+            .map(|result| Some(result.map(|(stream, _)| stream)))
+    });
+    Ok((incoming, UnixSocketFile(path.to_owned())))
+}
+
+#[cfg(unix)]
+struct UnixSocketFile(std::path::PathBuf);
+
+#[cfg(unix)]
+impl Drop for UnixSocketFile {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.0) {
+            Ok(()) => tracing::trace!("removed socket file {:?}", self.0),
+            Err(err) => tracing::warn!("socket file remove error (path={:?}): {}", self.0, err),
+        }
+    }
+}
+
+// The HTTP versions accepted on connections, set with `Server::http1_only`
+// and `Server::http2_only`.
+#[derive(Clone, Copy, Debug)]
+enum Protocol {
+    Auto,
+    Http1Only,
+    Http2Only,
+}
+
+// Applies the connection settings of a `Server` to a hyper builder.
+fn hyper_builder<I>(incoming: I, pipeline: bool, protocol: Protocol) -> hyper::server::Builder<I> {
+    let builder = HyperServer::builder(incoming).http1_pipeline_flush(pipeline);
+    match protocol {
+        Protocol::Auto => builder,
+        Protocol::Http1Only => builder.http1_only(true),
+        Protocol::Http2Only => builder.http2_only(true),
+    }
+}
+
 // Getting all various generic bounds to make this a re-usable method is
 // very complicated, so instead this is just a macro.
 macro_rules! into_service {
-    ($into:expr) => {{
-        let inner = crate::service($into);
+    ($this:expr) => {{
+        let inner = crate::service($this.filter);
+        let alt_svc = $this.alt_svc;
+        let server_header = $this.server_header;
+        let map_response = $this.map_response;
+        let not_found = $this.not_found;
+        let shed = $this.shed_load;
+        let concurrency = $this
+            .concurrency
+            .map(|semaphore| Concurrency { semaphore, shed });
+        let shutdown = $this.shutdown;
         make_service_fn(move |transport| {
             let inner = inner.clone();
+            let alt_svc = alt_svc.clone();
+            let server_header = server_header.clone();
+            let map_response = map_response.clone();
+            let not_found = not_found.clone();
+            let concurrency = concurrency.clone();
+            let shutdown = shutdown.clone();
             let remote_addr = Transport::remote_addr(transport);
-            future::ok::<_, Infallible>(service_fn(move |req| {
-                inner.call_with_addr(req, remote_addr)
+            #[cfg(feature = "tls")]
+            let peer_certs = Transport::peer_certificates(transport);
+            future::ok::<_, Infallible>(service_fn(move |mut req: crate::Request| {
+                let alt_svc = alt_svc.clone();
+                let server_header = server_header.clone();
+                let map_response = map_response.clone();
+                let concurrency = concurrency.clone();
+                // Only kept when needed to build a `not_found` reply.
+                let not_found = not_found.clone().map(|not_found| {
+                    (not_found, req.method().clone(), req.uri().path().to_owned())
+                });
+                req.extensions_mut().insert(shutdown.clone());
+                #[cfg(feature = "tls")]
+                if let Some(ref peer_certs) = peer_certs {
+                    peer_certs.add_to(&mut req);
+                }
+                let finish = move |mut res: Response| {
+                    if let Some((NotFound(func), method, path)) = not_found {
+                        if res.extensions().get::<UnhandledNotFound>().is_some() {
+                            res = func(&method, &path);
+                        }
+                    }
+                    if let Some(alt_svc) = alt_svc {
+                        res.headers_mut().entry(ALT_SVC).or_insert(alt_svc);
+                    }
+                    if let Some(server_header) = server_header {
+                        res.headers_mut().entry(SERVER).or_insert(server_header);
+                    }
+                    match map_response {
+                        Some(MapResponse(func)) => func(res),
+                        None => res,
+                    }
+                };
+                let fut = inner.call_with_addr(req, remote_addr);
+                async move {
+                    let _permit = match concurrency {
+                        Some(ref concurrency) => match concurrency.acquire().await {
+                            Some(permit) => Some(permit),
+                            None => {
+                                tracing::debug!("too many requests in flight, shedding");
+                                let mut res = Response::default();
+                                *res.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+                                return Ok(finish(res));
+                            }
+                        },
+                        None => None,
+                    };
+                    fut.await.map(finish)
+                }
             }))
         })
     }};
 }
 
 macro_rules! addr_incoming {
-    ($addr:expr) => {{
+    ($addr:expr, $on_accept_error:expr) => {{
         let mut incoming = AddrIncoming::bind($addr)?;
         incoming.set_nodelay(true);
+        // hyper's own backoff is replaced by the handler.
+        incoming.set_sleep_on_errors($on_accept_error.is_none());
         let addr = incoming.local_addr();
         (addr, incoming)
     }};
@@ -74,21 +303,25 @@ macro_rules! addr_incoming {
 
 macro_rules! bind_inner {
     ($this:ident, $addr:expr) => {{
-        let service = into_service!($this.filter);
-        let (addr, incoming) = addr_incoming!($addr);
-        let srv = HyperServer::builder(incoming)
-            .http1_pipeline_flush($this.pipeline)
-            .serve(service);
+        let service = into_service!($this);
+        let on_accept_error = $this.on_accept_error.map(|OnAcceptError(func)| func);
+        let (addr, incoming) = addr_incoming!($addr, on_accept_error);
+        let incoming = AcceptErrors::new(incoming, on_accept_error);
+        let incoming = LimitedAccept::new(incoming, $this.max_connections);
+        let srv = hyper_builder(incoming, $this.pipeline, $this.protocol).serve(service);
         Ok::<_, hyper::Error>((addr, srv))
     }};
 
     (tls: $this:ident, $addr:expr) => {{
-        let service = into_service!($this.server.filter);
-        let (addr, incoming) = addr_incoming!($addr);
+        let service = into_service!($this.server);
+        let on_accept_error = $this.server.on_accept_error.map(|OnAcceptError(func)| func);
+        let (addr, incoming) = addr_incoming!($addr, on_accept_error);
         let tls = $this.tls.build()?;
-        let srv = HyperServer::builder(crate::tls::TlsAcceptor::new(tls, incoming))
-            .http1_pipeline_flush($this.server.pipeline)
-            .serve(service);
+        let incoming = crate::tls::TlsAcceptor::new(tls, incoming);
+        let incoming = AcceptErrors::new(incoming, on_accept_error);
+        let incoming = LimitedAccept::new(incoming, $this.server.max_connections);
+        let srv =
+            hyper_builder(incoming, $this.server.pipeline, $this.server.protocol).serve(service);
         Ok::<_, Box<dyn std::error::Error + Send + Sync>>((addr, srv))
     }};
 }
@@ -171,6 +404,9 @@ where
     /// Bind to a socket address, returning a `Future` that can be
     /// executed on the current runtime.
     ///
+    /// To learn the address that was bound, such as the port picked when
+    /// binding to port `0`, use [`bind_ephemeral`](Server::bind_ephemeral).
+    ///
     /// # Panics
     ///
     /// Panics if we are unable to bind to the provided address.
@@ -179,6 +415,62 @@ where
         fut
     }
 
+    /// Bind to several socket addresses, returning a `Future` that serves
+    /// this filter on all of them, and can be executed on the current runtime.
+    ///
+    /// This is useful for binding both an IPv4 and IPv6 address, or for
+    /// exposing the same service on multiple ports.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .bind_all(vec![
+    ///         ([127, 0, 0, 1], 3030).into(),
+    ///         ([0, 0, 0, 0, 0, 0, 0, 1], 3030).into(),
+    ///     ])
+    ///     .await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to any of the provided addresses.
+    pub fn bind_all(
+        self,
+        addrs: impl IntoIterator<Item = SocketAddr>,
+    ) -> impl Future<Output = ()> + 'static {
+        let servers = addrs
+            .into_iter()
+            .map(|addr| {
+                let server = Server {
+                    alt_svc: self.alt_svc.clone(),
+                    server_header: self.server_header.clone(),
+                    map_response: self.map_response.clone(),
+                    not_found: self.not_found.clone(),
+                    concurrency: self.concurrency.clone(),
+                    shed_load: self.shed_load,
+                    max_connections: self.max_connections.clone(),
+                    pipeline: self.pipeline,
+                    protocol: self.protocol,
+                    on_accept_error: self.on_accept_error.clone(),
+                    shutdown: self.shutdown.clone(),
+                    filter: self.filter.clone(),
+                };
+                let (addr, fut) = server.bind_ephemeral(addr);
+                tracing::info!("listening on http://{}", addr);
+                fut
+            })
+            .collect::<Vec<_>>();
+
+        future::join_all(servers).map(|_| ())
+    }
+
     /// Bind to a socket address, returning a `Future` that can be
     /// executed on any runtime.
     ///
@@ -205,7 +497,24 @@ where
     /// Bind to a possibly ephemeral socket address.
     ///
     /// Returns the bound address and a `Future` that can be executed on
-    /// the current runtime.
+    /// the current runtime. The address is known as soon as this returns,
+    /// before the server starts, so when binding to port `0`, the port the
+    /// OS picked can be registered with a service discovery system, or
+    /// passed to tests.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// let (addr, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    /// println!("listening on port {}", addr.port());
+    /// server.await;
+    /// # }
+    /// ```
     ///
     /// # Panics
     ///
@@ -288,6 +597,7 @@ where
         addr: impl Into<SocketAddr> + 'static,
         signal: impl Future<Output = ()> + Send + 'static,
     ) -> (SocketAddr, impl Future<Output = ()> + 'static) {
+        let signal = self.shutdown.on_signal(signal);
         let (addr, srv) = bind!(self, addr);
         let fut = srv.with_graceful_shutdown(signal).map(|result| {
             if let Err(err) = result {
@@ -307,6 +617,7 @@ where
         signal: impl Future<Output = ()> + Send + 'static,
     ) -> Result<(SocketAddr, impl Future<Output = ()> + 'static), crate::Error> {
         let addr = addr.into();
+        let signal = self.shutdown.on_signal(signal);
         let (addr, srv) = try_bind!(self, &addr).map_err(crate::Error::new)?;
         let srv = srv.with_graceful_shutdown(signal).map(|result| {
             if let Err(err) = result {
@@ -317,6 +628,85 @@ where
         Ok((addr, srv))
     }
 
+    /// Run this `Server` forever on a Unix Domain Socket at `path`.
+    ///
+    /// See [`bind_unix`](Server::bind_unix) for how the socket file is
+    /// managed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to the provided path.
+    #[cfg(unix)]
+    pub async fn run_unix(self, path: impl AsRef<Path>) {
+        let path = path.as_ref();
+        let span = tracing::info_span!("Server::run_unix", ?path);
+        tracing::info!(parent: &span, "listening on unix:{}", path.display());
+
+        self.bind_unix(path).instrument(span).await;
+    }
+
+    /// Bind to a Unix Domain Socket at `path`, returning a `Future` that can
+    /// be executed on the current runtime.
+    ///
+    /// A socket file left at `path` by a previous run is removed before
+    /// binding, if connecting to it is refused. A socket that a server is
+    /// still listening on, or any other kind of file, is left alone, and
+    /// binding fails.
+    /// The socket file is removed once the returned `Future` completes or is
+    /// dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// // Proxied to by nginx with `proxy_pass http://unix:/run/app.sock;`.
+    /// warp::serve(routes).bind_unix("/run/app.sock").await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to the provided path.
+    #[cfg(unix)]
+    pub fn bind_unix(self, path: impl AsRef<Path>) -> impl Future<Output = ()> + 'static {
+        let (incoming, socket) = bind_unix(path.as_ref())
+            .unwrap_or_else(|err| panic!("error binding to {}: {}", path.as_ref().display(), err));
+        let srv = self.serve_incoming(incoming);
+        async move {
+            srv.await;
+            drop(socket);
+        }
+    }
+
+    /// Bind to a Unix Domain Socket at `path`, with a graceful shutdown
+    /// signal.
+    ///
+    /// When the signal completes, the server will start the graceful shutdown
+    /// process, and the socket file is removed once it's done. See
+    /// [`bind_unix`](Server::bind_unix) for how the socket file is managed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if we are unable to bind to the provided path.
+    #[cfg(unix)]
+    pub fn bind_unix_with_graceful_shutdown(
+        self,
+        path: impl AsRef<Path>,
+        signal: impl Future<Output = ()> + Send + 'static,
+    ) -> impl Future<Output = ()> + 'static {
+        let (incoming, socket) = bind_unix(path.as_ref())
+            .unwrap_or_else(|err| panic!("error binding to {}: {}", path.as_ref().display(), err));
+        let srv = self.serve_incoming_with_graceful_shutdown(incoming, signal);
+        async move {
+            srv.await;
+            drop(socket);
+        }
+    }
+
     /// Setup this `Server` with a specific stream of incoming connections.
     ///
     /// This can be used for Unix Domain Sockets, or TLS, etc.
@@ -353,16 +743,21 @@ where
         I::Error: Into<Box<dyn StdError + Send + Sync>>,
     {
         let incoming = incoming.map_ok(crate::transport::LiftIo);
-        let service = into_service!(self.filter);
+        let signal = self.shutdown.on_signal(signal);
         let pipeline = self.pipeline;
+        let protocol = self.protocol;
+        let max_connections = self.max_connections.clone();
+        let service = into_service!(self);
 
         async move {
-            let srv =
-                HyperServer::builder(hyper::server::accept::from_stream(incoming.into_stream()))
-                    .http1_pipeline_flush(pipeline)
-                    .serve(service)
-                    .with_graceful_shutdown(signal)
-                    .await;
+            let incoming = LimitedAccept::new(
+                hyper::server::accept::from_stream(incoming.into_stream()),
+                max_connections,
+            );
+            let srv = hyper_builder(incoming, pipeline, protocol)
+                .serve(service)
+                .with_graceful_shutdown(signal)
+                .await;
 
             if let Err(err) = srv {
                 tracing::error!("server error: {}", err);
@@ -379,10 +774,14 @@ where
         I::Ok: Transport + Send + 'static + Unpin,
         I::Error: Into<Box<dyn StdError + Send + Sync>>,
     {
-        let service = into_service!(self.filter);
+        let max_connections = self.max_connections.clone();
+        let service = into_service!(self);
 
-        let srv = HyperServer::builder(hyper::server::accept::from_stream(incoming.into_stream()))
-            .http1_pipeline_flush(self.pipeline)
+        let incoming = LimitedAccept::new(
+            hyper::server::accept::from_stream(incoming.into_stream()),
+            max_connections,
+        );
+        let srv = hyper_builder(incoming, self.pipeline, self.protocol)
             .serve(service)
             .await;
 
@@ -391,6 +790,288 @@ where
         }
     }
 
+    /// Advertise an alternative service, such as an HTTP/3 endpoint, in an
+    /// `alt-svc` header on every response.
+    ///
+    /// Responses that already have an `alt-svc` header are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// // HTTP/3 is served on UDP port 443, for the next 24 hours.
+    /// warp::serve(routes)
+    ///     .alt_svc("h3=\":443\"; ma=86400")
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't a valid header value.
+    pub fn alt_svc(mut self, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("alt-svc value must be a valid header");
+        self.alt_svc = Some(value);
+        self
+    }
+
+    /// Set a `server` header on every response, such as the name and version
+    /// of the application.
+    ///
+    /// Responses that already have a `server` header are left unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .server_header("myapp/1.2.3")
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` isn't a valid header value.
+    pub fn server_header(mut self, value: &str) -> Self {
+        let value = HeaderValue::from_str(value).expect("server value must be a valid header");
+        self.server_header = Some(value);
+        self
+    }
+
+    /// Apply a function to every response before it's sent.
+    ///
+    /// Unlike wrapping the filter with [`Filter::with`], this also applies to
+    /// the responses of rejections, making it a good place for headers every
+    /// response should have. It runs after any `alt-svc` or `server` header
+    /// is added.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::http::header::{HeaderValue, SERVER, X_CONTENT_TYPE_OPTIONS};
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::path("hello").map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .map_response(|mut res| {
+    ///         let headers = res.headers_mut();
+    ///         headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    ///         headers.remove(SERVER);
+    ///         res
+    ///     })
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn map_response<M>(mut self, func: M) -> Self
+    where
+        M: Fn(Response) -> Response + Send + Sync + 'static,
+    {
+        self.map_response = Some(MapResponse(Arc::new(func)));
+        self
+    }
+
+    /// Reply with `func` to requests that no route matched.
+    ///
+    /// This replaces the plain `404 Not Found` sent when the filter rejects a
+    /// request as not found, and the rejection isn't handled with
+    /// [`Filter::recover`]. `func` is given the method and path of the
+    /// request. The status of its reply is used as it is, so it should
+    /// usually set one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::http::StatusCode;
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::path("hello").map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .not_found(|method, path| {
+    ///         let error = format!("no route for {} {}", method, path);
+    ///         (StatusCode::NOT_FOUND, warp::reply::json(&error))
+    ///     })
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn not_found<N, R>(mut self, func: N) -> Self
+    where
+        N: Fn(&Method, &str) -> R + Send + Sync + 'static,
+        R: Reply,
+    {
+        self.not_found = Some(NotFound(Arc::new(move |method, path| {
+            func(method, path).into_response()
+        })));
+        self
+    }
+
+    /// Limit the number of requests served at the same time.
+    ///
+    /// Once `max` requests are being handled, further requests wait until one
+    /// of them has its response ready, or are answered with a
+    /// `503 Service Unavailable` right away if [`shed_load`] is set. The limit
+    /// is shared by all connections, and by servers created by
+    /// [`Server::bind_all`].
+    ///
+    /// A request stops counting against the limit once its response is ready,
+    /// even if its body, such as a stream, is still being sent.
+    ///
+    /// [`shed_load`]: Server::shed_load
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .max_concurrency(512)
+    ///     .shed_load(true)
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn max_concurrency(mut self, max: usize) -> Self {
+        self.concurrency = Some(Arc::new(Semaphore::new(max)));
+        self
+    }
+
+    /// Set whether requests over the [`max_concurrency`] limit are answered
+    /// with a `503 Service Unavailable`, instead of waiting their turn.
+    ///
+    /// Has no effect without a `max_concurrency` limit.
+    ///
+    /// [`max_concurrency`]: Server::max_concurrency
+    pub fn shed_load(mut self, shed: bool) -> Self {
+        self.shed_load = shed;
+        self
+    }
+
+    /// Limit the number of connections served at the same time.
+    ///
+    /// Once `max` connections are open, the server stops accepting new ones
+    /// until one of them closes. Clients waiting to connect are left in the
+    /// listen backlog of the operating system, rather than each taking up
+    /// memory in the server. Servers created by [`Server::bind_all`] share
+    /// the limit.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .max_connections(10_000)
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn max_connections(mut self, max: usize) -> Self {
+        self.max_connections = Some(Arc::new(Semaphore::new(max)));
+        self
+    }
+
+    /// Decide what happens when accepting a connection fails.
+    ///
+    /// Errors of a single connection, such as one reset before it was
+    /// accepted, are always skipped. For other errors, like running out of
+    /// file descriptors, the server logs the error and waits for a second by
+    /// default. With a handler, it's called with the error instead, and its
+    /// [`AcceptErrorAction`] decides whether to continue accepting, sleep
+    /// first, or stop the server.
+    ///
+    /// This applies to servers bound to an address by warp, and not to
+    /// custom streams of incoming connections.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use warp::{AcceptErrorAction, Filter};
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .on_accept_error(|err| {
+    ///         eprintln!("accept error: {}", err);
+    ///         AcceptErrorAction::Sleep(Duration::from_millis(50))
+    ///     })
+    ///     .run(([0, 0, 0, 0], 3030))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn on_accept_error<E>(mut self, func: E) -> Self
+    where
+        E: Fn(&std::io::Error) -> AcceptErrorAction + Send + Sync + 'static,
+    {
+        self.on_accept_error = Some(OnAcceptError(Arc::new(func)));
+        self
+    }
+
+    /// Sets whether to only accept HTTP/1 connections.
+    ///
+    /// By default, connections can use either HTTP/1 or HTTP/2, detected from
+    /// the start of each connection. Setting this to `false` restores that.
+    pub fn http1_only(mut self, only: bool) -> Self {
+        self.protocol = if only {
+            Protocol::Http1Only
+        } else {
+            Protocol::Auto
+        };
+        self
+    }
+
+    /// Sets whether to only accept HTTP/2 connections.
+    ///
+    /// Without TLS, this is HTTP/2 with prior knowledge (h2c), as used by
+    /// gRPC clients talking to internal services. Setting this to `false`
+    /// restores accepting either HTTP/1 or HTTP/2.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use warp::Filter;
+    ///
+    /// # async fn run() {
+    /// let routes = warp::any().map(|| "Hello, World!");
+    ///
+    /// warp::serve(routes)
+    ///     .http2_only(true)
+    ///     .run(([127, 0, 0, 1], 50051))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn http2_only(mut self, only: bool) -> Self {
+        self.protocol = if only {
+            Protocol::Http2Only
+        } else {
+            Protocol::Auto
+        };
+        self
+    }
+
     // Generally shouldn't be used, as it can slow down non-pipelined responses.
     //
     // It's only real use is to make silly pipeline benchmarks look better.
@@ -572,6 +1253,7 @@ where
         addr: impl Into<SocketAddr> + 'static,
         signal: impl Future<Output = ()> + Send + 'static,
     ) -> (SocketAddr, impl Future<Output = ()> + 'static) {
+        let signal = self.server.shutdown.on_signal(signal);
         let (addr, srv) = bind!(tls: self, addr);
 
         let fut = srv.with_graceful_shutdown(signal).map(|result| {
@@ -594,6 +1276,7 @@ where
         signal: impl Future<Output = ()> + Send + 'static,
     ) -> Result<(SocketAddr, impl Future<Output = ()> + 'static), crate::Error> {
         let addr = addr.into();
+        let signal = self.server.shutdown.on_signal(signal);
         let (addr, srv) = try_bind!(tls: self, &addr).map_err(crate::Error::new)?;
         let srv = srv.with_graceful_shutdown(signal).map(|result| {
             if let Err(err) = result {
@@ -96,9 +96,7 @@ use std::task::{self, Poll};
 use bytes::Bytes;
 #[cfg(feature = "websocket")]
 use futures_channel::mpsc;
-#[cfg(feature = "websocket")]
-use futures_util::StreamExt;
-use futures_util::{future, FutureExt, TryFutureExt};
+use futures_util::{future, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
 use http::{
     header::{HeaderName, HeaderValue},
     Response,
@@ -127,6 +125,37 @@ pub fn request() -> RequestBuilder {
     }
 }
 
+/// Starts a new test `RequestBuilder` from a complete `http::Request`.
+///
+/// Unlike [`request`], this allows anything an `http::Request` can hold, like
+/// a streaming body or extensions, which is useful for driving filters in
+/// benchmarks, property tests, or fuzzers.
+///
+/// # Example
+///
+/// ```
+/// # async fn run() {
+/// use warp::hyper::Body;
+///
+/// let chunks = vec![Ok::<_, std::io::Error>("hello "), Ok("world")];
+/// let req = warp::http::Request::post("/upload")
+///     .body(Body::wrap_stream(futures_util::stream::iter(chunks)))
+///     .unwrap();
+///
+/// let body = warp::test::from_request(req)
+///     .filter(&warp::body::bytes())
+///     .await
+///     .unwrap();
+/// assert_eq!(body, "hello world");
+/// # }
+/// ```
+pub fn from_request(req: Request) -> RequestBuilder {
+    RequestBuilder {
+        remote_addr: None,
+        req,
+    }
+}
+
 /// Starts a new test `WsBuilder`.
 #[cfg(feature = "websocket")]
 pub fn ws() -> WsBuilder {
@@ -238,7 +267,8 @@ impl RequestBuilder {
 
     /// Set the remote address of this request
     ///
-    /// Default is no remote address.
+    /// Default is no remote address, so that
+    /// [`warp::addr::remote`](crate::addr::remote) extracts `None`.
     ///
     /// # Example
     /// ```
@@ -253,6 +283,29 @@ impl RequestBuilder {
     }
 
     /// Add a type to the request's `http::Extensions`.
+    ///
+    /// This stands in for a middleware that would have inserted it, so
+    /// filters using [`warp::ext::get`](crate::ext::get) can be tested alone.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use warp::Filter;
+    ///
+    /// #[derive(Clone)]
+    /// struct User(String);
+    ///
+    /// # async fn run() {
+    /// let route = warp::ext::get::<User>().map(|user: User| user.0);
+    ///
+    /// let name = warp::test::request()
+    ///     .extension(User("sean".into()))
+    ///     .filter(&route)
+    ///     .await
+    ///     .unwrap();
+    /// assert_eq!(name, "sean");
+    /// # }
+    /// ```
     pub fn extension<T>(mut self, ext: T) -> Self
     where
         T: Send + Sync + 'static,
@@ -371,6 +424,61 @@ impl RequestBuilder {
         F: Filter + 'static,
         F::Extract: Reply + Send,
         F::Error: IsReject + Send,
+    {
+        self.reply_with(f, hyper::body::to_bytes).await
+    }
+
+    /// Returns `Response` provided by applying the `Filter`, with the first
+    /// `n` chunks of its body.
+    ///
+    /// The rest of the body is dropped, as when the client disconnects, so
+    /// this works for bodies that never end, such as server-sent events. Each
+    /// event is its own chunk.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::convert::Infallible;
+    /// use std::time::Duration;
+    /// use futures_util::StreamExt;
+    /// use warp::Filter;
+    /// use warp::sse::Event;
+    ///
+    /// # async fn run() {
+    /// let route = warp::path("ticks").map(|| {
+    ///     let ticks = tokio_stream::wrappers::IntervalStream::new(
+    ///         tokio::time::interval(Duration::from_millis(10)),
+    ///     )
+    ///     .map(|_| Ok::<_, Infallible>(Event::default().data("tick")));
+    ///     warp::sse::reply(warp::sse::keep_alive().stream(ticks))
+    /// });
+    ///
+    /// let res = warp::test::request()
+    ///     .path("/ticks")
+    ///     .reply_take(&route, 2)
+    ///     .await;
+    /// assert_eq!(res.body().len(), 2);
+    /// assert_eq!(res.body()[0], "data:tick\n\n");
+    /// # }
+    /// ```
+    pub async fn reply_take<F>(self, f: &F, n: usize) -> Response<Vec<Bytes>>
+    where
+        F: Filter + 'static,
+        F::Extract: Reply + Send,
+        F::Error: IsReject + Send,
+    {
+        self.reply_with(f, move |body| body.take(n).try_collect())
+            .await
+    }
+
+    // Applies the filter, and reads the body of its response with `read`.
+    async fn reply_with<F, R, Fut, T>(self, f: &F, read: R) -> Response<T>
+    where
+        F: Filter + 'static,
+        F::Extract: Reply + Send,
+        F::Error: IsReject + Send,
+        R: FnOnce(hyper::Body) -> Fut,
+        Fut: Future<Output = Result<T, hyper::Error>>,
     {
         // TODO: de-duplicate this and apply_filter()
         assert!(!route::is_set(), "nested test filter calls");
@@ -386,7 +494,7 @@ impl RequestBuilder {
                     }
                 };
                 let (parts, body) = res.into_parts();
-                hyper::body::to_bytes(body).map_ok(|chunk| Response::from_parts(parts, chunk))
+                read(body).map_ok(|body| Response::from_parts(parts, body))
             }),
         );
 
@@ -535,7 +643,7 @@ impl WsBuilder {
                 }
             };
             let ws = crate::ws::WebSocket::from_raw_socket(
-                upgraded,
+                crate::upgrade::Upgraded::new(upgraded),
                 protocol::Role::Client,
                 Default::default(),
             )
@@ -5,18 +5,45 @@ use std::io::{self, BufReader, Cursor, Read};
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 use futures_util::ready;
 use hyper::server::accept::Accept;
 use hyper::server::conn::{AddrIncoming, AddrStream};
+use tokio_rustls::rustls::pki_types::CertificateDer;
 use tokio_rustls::rustls::server::WebPkiClientVerifier;
 use tokio_rustls::rustls::{Error as TlsError, RootCertStore, ServerConfig};
 
 use crate::transport::Transport;
 
+// The certificate chain of the client of a connection, as sent during its
+// handshake, and added to the extensions of each of its requests.
+#[derive(Clone)]
+pub(crate) struct ClientCertificates(pub(crate) Arc<[CertificateDer<'static>]>);
+
+// Shared between a `TlsStream` and the service of its connection, which is
+// made before the handshake is done.
+#[derive(Clone, Default)]
+pub(crate) struct PeerCertificates(Arc<Mutex<Option<ClientCertificates>>>);
+
+impl PeerCertificates {
+    fn set(&self, certs: Option<&[CertificateDer<'static>]>) {
+        if let Some(certs) = certs {
+            *self.0.lock().unwrap_or_else(|e| e.into_inner()) =
+                Some(ClientCertificates(certs.into()));
+        }
+    }
+
+    pub(crate) fn add_to(&self, req: &mut crate::Request) {
+        let certs = self.0.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(certs) = certs {
+            req.extensions_mut().insert(certs);
+        }
+    }
+}
+
 /// Represents errors that can occur building the TlsConfig
 #[derive(Debug)]
 pub(crate) enum TlsConfigError {
@@ -284,6 +311,10 @@ impl Transport for TlsStream {
     fn remote_addr(&self) -> Option<SocketAddr> {
         Some(self.remote_addr)
     }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        Some(self.peer_certs.clone())
+    }
 }
 
 enum State {
@@ -297,6 +328,7 @@ enum State {
 pub(crate) struct TlsStream {
     state: State,
     remote_addr: SocketAddr,
+    peer_certs: PeerCertificates,
 }
 
 impl TlsStream {
@@ -306,6 +338,7 @@ impl TlsStream {
         TlsStream {
             state: State::Handshaking(accept),
             remote_addr,
+            peer_certs: PeerCertificates::default(),
         }
     }
 }
@@ -320,6 +353,7 @@ impl AsyncRead for TlsStream {
         match pin.state {
             State::Handshaking(ref mut accept) => match ready!(Pin::new(accept).poll(cx)) {
                 Ok(mut stream) => {
+                    pin.peer_certs.set(stream.get_ref().1.peer_certificates());
                     let result = Pin::new(&mut stream).poll_read(cx, buf);
                     pin.state = State::Streaming(stream);
                     result
@@ -341,6 +375,7 @@ impl AsyncWrite for TlsStream {
         match pin.state {
             State::Handshaking(ref mut accept) => match ready!(Pin::new(accept).poll(cx)) {
                 Ok(mut stream) => {
+                    pin.peer_certs.set(stream.get_ref().1.peer_certificates());
                     let result = Pin::new(&mut stream).poll_write(cx, buf);
                     pin.state = State::Streaming(stream);
                     result
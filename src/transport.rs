@@ -1,13 +1,28 @@
+use std::future::Future;
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use futures_util::ready;
+use hyper::server::accept::Accept;
 use hyper::server::conn::AddrStream;
+use pin_project::pin_project;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Sleep;
+use tokio_util::sync::PollSemaphore;
+
+use crate::server::AcceptErrorAction;
 
 pub trait Transport: AsyncRead + AsyncWrite {
     fn remote_addr(&self) -> Option<SocketAddr>;
+
+    #[cfg(feature = "tls")]
+    fn peer_certificates(&self) -> Option<crate::tls::PeerCertificates> {
+        None
+    }
 }
 
 impl Transport for AddrStream {
@@ -51,3 +66,213 @@ impl<T: AsyncRead + AsyncWrite + Unpin> Transport for LiftIo<T> {
         None
     }
 }
+
+// Stops accepting connections while `limit` has no permits left, holding a
+// permit for as long as each accepted connection is open.
+#[pin_project]
+pub(crate) struct LimitedAccept<A> {
+    #[pin]
+    inner: A,
+    limit: Option<PollSemaphore>,
+    permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<A> LimitedAccept<A> {
+    pub(crate) fn new(inner: A, limit: Option<Arc<Semaphore>>) -> Self {
+        LimitedAccept {
+            inner,
+            limit: limit.map(PollSemaphore::new),
+            permit: None,
+        }
+    }
+}
+
+impl<A: Accept> Accept for LimitedAccept<A> {
+    type Conn = Limited<A::Conn>;
+    type Error = A::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let this = self.project();
+        if let Some(limit) = this.limit {
+            if this.permit.is_none() {
+                if limit.available_permits() == 0 {
+                    tracing::trace!("max connections reached, pausing accept");
+                }
+                match ready!(limit.poll_acquire(cx)) {
+                    Some(permit) => *this.permit = Some(permit),
+                    None => return Poll::Ready(None),
+                }
+            }
+        }
+
+        let permit = this.permit;
+        let conn = ready!(this.inner.poll_accept(cx));
+        Poll::Ready(conn.map(|result| {
+            result.map(|io| Limited {
+                io,
+                _permit: permit.take(),
+            })
+        }))
+    }
+}
+
+pub(crate) type OnAcceptErrorFn = dyn Fn(&io::Error) -> AcceptErrorAction + Send + Sync;
+
+// Lets a `Server::on_accept_error` handler decide what happens after an
+// accept error. Without a handler, errors are passed through.
+#[pin_project]
+pub(crate) struct AcceptErrors<A> {
+    #[pin]
+    inner: A,
+    handler: Option<Arc<OnAcceptErrorFn>>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl<A> AcceptErrors<A> {
+    pub(crate) fn new(inner: A, handler: Option<Arc<OnAcceptErrorFn>>) -> Self {
+        AcceptErrors {
+            inner,
+            handler,
+            sleep: None,
+        }
+    }
+}
+
+impl<A: Accept<Error = io::Error>> Accept for AcceptErrors<A> {
+    type Conn = A::Conn;
+    type Error = io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let mut this = self.project();
+        loop {
+            if let Some(sleep) = this.sleep {
+                ready!(sleep.as_mut().poll(cx));
+                *this.sleep = None;
+            }
+
+            let err = match ready!(this.inner.as_mut().poll_accept(cx)) {
+                Some(Err(err)) => err,
+                other => return Poll::Ready(other),
+            };
+            let handler = match this.handler {
+                Some(handler) => handler,
+                None => return Poll::Ready(Some(Err(err))),
+            };
+            match handler(&err) {
+                AcceptErrorAction::Continue => {
+                    tracing::debug!("accept error, continuing: {}", err);
+                }
+                AcceptErrorAction::Sleep(duration) => {
+                    tracing::debug!("accept error, sleeping {:?}: {}", duration, err);
+                    *this.sleep = Some(Box::pin(tokio::time::sleep(duration)));
+                }
+                AcceptErrorAction::Stop => {
+                    tracing::debug!("accept error, stopping: {}", err);
+                    return Poll::Ready(None);
+                }
+            }
+        }
+    }
+}
+
+#[pin_project]
+pub(crate) struct Limited<T> {
+    #[pin]
+    io: T,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl<T: AsyncRead> AsyncRead for Limited<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().io.poll_read(cx, buf)
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Limited<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.project().io.poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.project().io.poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().io.poll_shutdown(cx)
+    }
+}
+
+impl<T: Transport> Transport for Limited<T> {
+    fn remote_addr(&self) -> Option<SocketAddr> {
+        self.io.remote_addr()
+    }
+
+    #[cfg(feature = "tls")]
+    fn peer_certificates(&self) -> Option<crate::tls::PeerCertificates> {
+        self.io.peer_certificates()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::future::poll_fn;
+    use std::time::Duration;
+
+    fn failing(errors: usize) -> impl Accept<Conn = (), Error = io::Error> {
+        let results = (0..errors)
+            .map(|_| Err(io::Error::new(io::ErrorKind::Other, "too many open files")))
+            .chain(Some(Ok(())));
+        hyper::server::accept::from_stream(futures_util::stream::iter(results))
+    }
+
+    async fn accept<A: Accept + Unpin>(accept: &mut A) -> Option<Result<A::Conn, A::Error>> {
+        poll_fn(|cx| Pin::new(&mut *accept).poll_accept(cx)).await
+    }
+
+    #[tokio::test]
+    async fn accept_errors() {
+        let mut passed = AcceptErrors::new(failing(1), None);
+        assert!(matches!(accept(&mut passed).await, Some(Err(_))));
+
+        let handler = Arc::new(|_: &io::Error| AcceptErrorAction::Continue);
+        let mut continued = AcceptErrors::new(failing(2), Some(handler));
+        assert!(matches!(accept(&mut continued).await, Some(Ok(()))));
+
+        let handler = Arc::new(|_: &io::Error| AcceptErrorAction::Sleep(Duration::from_millis(10)));
+        let mut slept = AcceptErrors::new(failing(2), Some(handler));
+        let start = tokio::time::Instant::now();
+        assert!(matches!(accept(&mut slept).await, Some(Ok(()))));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+
+        let handler = Arc::new(|_: &io::Error| AcceptErrorAction::Stop);
+        let mut stopped = AcceptErrors::new(failing(1), Some(handler));
+        assert!(accept(&mut stopped).await.is_none());
+    }
+}
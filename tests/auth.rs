@@ -0,0 +1,150 @@
+#![deny(warnings)]
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_derive::Deserialize;
+use warp::auth::{JwtErrorKind, JwtHeader, JwtVerifier};
+use warp::Filter;
+
+// Accepts signatures of "signed:<message>", for HS256 only.
+struct TestVerifier;
+
+impl JwtVerifier for TestVerifier {
+    fn verify(&self, header: &JwtHeader, message: &[u8], signature: &[u8]) -> bool {
+        header.alg() == "HS256" && signature == [&b"signed:"[..], message].concat()
+    }
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+fn base64url(input: &[u8]) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::new();
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..=chunk.len() {
+            out.push(CHARS[(bits >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+fn token(alg: &str, claims: &str, valid: bool) -> String {
+    let header = format!("{{\"alg\":\"{}\",\"typ\":\"JWT\"}}", alg);
+    let message = format!(
+        "{}.{}",
+        base64url(header.as_bytes()),
+        base64url(claims.as_bytes())
+    );
+    let signature = if valid {
+        format!("signed:{}", message)
+    } else {
+        "forged".to_owned()
+    };
+    format!("{}.{}", message, base64url(signature.as_bytes()))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+async fn reject_kind(route: &warp::filters::BoxedFilter<(String,)>, auth: &str) -> JwtErrorKind {
+    let err = warp::test::request()
+        .header("authorization", auth)
+        .filter(route)
+        .await
+        .unwrap_err();
+    err.find::<warp::auth::InvalidJwt>().unwrap().kind()
+}
+
+#[tokio::test]
+async fn jwt() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::auth::jwt(Arc::new(TestVerifier))
+        .map(|claims: Claims| claims.sub)
+        .boxed();
+
+    let exp = now() + 60;
+    let valid = token(
+        "HS256",
+        &format!("{{\"sub\":\"sean\",\"exp\":{}}}", exp),
+        true,
+    );
+    let res = warp::test::request()
+        .header("authorization", format!("Bearer {}", valid))
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "sean");
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 401);
+
+    let forged = token("HS256", "{\"sub\":\"sean\"}", false);
+    assert_eq!(
+        reject_kind(&route, &format!("Bearer {}", forged)).await,
+        JwtErrorKind::InvalidSignature
+    );
+    let none = token("none", "{\"sub\":\"sean\"}", true);
+    assert_eq!(
+        reject_kind(&route, &format!("bearer {}", none)).await,
+        JwtErrorKind::InvalidSignature
+    );
+
+    let expired = token(
+        "HS256",
+        &format!("{{\"sub\":\"sean\",\"exp\":{}}}", now() - 60),
+        true,
+    );
+    assert_eq!(
+        reject_kind(&route, &format!("Bearer {}", expired)).await,
+        JwtErrorKind::Expired
+    );
+    let early = token(
+        "HS256",
+        &format!("{{\"sub\":\"sean\",\"nbf\":{}}}", now() + 60),
+        true,
+    );
+    assert_eq!(
+        reject_kind(&route, &format!("Bearer {}", early)).await,
+        JwtErrorKind::NotYetValid
+    );
+
+    let no_sub = token("HS256", "{\"name\":\"sean\"}", true);
+    assert_eq!(
+        reject_kind(&route, &format!("Bearer {}", no_sub)).await,
+        JwtErrorKind::Malformed
+    );
+    assert_eq!(
+        reject_kind(&route, "Bearer not.a-token").await,
+        JwtErrorKind::Malformed
+    );
+
+    // The same signature, encoded with non-zero trailing bits.
+    let mut non_canonical = (1..4)
+        .map(|n| token("HS256", &format!("{{\"sub\":\"{}\"}}", "s".repeat(n)), true))
+        .find(|token| token.rsplit('.').next().unwrap().len() % 4 != 0)
+        .unwrap();
+    let last = non_canonical.pop().unwrap();
+    non_canonical.push((last as u8 + 1) as char);
+    assert_eq!(
+        reject_kind(&route, &format!("Bearer {}", non_canonical)).await,
+        JwtErrorKind::Malformed
+    );
+    assert_eq!(
+        reject_kind(&route, &format!("Basic {}", valid)).await,
+        JwtErrorKind::Missing
+    );
+}
@@ -106,6 +106,163 @@ async fn json_invalid() {
     assert_eq!(&res.body()[..prefix.len()], prefix);
 }
 
+#[tokio::test]
+async fn json_streaming() {
+    let _ = pretty_env_logger::try_init();
+
+    let json = warp::body::json_streaming::<Vec<i32>>(16);
+
+    let vec = warp::test::request()
+        .body("[1, 2, 3]")
+        .filter(&json)
+        .await
+        .unwrap();
+    assert_eq!(vec, &[1, 2, 3]);
+
+    let json = json.map(|vec| warp::reply::json(&vec));
+
+    let res = warp::test::request()
+        .body("[1, 2, 3, 4, 5, 6, 7]")
+        .reply(&json)
+        .await;
+    assert_eq!(res.status(), 413, "known length over limit");
+
+    // Without a content-length, the limit applies as chunks arrive.
+    let chunked = |chunks: Vec<&'static str>| {
+        let chunks = chunks.into_iter().map(Ok::<_, std::io::Error>);
+        let body = warp::hyper::Body::wrap_stream(futures_util::stream::iter(chunks));
+        warp::test::from_request(warp::http::Request::post("/").body(body).unwrap())
+    };
+
+    let res = chunked(vec!["[1, 2,", " 3]"]).reply(&json).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "[1,2,3]");
+
+    let res = chunked(vec!["[1, 2, 3,", " 4, 5, 6,", " 7]"])
+        .reply(&json)
+        .await;
+    assert_eq!(res.status(), 413, "chunked over limit");
+}
+
+#[tokio::test]
+async fn buffered() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::buffered(16)
+        .and(warp::body::json())
+        .map(|raw: bytes::Bytes, vec: Vec<i32>| format!("{:?} {:?}", raw, vec));
+
+    let res = warp::test::request().body("[1, 2, 3]").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "b\"[1, 2, 3]\" [1, 2, 3]");
+
+    let res = warp::test::request()
+        .body("[1, 2, 3, 4, 5, 6, 7]")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 413);
+}
+
+#[tokio::test]
+async fn bytes_with_limit() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::bytes_with_limit(8);
+
+    let (body, truncated) = warp::test::request()
+        .body("12345678")
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(body, "12345678");
+    assert!(!truncated);
+
+    let (body, truncated) = warp::test::request()
+        .body("123456789")
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(body, "12345678");
+    assert!(truncated);
+
+    // Without a content-length, reading stops within a chunk.
+    let chunks = vec![Ok::<_, std::io::Error>("1234"), Ok("5678"), Ok("9")];
+    let body = warp::hyper::Body::wrap_stream(futures_util::stream::iter(chunks));
+    let (body, truncated) =
+        warp::test::from_request(warp::http::Request::post("/").body(body).unwrap())
+            .filter(&route)
+            .await
+            .unwrap();
+    assert_eq!(body, "12345678");
+    assert!(truncated);
+}
+
+#[tokio::test]
+async fn accept_content_types() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::accept_content_types(&["image/png", "image/jpeg"])
+        .and(warp::body::bytes())
+        .map(|body: bytes::Bytes| format!("{} bytes", body.len()));
+
+    let res = warp::test::request()
+        .header("content-type", "image/png")
+        .body("png")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "3 bytes");
+
+    let res = warp::test::request()
+        .header("content-type", "Image/JPEG; foo=bar")
+        .body("jpeg")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200, "ignores case and parameters");
+
+    let res = warp::test::request()
+        .header("content-type", "image/gif")
+        .body("gif")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 415);
+
+    let res = warp::test::request().body("png").reply(&route).await;
+    assert_eq!(res.status(), 415, "missing content-type");
+}
+
+#[tokio::test]
+async fn json_value() {
+    let _ = pretty_env_logger::try_init();
+
+    let json = warp::body::json_value(32);
+
+    let value = warp::test::request()
+        .body(r#"{"a": [1, "two"]}"#)
+        .filter(&json)
+        .await
+        .unwrap();
+    assert_eq!(value, serde_json::json!({ "a": [1, "two"] }));
+
+    let json = json.map(|_| warp::reply());
+
+    let res = warp::test::request().body("lol#wat").reply(&json).await;
+    assert_eq!(res.status(), 400);
+
+    let res = warp::test::request()
+        .header("content-type", "text/xml")
+        .body("{}")
+        .reply(&json)
+        .await;
+    assert_eq!(res.status(), 415);
+
+    let res = warp::test::request()
+        .body(r#"{"a": [1, "two", "three", "four"]}"#)
+        .reply(&json)
+        .await;
+    assert_eq!(res.status(), 413);
+}
+
 #[test]
 fn json_size_of() {
     let json = warp::body::json::<Vec<i32>>();
@@ -128,6 +285,35 @@ async fn form() {
     assert_eq!(vec, expected);
 }
 
+#[tokio::test]
+async fn form_pairs() {
+    let _ = pretty_env_logger::try_init();
+
+    let form =
+        warp::body::form_pairs(32).map(|pairs: Vec<(String, String)>| format!("{:?}", pairs));
+
+    let res = warp::test::request()
+        .header("content-type", "application/x-www-form-urlencoded")
+        .body("a=1&b=2&a=3%20x")
+        .reply(&form)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), r#"[("a", "1"), ("b", "2"), ("a", "3 x")]"#);
+
+    let res = warp::test::request()
+        .header("content-type", "text/plain")
+        .body("a=1")
+        .reply(&form)
+        .await;
+    assert_eq!(res.status(), 415);
+
+    let res = warp::test::request()
+        .body("a=".repeat(20))
+        .reply(&form)
+        .await;
+    assert_eq!(res.status(), 413);
+}
+
 #[tokio::test]
 async fn form_rejects_bad_content_type() {
     let _ = pretty_env_logger::try_init();
@@ -200,3 +386,235 @@ async fn stream() {
     assert_eq!(bufs.len(), 1);
     assert_eq!(bufs[0].chunk(), b"foo=bar");
 }
+
+#[cfg(feature = "compression-gzip")]
+async fn gzip(data: &[u8]) -> Vec<u8> {
+    use async_compression::tokio::bufread::GzipEncoder;
+    use tokio::io::AsyncReadExt;
+
+    let mut compressed = Vec::new();
+    GzipEncoder::new(data)
+        .read_to_end(&mut compressed)
+        .await
+        .unwrap();
+    compressed
+}
+
+#[cfg(feature = "compression-gzip")]
+#[tokio::test]
+async fn gunzip() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::gunzip(1024, 100);
+
+    let body = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body(gzip(b"hello world").await)
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(body, "hello world");
+
+    let route = route.map(|_| warp::reply());
+
+    let res = warp::test::request()
+        .body(gzip(b"hello world").await)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 415, "missing content-encoding");
+
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body("not gzip")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 400, "invalid gzip data");
+}
+
+#[cfg(feature = "compression-gzip")]
+#[tokio::test]
+async fn gunzip_limits() {
+    let _ = pretty_env_logger::try_init();
+
+    let zeros = gzip(&[0; 64 * 1024]).await;
+
+    let too_large = warp::body::gunzip(16 * 1024, u32::MAX).map(|_| warp::reply());
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body(&zeros)
+        .reply(&too_large)
+        .await;
+    assert_eq!(res.status(), 413);
+
+    let bad_ratio = warp::body::gunzip(u64::MAX, 10).map(|_| warp::reply());
+    let res = warp::test::request()
+        .header("content-encoding", "gzip")
+        .body(&zeros)
+        .reply(&bad_ratio)
+        .await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(
+        res.body(),
+        "Request body decompress error: compression ratio is over the limit"
+    );
+}
+
+#[tokio::test]
+async fn read_timeout() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::read_timeout(std::time::Duration::from_millis(50))
+        .and(warp::body::bytes())
+        .map(|bytes: bytes::Bytes| format!("{} bytes", bytes.len()));
+
+    // a complete body isn't affected
+    let res = warp::test::request().body("hello").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "5 bytes");
+
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    // the body stops arriving partway
+    let mut stream = tokio::net::TcpStream::connect(addr).await.expect("connect");
+    stream
+        .write_all(b"POST / HTTP/1.1\r\nhost: localhost\r\ncontent-length: 10\r\n\r\nhel")
+        .await
+        .expect("write request");
+
+    let mut buf = [0; 12];
+    stream.read_exact(&mut buf).await.expect("read response");
+    assert_eq!(&buf, b"HTTP/1.1 408");
+}
+
+#[tokio::test]
+async fn json_stream() {
+    use futures_util::{Stream, TryStreamExt};
+    use warp::body::BodyDeserializeError;
+
+    let _ = pretty_env_logger::try_init();
+
+    async fn collect(
+        values: impl Stream<Item = Result<Vec<String>, BodyDeserializeError>>,
+    ) -> String {
+        match values.try_collect::<Vec<_>>().await {
+            Ok(values) => format!("{:?}", values),
+            Err(err) => format!("error: {}", err),
+        }
+    }
+
+    let route = warp::body::json_stream(16).then(collect);
+
+    // Chunks split values apart, and strings hold `,` and `]`.
+    let chunked = |content_type: &str, chunks: Vec<&'static str>| {
+        let chunks = chunks.into_iter().map(Ok::<_, std::io::Error>);
+        let body = warp::hyper::Body::wrap_stream(futures_util::stream::iter(chunks));
+        let req = warp::http::Request::post("/")
+            .header("content-type", content_type)
+            .body(body)
+            .unwrap();
+        warp::test::from_request(req)
+    };
+
+    let res = chunked(
+        "application/json",
+        vec![" [[\"a\", \"b", "]\"], [", "], [\"\\\"c,\"]\n", "] "],
+    )
+    .reply(&route)
+    .await;
+    assert_eq!(res.body(), r#"[["a", "b]"], [], ["\"c,"]]"#);
+
+    let res = chunked("application/json", vec!["[]"]).reply(&route).await;
+    assert_eq!(res.body(), "[]");
+
+    let res = chunked(
+        "application/x-ndjson",
+        vec!["[\"a\"]\n\n[\"b", "\"]\r\n[\"c\"]"],
+    )
+    .reply(&route)
+    .await;
+    assert_eq!(res.body(), r#"[["a"], ["b"], ["c"]]"#);
+
+    let res = chunked("application/json", vec!["[[\"a\"], [1]]"])
+        .reply(&route)
+        .await;
+    assert!(res
+        .body()
+        .starts_with(b"error: Request body deserialize error"));
+
+    let res = chunked("application/json", vec!["[[\"a\"], "])
+        .reply(&route)
+        .await;
+    assert_eq!(
+        res.body(),
+        "error: Request body deserialize error: unexpected end of the JSON array"
+    );
+
+    // Values past the limit end the stream, without buffering the rest.
+    let res = chunked(
+        "application/x-ndjson",
+        vec!["[\"a\"]\n[\"", "0123456789", "0123456789", "0123456789"],
+    )
+    .reply(&route)
+    .await;
+    assert_eq!(
+        res.body(),
+        "error: Request body deserialize error: a value is larger than the limit"
+    );
+
+    let res = chunked(
+        "application/json",
+        vec!["[[\"0123456789\", \"0123456789\"]]"],
+    )
+    .reply(&route)
+    .await;
+    assert_eq!(
+        res.body(),
+        "error: Request body deserialize error: a value is larger than the limit"
+    );
+
+    let res = chunked("text/plain", vec!["[]"]).reply(&route).await;
+    assert_eq!(res.status(), 415);
+}
+
+#[tokio::test]
+async fn text() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::body::text();
+
+    let req = warp::test::request().body("héllo");
+    assert_eq!(req.filter(&route).await.unwrap(), "héllo");
+
+    let req = warp::test::request()
+        .header("content-type", "text/plain; charset=ISO-8859-1")
+        .body(&b"h\xE9llo"[..]);
+    assert_eq!(req.filter(&route).await.unwrap(), "héllo");
+
+    let req = warp::test::request()
+        .header("content-type", "text/plain; charset=utf-16")
+        .body(&b"\xFF\xFEh\x00\xE9\x00"[..]);
+    assert_eq!(req.filter(&route).await.unwrap(), "hé");
+
+    let req = warp::test::request()
+        .header("content-type", "text/plain; charset=utf-16be")
+        .body(&b"\x00h\x00\xE9"[..]);
+    assert_eq!(req.filter(&route).await.unwrap(), "hé");
+
+    let res = warp::test::request()
+        .header("content-type", "text/plain; charset=utf-8")
+        .body(&b"h\xE9llo"[..])
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(res.body(), "Request body is not valid in its charset");
+
+    let res = warp::test::request()
+        .header("content-type", "text/plain; charset=klingon")
+        .body("hello")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 415);
+}
@@ -0,0 +1,174 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn versioned() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("app")
+        .map(|| "shell")
+        .with(warp::cache::versioned("abc123"))
+        .or(warp::path("other").map(|| "other"));
+
+    let res = warp::test::request()
+        .path("/app")
+        .header("if-none-match", "\"abc123\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 304);
+    assert_eq!(res.headers()["etag"], "\"abc123\"");
+    assert_eq!(res.body(), "");
+
+    // Only replies of the wrapped filter are replaced.
+    let res = warp::test::request()
+        .path("/other")
+        .header("if-none-match", "\"abc123\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "other");
+
+    let res = warp::test::request()
+        .path("/missing")
+        .header("if-none-match", "\"abc123\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn versioned_stale() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("app")
+        .map(|| "shell")
+        .with(warp::cache::versioned("abc123"));
+
+    let res = warp::test::request()
+        .path("/app")
+        .header("if-none-match", "\"old\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["etag"], "\"abc123\"");
+    assert_eq!(res.body(), "shell");
+
+    let res = warp::test::request().path("/app").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["etag"], "\"abc123\"");
+
+    // Only safe methods are short-circuited.
+    let res = warp::test::request()
+        .method("POST")
+        .path("/app")
+        .header("if-none-match", "\"abc123\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "shell");
+
+    // Rejections don't get an ETag.
+    let res = warp::test::request().path("/other").reply(&route).await;
+    assert_eq!(res.status(), 404);
+    assert!(!res.headers().contains_key("etag"));
+}
+
+#[test]
+#[should_panic(expected = "invalid cache version")]
+fn versioned_invalid() {
+    warp::cache::versioned("not\"valid");
+}
+
+#[tokio::test]
+async fn validators() {
+    let _ = pretty_env_logger::try_init();
+
+    let validators = warp::test::request()
+        .header("if-match", "\"a\", \"b\"")
+        .header("if-none-match", "*")
+        .header("if-modified-since", "Sun, 06 Nov 1994 08:49:37 GMT")
+        .header("if-range", "\"a\"")
+        .header("range", "bytes=0-9")
+        .filter(&warp::cache::validators())
+        .await
+        .unwrap();
+
+    assert!(!validators
+        .if_match()
+        .unwrap()
+        .precondition_passes(&"\"c\"".parse().unwrap()));
+    assert!(!validators
+        .if_none_match()
+        .unwrap()
+        .precondition_passes(&"\"c\"".parse().unwrap()));
+    let since = std::time::UNIX_EPOCH + std::time::Duration::from_secs(784_111_777);
+    assert!(!validators.if_modified_since().unwrap().is_modified(since));
+    assert!(validators.if_unmodified_since().is_none());
+    assert!(validators.if_range().is_some());
+    assert_eq!(
+        validators.range().unwrap().iter().collect::<Vec<_>>(),
+        [(std::ops::Bound::Included(0), std::ops::Bound::Included(9))]
+    );
+
+    let validators = warp::test::request()
+        .header("if-modified-since", "not a date")
+        .filter(&warp::cache::validators())
+        .await
+        .unwrap();
+    assert!(validators.if_modified_since().is_none());
+    assert!(validators.range().is_none());
+}
+
+#[tokio::test]
+async fn conditional() {
+    use warp::cache::CacheValidators;
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::cache::validators().map(|validators: CacheValidators| {
+        let etag = "\"v42\"".parse().unwrap();
+        validators.conditional(etag, "the resource")
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["etag"], "\"v42\"");
+    assert_eq!(res.body(), "the resource");
+
+    let res = warp::test::request()
+        .header("if-none-match", "\"v41\", W/\"v42\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 304);
+    assert_eq!(res.headers()["etag"], "\"v42\"");
+    assert_eq!(res.body(), "");
+
+    let res = warp::test::request()
+        .header("if-none-match", "\"v41\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .header("if-match", "\"v41\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 412);
+
+    let res = warp::test::request()
+        .header("if-match", "\"v42\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn with_etag() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| warp::reply::with_etag("hi", "W/\"v1\"".parse().unwrap()));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.headers()["etag"], "W/\"v1\"");
+    assert_eq!(res.body(), "hi");
+}
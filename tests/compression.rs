@@ -0,0 +1,83 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn gzip() {
+    let _ = pretty_env_logger::try_init();
+
+    let body = "hello, compressed world! ".repeat(20);
+    let route = warp::path("big")
+        .map({
+            let body = body.clone();
+            move || body.clone()
+        })
+        .or(warp::path("small").map(|| "tiny"))
+        .or(warp::path("encoded")
+            .map(|| warp::reply::with_header("already", "content-encoding", "identity")))
+        .with(warp::compression::gzip().min_size(64));
+
+    let res = warp::test::request()
+        .path("/big")
+        .header("accept-encoding", "br;q=0.5, gzip")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-encoding"], "gzip");
+    assert_eq!(res.headers()["vary"], "accept-encoding");
+    assert!(!res.headers().contains_key("content-length"));
+    assert_eq!(&res.body()[..2], b"\x1f\x8b");
+
+    // not accepted
+    let res = warp::test::request()
+        .path("/big")
+        .header("accept-encoding", "br, gzip;q=0")
+        .reply(&route)
+        .await;
+    assert!(!res.headers().contains_key("content-encoding"));
+    assert_eq!(res.headers()["vary"], "accept-encoding");
+    assert_eq!(res.body(), body.as_str());
+
+    let res = warp::test::request().path("/big").reply(&route).await;
+    assert!(!res.headers().contains_key("content-encoding"));
+
+    // below min_size
+    let res = warp::test::request()
+        .path("/small")
+        .header("accept-encoding", "gzip")
+        .reply(&route)
+        .await;
+    assert!(!res.headers().contains_key("content-encoding"));
+    assert_eq!(res.headers()["vary"], "accept-encoding");
+    assert_eq!(res.body(), "tiny");
+
+    // already encoded
+    let res = warp::test::request()
+        .path("/encoded")
+        .header("accept-encoding", "*")
+        .reply(&route)
+        .await;
+    assert_eq!(res.headers()["content-encoding"], "identity");
+    assert!(!res.headers().contains_key("vary"));
+    assert_eq!(res.body(), "already");
+}
+
+#[tokio::test]
+async fn brotli() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(|| "hello, compressed world! ".repeat(20))
+        .with(warp::compression::brotli());
+
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip, br")
+        .reply(&route)
+        .await;
+    assert_eq!(res.headers()["content-encoding"], "br");
+
+    let res = warp::test::request()
+        .header("accept-encoding", "gzip")
+        .reply(&route)
+        .await;
+    assert!(!res.headers().contains_key("content-encoding"));
+}
@@ -1,4 +1,5 @@
 #![deny(warnings)]
+use warp::Filter;
 
 #[tokio::test]
 async fn cookie() {
@@ -48,3 +49,84 @@ async fn missing() {
     assert_eq!(res.status(), 400);
     assert_eq!(res.body(), "Missing request cookie \"foo\"");
 }
+
+#[tokio::test]
+async fn deserialize() {
+    use serde_derive::Deserialize;
+
+    let _ = pretty_env_logger::try_init();
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Prefs {
+        theme: String,
+        #[serde(default)]
+        page_size: Option<u32>,
+    }
+
+    let cookies = warp::cookie::deserialize::<Prefs>();
+
+    let req = warp::test::request().header("cookie", "theme=dark; page_size=50; other=1");
+    assert_eq!(
+        req.filter(&cookies).await.unwrap(),
+        Prefs {
+            theme: "dark".into(),
+            page_size: Some(50),
+        }
+    );
+
+    let req = warp::test::request().header("cookie", "theme=light");
+    assert_eq!(req.filter(&cookies).await.unwrap().page_size, None);
+
+    let cookies = cookies.map(|_| warp::reply());
+
+    let req = warp::test::request().header("cookie", "theme=dark; page_size=lots");
+    assert_eq!(req.reply(&cookies).await.status(), 400);
+
+    let req = warp::test::request();
+    assert_eq!(req.reply(&cookies).await.status(), 400, "missing required");
+}
+
+#[tokio::test]
+async fn round_trip() {
+    use warp::Reply;
+
+    let _ = pretty_env_logger::try_init();
+
+    let res = warp::reply::with_cookie(warp::reply(), warp::reply::Cookie::new("n", "a b;%"))
+        .into_response();
+    let set_cookie = res.headers()["set-cookie"].to_str().unwrap();
+    let pair = set_cookie.split(';').next().unwrap().to_owned();
+    assert_eq!(pair, "n=a%20b%3B%25");
+
+    let req = warp::test::request().header("cookie", &pair);
+    assert_eq!(
+        req.filter(&warp::cookie::decoded::<String>("n"))
+            .await
+            .unwrap(),
+        "a b;%"
+    );
+
+    // The other filters extract the raw value.
+    let req = warp::test::request().header("cookie", &pair);
+    assert_eq!(
+        req.filter(&warp::cookie::<String>("n")).await.unwrap(),
+        "a%20b%3B%25"
+    );
+
+    let req = warp::test::request().header("cookie", &pair);
+    assert_eq!(
+        req.filter(&warp::cookie::optional::<String>("n"))
+            .await
+            .unwrap()
+            .as_deref(),
+        Some("a%20b%3B%25")
+    );
+
+    #[derive(serde_derive::Deserialize)]
+    struct Cookies {
+        n: String,
+    }
+    let req = warp::test::request().header("cookie", &pair);
+    let cookies: Cookies = req.filter(&warp::cookie::deserialize()).await.unwrap();
+    assert_eq!(cookies.n, "a b;%");
+}
@@ -0,0 +1,57 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn double_submit() {
+    let _ = pretty_env_logger::try_init();
+
+    let csrf = warp::csrf::double_submit("csrf", "x-csrf-token");
+
+    let req = warp::test::request()
+        .header("cookie", "csrf=abc123")
+        .header("x-csrf-token", "abc123");
+    assert!(req.matches(&csrf).await);
+
+    let route = csrf.map(warp::reply);
+
+    let res = warp::test::request()
+        .header("cookie", "csrf=abc123")
+        .header("x-csrf-token", "abc124")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 403, "mismatch");
+    assert_eq!(res.body(), "CSRF token missing or invalid");
+
+    let res = warp::test::request()
+        .header("cookie", "csrf=abc123")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 403, "missing header");
+
+    let res = warp::test::request()
+        .header("x-csrf-token", "abc123")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 403, "missing cookie");
+}
+
+#[tokio::test]
+async fn with_token() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any()
+        .map(warp::reply)
+        .map(|reply| warp::csrf::with_token(reply, "csrf"));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+
+    let cookie = res.headers()["set-cookie"].to_str().unwrap();
+    let (token, attrs) = cookie.split_once(';').unwrap();
+    let token = token.strip_prefix("csrf=").unwrap();
+    assert_eq!(token.len(), 64);
+    assert_eq!(attrs, " Path=/; Secure; SameSite=Strict");
+
+    let other = warp::test::request().reply(&route).await;
+    assert_ne!(res.headers()["set-cookie"], other.headers()["set-cookie"]);
+}
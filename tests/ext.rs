@@ -26,3 +26,19 @@ async fn get_missing() {
     assert_eq!(res.status(), 500);
     assert_eq!(res.body(), "Missing request extension");
 }
+
+#[tokio::test]
+async fn set() {
+    let route = warp::ext::set(Ext1(7))
+        .and(warp::ext::get::<Ext1>())
+        .map(|e: Ext1| e.0.to_string());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.body(), "7");
+
+    let res = warp::test::request()
+        .extension(Ext1(55))
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "7");
+}
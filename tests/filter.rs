@@ -79,6 +79,33 @@ async fn map() {
     assert_eq!(resp.status(), 200);
 }
 
+#[tokio::test]
+async fn and_then_with() {
+    let _ = pretty_env_logger::try_init();
+
+    // Not `Clone`, only shared.
+    struct Prefix(String);
+
+    let route = warp::path::param::<u32>()
+        .and(warp::header::<String>("name"))
+        .and_then_with(Prefix("hi".into()), |prefix, (id, name)| {
+            let reply = format!("{} {} #{}", prefix.0, name, id);
+            async move {
+                if id != 0 {
+                    Ok(reply)
+                } else {
+                    Err(warp::reject::not_found())
+                }
+            }
+        });
+
+    let req = warp::test::request().path("/7").header("name", "sean");
+    assert_eq!(req.filter(&route).await.unwrap(), "hi sean #7");
+
+    let req = warp::test::request().path("/0").header("name", "sean");
+    assert!(req.filter(&route).await.unwrap_err().is_not_found());
+}
+
 #[tokio::test]
 async fn or() {
     let _ = pretty_env_logger::try_init();
@@ -91,6 +118,93 @@ async fn or() {
     let _: Result<_, Infallible> = warp::test::request().filter(&f).await;
 }
 
+#[tokio::test]
+async fn or_if() {
+    use warp::reject::MethodNotAllowed;
+
+    let _ = pretty_env_logger::try_init();
+
+    let item = warp::path("item")
+        .and(warp::get())
+        .and(warp::header::<String>("x-token"))
+        .map(|_: String| "item");
+    let route = item.or_if(
+        |rejection: &warp::Rejection| rejection.find::<MethodNotAllowed>().is_some(),
+        warp::path("item").map(|| "fallback"),
+    );
+
+    let res = warp::test::request()
+        .path("/item")
+        .header("x-token", "t")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "item");
+
+    // method not allowed, tries the other filter
+    let res = warp::test::request()
+        .method("POST")
+        .path("/item")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "fallback");
+
+    // missing header, rejects right away
+    let res = warp::test::request().path("/item").reply(&route).await;
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn map_blocking() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path::param().map_blocking(|num: u32| {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        if num == 0 {
+            panic!("zero");
+        }
+        num * 2
+    });
+
+    let req = warp::test::request().path("/21");
+    assert_eq!(req.filter(&route).await.unwrap(), 42);
+
+    let res = warp::test::request()
+        .path("/0")
+        .reply(&route.map(|num: u32| num.to_string()))
+        .await;
+    assert_eq!(res.status(), 500);
+
+    let rejection = warp::test::request()
+        .path("/0")
+        .filter(&route)
+        .await
+        .unwrap_err();
+    assert!(rejection
+        .find::<warp::reject::BlockingHandlerFailed>()
+        .is_some());
+}
+
+#[tokio::test]
+async fn and_then_blocking() {
+    let _ = pretty_env_logger::try_init();
+
+    // `is_multiple_of` is newer than the toolchains CI runs.
+    #[allow(unknown_lints, clippy::manual_is_multiple_of)]
+    let route = warp::path::param().and_then_blocking(|num: u32| {
+        if num % 2 == 0 {
+            Ok(num / 2)
+        } else {
+            Err(warp::reject::not_found())
+        }
+    });
+
+    let req = warp::test::request().path("/84");
+    assert_eq!(req.filter(&route).await.unwrap(), 42);
+
+    let req = warp::test::request().path("/3");
+    assert!(req.filter(&route).await.unwrap_err().is_not_found());
+}
+
 #[tokio::test]
 async fn or_else() {
     let _ = pretty_env_logger::try_init();
@@ -133,6 +247,42 @@ async fn recover() {
     let _: Result<_, Infallible> = warp::test::request().filter(&f).await;
 }
 
+#[tokio::test]
+async fn recover_partially() {
+    use warp::reject::MethodNotAllowed;
+
+    let _ = pretty_env_logger::try_init();
+
+    let item = warp::path("item").and(warp::get()).map(|| "item").recover(
+        |err: warp::Rejection| async move {
+            if err.find::<MethodNotAllowed>().is_some() {
+                Ok(warp::reply::with_status(
+                    "read only",
+                    warp::http::StatusCode::METHOD_NOT_ALLOWED,
+                ))
+            } else {
+                Err(err)
+            }
+        },
+    );
+    let route = item.or(warp::any().map(|| "fallback"));
+
+    let res = warp::test::request().path("/item").reply(&route).await;
+    assert_eq!(res.body(), "item");
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/item")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 405);
+    assert_eq!(res.body(), "read only");
+
+    // Other rejections fall through.
+    let res = warp::test::request().path("/other").reply(&route).await;
+    assert_eq!(res.body(), "fallback");
+}
+
 #[tokio::test]
 async fn unify() {
     let _ = pretty_env_logger::try_init();
@@ -146,6 +296,86 @@ async fn unify() {
     assert_eq!(ex, 1);
 }
 
+#[tokio::test]
+async fn boxed_reply() {
+    use warp::http::StatusCode;
+    use warp::Reply;
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("a")
+        .map(|| "a")
+        .or(warp::path("b").map(|| warp::reply::with_status("b", StatusCode::CREATED)))
+        .or(warp::path("c").map(|| warp::reply::json(&"c")))
+        .boxed_reply()
+        .map(|reply: Box<dyn Reply>| warp::reply::with_header(reply, "x-boxed", "1"));
+
+    let res = warp::test::request().path("/a").reply(&route).await;
+    assert_eq!(res.body(), "a");
+    assert_eq!(res.headers()["x-boxed"], "1");
+
+    let res = warp::test::request().path("/b").reply(&route).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.body(), "b");
+
+    let res = warp::test::request().path("/c").reply(&route).await;
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert_eq!(res.body(), "\"c\"");
+
+    let res = warp::test::request().path("/d").reply(&route).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn boxed_clone() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes: Vec<warp::filters::DynFilter> = vec![
+        warp::path("a").map(|| "a").boxed_clone(),
+        warp::path("b")
+            .map(|| warp::reply::with_status("b", warp::http::StatusCode::CREATED))
+            .boxed_clone(),
+    ];
+    let api = warp::filters::DynFilter::one_of(routes.clone());
+
+    let res = warp::test::request().path("/a").reply(&api).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "a");
+
+    let res = warp::test::request().path("/b").reply(&api).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.body(), "b");
+
+    let res = warp::test::request().path("/c").reply(&api).await;
+    assert_eq!(res.status(), 404);
+
+    let empty = warp::filters::DynFilter::one_of(Vec::new());
+    let res = warp::test::request().path("/a").reply(&empty).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn from_request() {
+    let _ = pretty_env_logger::try_init();
+
+    let chunks = vec![Ok::<_, std::io::Error>("a"), Ok("b"), Ok("c")];
+    let mut req = warp::http::Request::post("/upload")
+        .body(warp::hyper::Body::wrap_stream(futures_util::stream::iter(
+            chunks,
+        )))
+        .unwrap();
+    req.extensions_mut().insert(7u32);
+
+    let route = warp::path("upload")
+        .and(warp::ext::get::<u32>())
+        .and(warp::body::bytes())
+        .map(|ext: u32, body: bytes::Bytes| format!("{} {:?}", ext, body));
+
+    let res = warp::test::from_request(req).reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "7 b\"abc\"");
+}
+
 #[should_panic]
 #[tokio::test]
 async fn nested() {
@@ -156,3 +386,87 @@ async fn nested() {
 
     let _ = warp::test::request().filter(&f).await;
 }
+
+#[tokio::test]
+async fn inspect() {
+    use std::sync::{Arc, Mutex};
+
+    let _ = pretty_env_logger::try_init();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = seen.clone();
+    let route = warp::path::param::<u32>()
+        .and(warp::path::param::<String>())
+        .inspect(move |(id, name): &(u32, String)| {
+            log.lock().unwrap().push(format!("{}:{}", id, name));
+        })
+        .map(|id: u32, name: String| format!("{} {}", id, name));
+
+    let ex = warp::test::request()
+        .path("/5/sean")
+        .filter(&route)
+        .await
+        .unwrap();
+    assert_eq!(ex, "5 sean");
+
+    assert!(!warp::test::request().path("/x/y").matches(&route).await);
+    assert_eq!(*seen.lock().unwrap(), ["5:sean"]);
+}
+
+#[tokio::test]
+async fn and_strict() {
+    let _ = pretty_env_logger::try_init();
+
+    let upload = warp::post().and(warp::path("upload")).map(|| "uploaded");
+    let lenient = warp::path("users")
+        .and(warp::path::param::<u32>().and(warp::path::end()))
+        .map(|_| "user")
+        .or(upload);
+    let strict = warp::path("users")
+        .and_strict(warp::path::param::<u32>().and(warp::path::end()))
+        .map(|_| "user")
+        .or(upload);
+
+    let res = warp::test::request()
+        .path("/users/abc")
+        .reply(&lenient)
+        .await;
+    assert_eq!(res.status(), 405);
+
+    let res = warp::test::request()
+        .path("/users/abc")
+        .reply(&strict)
+        .await;
+    assert_eq!(res.status(), 404);
+
+    // Not committed when the first filter doesn't match.
+    let res = warp::test::request().path("/other").reply(&strict).await;
+    assert_eq!(res.status(), 405);
+
+    let res = warp::test::request().path("/users/5").reply(&strict).await;
+    assert_eq!(res.status(), 200);
+    let res = warp::test::request()
+        .method("POST")
+        .path("/upload")
+        .reply(&strict)
+        .await;
+    assert_eq!(res.status(), 200);
+
+    // Committed routes for the same path still combine as usual.
+    let routes = warp::path("item")
+        .and_strict(warp::get())
+        .map(|| "get")
+        .or(warp::path("item").and_strict(warp::put()).map(|| "put"));
+    let res = warp::test::request()
+        .method("PUT")
+        .path("/item")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "put");
+    let res = warp::test::request()
+        .method("DELETE")
+        .path("/item")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
@@ -1,5 +1,6 @@
 #![deny(warnings)]
 use std::fs;
+use warp::Filter;
 
 #[tokio::test]
 async fn file() {
@@ -26,6 +27,40 @@ async fn file() {
     assert_eq!(res.body(), &*contents);
 }
 
+#[tokio::test]
+async fn file_attachment() {
+    let _ = pretty_env_logger::try_init();
+
+    let config = warp::fs::Config::new().attachment("notes.md".to_owned());
+    let file = warp::fs::file_with_config("README.md", config);
+    let res = warp::test::request().reply(&file).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["content-disposition"],
+        "attachment; filename=\"notes.md\""
+    );
+
+    let config = warp::fs::Config::new().attachment("résumé \"v2\".md".to_owned());
+    let file = warp::fs::file_with_config("README.md", config);
+    let res = warp::test::request().reply(&file).await;
+    assert_eq!(
+        res.headers()["content-disposition"],
+        "attachment; filename=\"r_sum_ _v2_.md\"; filename*=UTF-8''r%C3%A9sum%C3%A9%20%22v2%22.md"
+    );
+
+    let res = warp::test::request()
+        .reply(&warp::fs::file("README.md"))
+        .await;
+    assert!(!res.headers().contains_key("content-disposition"));
+
+    // Each file of a directory keeps its own name.
+    let config = warp::fs::Config::new().attachment("notes.md".to_owned());
+    let dir = warp::fs::dir_with_config("examples", config);
+    let res = warp::test::request().path("/todos.rs").reply(&dir).await;
+    assert_eq!(res.status(), 200);
+    assert!(!res.headers().contains_key("content-disposition"));
+}
+
 #[tokio::test]
 async fn dir() {
     let _ = pretty_env_logger::try_init();
@@ -48,6 +83,84 @@ async fn dir() {
     assert_eq!(malformed_req.reply(&file).await.status(), 404);
 }
 
+#[tokio::test]
+async fn immutable() {
+    let _ = pretty_env_logger::try_init();
+
+    let file = warp::fs::file("README.md").map(|file: warp::filters::fs::File| file.immutable());
+
+    let res = warp::test::request().reply(&file).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+
+    let missing = warp::fs::file("nope.md").map(|file: warp::filters::fs::File| file.immutable());
+    let res = warp::test::request().reply(&missing).await;
+    assert_eq!(res.status(), 404);
+    assert!(!res.headers().contains_key("cache-control"));
+}
+
+#[tokio::test]
+async fn config_immutable() {
+    let _ = pretty_env_logger::try_init();
+
+    let config = warp::fs::Config::new().immutable(true);
+    let file = warp::fs::file_with_config("README.md", config.clone());
+    let res = warp::test::request().reply(&file).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+
+    let dir = warp::fs::dir_with_config("examples", config);
+    let res = warp::test::request().path("/todos.rs").reply(&dir).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["cache-control"],
+        "public, max-age=31536000, immutable"
+    );
+
+    let file = warp::fs::file("README.md");
+    let res = warp::test::request().reply(&file).await;
+    assert!(!res.headers().contains_key("cache-control"));
+}
+
+#[tokio::test]
+async fn dir_authorized() {
+    let _ = pretty_env_logger::try_init();
+
+    let file = warp::fs::dir_authorized("examples", |ctx: &warp::fs::Context| {
+        let allowed = ctx.headers().contains_key("x-allow")
+            && ctx.metadata().is_file()
+            && ctx.path().ends_with("todos.rs");
+        async move { allowed }
+    });
+
+    let req = warp::test::request()
+        .path("/todos.rs")
+        .header("x-allow", "1");
+    let res = req.reply(&file).await;
+    assert_eq!(res.status(), 200);
+    let contents = fs::read("examples/todos.rs").expect("fs::read");
+    assert_eq!(res.body(), &*contents);
+
+    let req = warp::test::request().path("/todos.rs");
+    assert_eq!(req.reply(&file).await.status(), 403);
+
+    let req = warp::test::request()
+        .path("/hello.rs")
+        .header("x-allow", "1");
+    assert_eq!(req.reply(&file).await.status(), 403);
+
+    let req = warp::test::request()
+        .path("/definitely-not-found")
+        .header("x-allow", "1");
+    assert_eq!(req.reply(&file).await.status(), 404);
+}
+
 #[tokio::test]
 async fn dir_encoded() {
     let _ = pretty_env_logger::try_init();
@@ -117,6 +230,66 @@ async fn dir_fallback_index_on_dir() {
     assert_eq!(res.status(), 200);
 }
 
+#[tokio::test]
+async fn dir_custom_index_and_listing() {
+    let _ = pretty_env_logger::try_init();
+
+    let config = warp::fs::Config::new().index_file("another.html".to_owned());
+    let file = warp::fs::dir_with_config("examples", config);
+    let res = warp::test::request().path("/dir/").reply(&file).await;
+    let contents = fs::read("examples/dir/another.html").expect("fs::read");
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), &contents[..]);
+
+    // no index and no listing
+    let config = warp::fs::Config::new().index_file(None);
+    let file = warp::fs::dir_with_config("examples", config);
+    let res = warp::test::request().path("/dir/").reply(&file).await;
+    assert_eq!(res.status(), 404);
+
+    // missing index falls back to the listing
+    let config = warp::fs::Config::new()
+        .index_file("missing.html".to_owned())
+        .directory_listing(true);
+    let file = warp::fs::dir_with_config("examples", config);
+    let res = warp::test::request().path("/dir").reply(&file).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "text/html; charset=utf-8");
+    let body = std::str::from_utf8(res.body()).unwrap();
+    assert!(body.contains("Index of /dir"), "{}", body);
+    assert!(body.contains("href=\"../\""), "{}", body);
+    assert!(body.contains("href=\"/dir/another.html\""), "{}", body);
+    assert!(body.contains("href=\"/dir/index.html\""), "{}", body);
+
+    // names are escaped
+    let root = std::env::temp_dir().join(format!("warp-listing-{}", std::process::id()));
+    fs::create_dir_all(root.join("sub dir")).unwrap();
+    fs::write(root.join("a<b>#.txt"), "hi").unwrap();
+    fs::create_dir_all(root.join("q\"x")).unwrap();
+    fs::write(root.join("q\"x").join("f.txt"), "hi").unwrap();
+    let config = warp::fs::Config::new().directory_listing(true);
+    let file = warp::fs::dir_with_config(root.clone(), config);
+    let res = warp::test::request().path("/").reply(&file).await;
+    let quoted = warp::test::request().path("/q\"x/").reply(&file).await;
+    fs::remove_dir_all(&root).unwrap();
+    assert_eq!(quoted.status(), 200);
+    let body = std::str::from_utf8(quoted.body()).unwrap();
+    assert!(body.contains("href=\"/q&quot;x/f.txt\""), "{}", body);
+    assert_eq!(res.status(), 200);
+    let body = std::str::from_utf8(res.body()).unwrap();
+    assert!(!body.contains("href=\"../\""), "{}", body);
+    assert!(
+        body.contains("<a href=\"/a%3Cb%3E%23.txt\">a&lt;b&gt;#.txt</a></td><td>2</td>"),
+        "{}",
+        body
+    );
+    assert!(
+        body.contains("<a href=\"/sub%20dir/\">sub dir/</a></td><td>-</td>"),
+        "{}",
+        body
+    );
+}
+
 #[tokio::test]
 async fn not_modified() {
     let _ = pretty_env_logger::try_init();
@@ -269,3 +442,97 @@ async fn byte_ranges_with_excluded_file_size() {
     );
     assert_eq!(res.body(), &contents[100..=contents.len() - 1]);
 }
+
+#[cfg(feature = "archive")]
+#[tokio::test]
+async fn archive_tar() {
+    use std::io::Read;
+
+    let _ = pretty_env_logger::try_init();
+
+    let archive = warp::fs::archive("tests/fixtures/archive", warp::filters::fs::Format::Tar);
+
+    let res = warp::test::request().reply(&archive).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "application/x-tar");
+    assert_eq!(
+        res.headers()["content-disposition"],
+        "attachment; filename=\"archive.tar\""
+    );
+
+    let mut tar = tar::Archive::new(&res.body()[..]);
+    let entries = tar
+        .entries()
+        .expect("tar entries")
+        .map(|entry| {
+            let mut entry = entry.expect("tar entry");
+            let name = entry.path().expect("tar path").display().to_string();
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents).expect("tar read");
+            (name, contents)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        [
+            ("hello.txt".to_owned(), "hello\n".to_owned()),
+            ("sub/".to_owned(), String::new()),
+            ("sub/nested.txt".to_owned(), "nested\n".to_owned()),
+        ]
+    );
+}
+
+#[cfg(feature = "archive")]
+#[tokio::test]
+async fn archive_zip() {
+    use std::io::Read;
+
+    let _ = pretty_env_logger::try_init();
+
+    let archive = warp::fs::archive("tests/fixtures/archive", warp::filters::fs::Format::Zip);
+
+    let res = warp::test::request().reply(&archive).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "application/zip");
+
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(res.body().clone())).expect("zip");
+    let mut entries = Vec::new();
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).expect("zip entry");
+        let mut contents = String::new();
+        entry.read_to_string(&mut contents).expect("zip read");
+        entries.push((entry.name().to_owned(), contents));
+    }
+    assert_eq!(
+        entries,
+        [
+            ("hello.txt".to_owned(), "hello\n".to_owned()),
+            ("sub/".to_owned(), String::new()),
+            ("sub/nested.txt".to_owned(), "nested\n".to_owned()),
+        ]
+    );
+}
+
+#[cfg(all(feature = "archive", feature = "compression-gzip"))]
+#[tokio::test]
+async fn archive_tar_gz() {
+    let _ = pretty_env_logger::try_init();
+
+    let archive = warp::fs::archive("tests/fixtures/archive", warp::filters::fs::Format::TarGz);
+
+    let res = warp::test::request().reply(&archive).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "application/gzip");
+    assert_eq!(&res.body()[..2], b"\x1f\x8b");
+}
+
+#[cfg(feature = "archive")]
+#[tokio::test]
+async fn archive_not_found() {
+    let _ = pretty_env_logger::try_init();
+
+    let archive = warp::fs::archive("README.md", warp::filters::fs::Format::Zip);
+
+    let res = warp::test::request().reply(&archive).await;
+    assert_eq!(res.status(), 404);
+}
@@ -0,0 +1,88 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn derive_from_get() {
+    let _ = pretty_env_logger::try_init();
+
+    let get = warp::get()
+        .and(warp::path("readme"))
+        .map(|| warp::reply::with_header("# warp\n", "x-doc", "readme"));
+    let route = warp::head::derive_from_get(get).or(warp::head().map(|| "fallback"));
+
+    let res = warp::test::request()
+        .method("HEAD")
+        .path("/readme")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "7");
+    assert_eq!(res.headers()["x-doc"], "readme");
+    assert_eq!(res.body(), "");
+
+    let res = warp::test::request().path("/readme").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "# warp\n");
+
+    // Later filters see the original method.
+    let res = warp::test::request()
+        .method("HEAD")
+        .path("/other")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "fallback");
+
+    // An empty body is known to be empty.
+    let empty = warp::get().map(warp::reply);
+    let res = warp::test::request()
+        .method("HEAD")
+        .reply(&warp::head::derive_from_get(empty))
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "0");
+}
+
+#[tokio::test]
+async fn is_head() {
+    let _ = pretty_env_logger::try_init();
+
+    let route =
+        warp::head::derive_from_get(warp::get().and(warp::head::is_head()).map(|is_head: bool| {
+            warp::reply::with_header("", "content-length", if is_head { "5" } else { "0" })
+        }));
+
+    let res = warp::test::request().method("HEAD").reply(&route).await;
+    assert_eq!(res.headers()["content-length"], "5");
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.headers()["content-length"], "0");
+
+    let ext = warp::test::request()
+        .method("HEAD")
+        .filter(&warp::head::is_head())
+        .await
+        .unwrap();
+    assert!(!ext);
+}
+
+#[tokio::test]
+async fn auto() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::get()
+        .and(warp::path("readme"))
+        .map(|| "# warp\n")
+        .with(warp::head::auto());
+
+    let res = warp::test::request()
+        .method("HEAD")
+        .path("/readme")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "7");
+    assert_eq!(res.body(), "");
+
+    let res = warp::test::request().path("/readme").reply(&route).await;
+    assert_eq!(res.body(), "# warp\n");
+}
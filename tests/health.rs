@@ -0,0 +1,59 @@
+#![deny(warnings)]
+use warp::health::{HealthCheck, HealthStatus};
+use warp::Filter;
+
+#[tokio::test]
+async fn endpoint_healthy() {
+    let _ = pretty_env_logger::try_init();
+
+    let checks: Vec<HealthCheck> = vec![Box::new(|| HealthStatus::Healthy)];
+    let route = warp::health::endpoint(checks);
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert_eq!(res.body(), r#"{"status":"ok"}"#);
+}
+
+#[tokio::test]
+async fn endpoint_unhealthy() {
+    let _ = pretty_env_logger::try_init();
+
+    let checks: Vec<HealthCheck> = vec![
+        Box::new(|| HealthStatus::unhealthy("database")),
+        Box::new(|| HealthStatus::Healthy),
+        Box::new(|| HealthStatus::unhealthy("cache")),
+    ];
+    let route = warp::health::endpoint(checks);
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 503);
+    assert_eq!(
+        res.body(),
+        r#"{"failing":["database","cache"],"status":"unavailable"}"#
+    );
+}
+
+#[tokio::test]
+async fn probes() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::health::liveness()
+        .or(warp::health::readiness(vec![Box::new(|| {
+            HealthStatus::unhealthy("warming up")
+        })]))
+        .or(warp::any().map(warp::reply));
+
+    let res = warp::test::request().path("/livez").reply(&routes).await;
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request().path("/readyz").reply(&routes).await;
+    assert_eq!(res.status(), 503);
+
+    let res = warp::test::request()
+        .path("/readyz/nope")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "");
+}
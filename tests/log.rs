@@ -0,0 +1,77 @@
+#![deny(warnings)]
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use warp::Filter;
+
+#[tokio::test]
+async fn tap_response() {
+    let _ = pretty_env_logger::try_init();
+
+    let tapped = Arc::new(Mutex::new(Vec::<Bytes>::new()));
+    let sink = tapped.clone();
+    let tap = warp::log::tap_response(move |body| sink.lock().unwrap().push(body)).limit(8);
+
+    let route = warp::path("short")
+        .map(|| "hello")
+        .or(warp::path("long").map(|| "hello, world"))
+        .or(warp::path("stream").map(|| {
+            let chunks = vec![Ok::<_, std::io::Error>("he"), Ok("llo")];
+            warp::http::Response::new(warp::hyper::Body::wrap_stream(futures_util::stream::iter(
+                chunks,
+            )))
+        }))
+        .with(tap);
+
+    let res = warp::test::request().path("/short").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-length"], "5");
+    assert_eq!(res.body(), "hello");
+
+    let res = warp::test::request().path("/long").reply(&route).await;
+    assert_eq!(res.body(), "hello, world");
+
+    let res = warp::test::request().path("/stream").reply(&route).await;
+    assert_eq!(res.body(), "hello");
+
+    let tapped = tapped.lock().unwrap();
+    assert_eq!(*tapped, ["hello", "hello"]);
+}
+
+#[tokio::test]
+async fn custom_info() {
+    let _ = pretty_env_logger::try_init();
+
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let sink = logged.clone();
+    let log = warp::log::custom(move |info| {
+        sink.lock().unwrap().push((
+            info.matched_pattern(),
+            info.status().as_u16(),
+            info.response_length(),
+        ));
+    });
+    let route = warp::path!("users" / u32)
+        .map(|_| "hello")
+        .or(warp::path("stream").map(|| {
+            let chunks = vec![Ok::<_, std::io::Error>("he"), Ok("llo")];
+            warp::http::Response::new(warp::hyper::Body::wrap_stream(futures_util::stream::iter(
+                chunks,
+            )))
+        }))
+        .with(log);
+
+    warp::test::request().path("/users/5").reply(&route).await;
+    warp::test::request().path("/stream").reply(&route).await;
+    warp::test::request().path("/nope").reply(&route).await;
+
+    let logged = logged.lock().unwrap();
+    assert_eq!(
+        *logged,
+        [
+            ("/users/:param".to_owned(), 200, Some(5)),
+            ("/stream".to_owned(), 200, None),
+            ("/".to_owned(), 404, None),
+        ]
+    );
+}
@@ -50,3 +50,62 @@ async fn bad_request_trumps_method_not_allowed() {
     // assume POST was the appropriate method.
     assert_eq!(resp.status(), 400);
 }
+
+#[tokio::test]
+async fn extract_nonstandard_method() {
+    let _ = pretty_env_logger::try_init();
+    let route = warp::method().map(|method: warp::http::Method| method.to_string());
+
+    let req = warp::test::request().method("PROPFIND");
+    let resp = req.reply(&route).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body(), "PROPFIND");
+}
+
+#[tokio::test]
+async fn with_override() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::delete()
+        .map(|| "deleted")
+        .or(warp::post().map(|| "posted"))
+        .or(warp::get().map(|| "got"))
+        .with(warp::method::with_override());
+    let route = routes.clone().and(warp::method()).map(|reply, method| {
+        // The override is only seen by the wrapped filter.
+        assert_eq!(method, "POST");
+        reply
+    });
+
+    let res = warp::test::request()
+        .method("POST")
+        .header("x-http-method-override", "delete")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "deleted");
+
+    // not an allowed method
+    let res = warp::test::request()
+        .method("POST")
+        .header("x-http-method-override", "GET")
+        .reply(&route)
+        .await;
+    assert_eq!(res.body(), "posted");
+
+    // only POST can be overridden
+    let res = warp::test::request()
+        .header("x-http-method-override", "DELETE")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.body(), "got");
+
+    let custom = warp::get()
+        .map(|| "got")
+        .with(warp::method::with_override().allow(vec![warp::http::Method::GET]));
+    let res = warp::test::request()
+        .method("POST")
+        .header("x-http-method-override", "GET")
+        .reply(&custom)
+        .await;
+    assert_eq!(res.body(), "got");
+}
@@ -0,0 +1,50 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn metrics() {
+    let _ = pretty_env_logger::try_init();
+
+    let registry = warp::metrics::Registry::new();
+
+    let items = warp::path("items")
+        .and(warp::path::param::<u32>())
+        .map(|id: u32| {
+            let status = if id == 0 {
+                warp::http::StatusCode::NOT_FOUND
+            } else {
+                warp::http::StatusCode::OK
+            };
+            warp::reply::with_status("item", status)
+        })
+        .with(warp::metrics(registry.clone()).route("items"));
+    let routes = items.or(warp::metrics::exporter(registry));
+
+    for path in &["/items/1", "/items/2", "/items/0", "/missing"] {
+        warp::test::request().path(path).reply(&routes).await;
+    }
+
+    let res = warp::test::request().path("/metrics").reply(&routes).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(
+        res.headers()["content-type"],
+        "text/plain; version=0.0.4; charset=utf-8"
+    );
+    let body = std::str::from_utf8(res.body()).unwrap();
+    assert!(body
+        .contains("warp_http_requests_total{route=\"items\",method=\"GET\",status=\"2xx\"} 2\n"));
+    assert!(body
+        .contains("warp_http_requests_total{route=\"items\",method=\"GET\",status=\"4xx\"} 1\n"));
+    assert!(body.contains(
+        "warp_http_request_duration_seconds_bucket{route=\"items\",method=\"GET\",le=\"+Inf\"} 3\n"
+    ));
+    assert!(body
+        .contains("warp_http_request_duration_seconds_count{route=\"items\",method=\"GET\"} 3\n"));
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/metrics")
+        .reply(&routes)
+        .await;
+    assert_eq!(res.status(), 405);
+}
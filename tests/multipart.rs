@@ -53,6 +53,56 @@ async fn form_fields() {
     assert_eq!(&vec[0].1, b"bar");
 }
 
+#[tokio::test]
+async fn part_content_type() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = multipart::form().and_then(|form: multipart::FormData| async {
+        form.map_ok(|part| part.content_type().map(ToOwned::to_owned))
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(|e| -> warp::Rejection { panic!("multipart error: {:?}", e) })
+    });
+
+    let boundary = "--abcdef1234--";
+    let body = format!(
+        "\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"photo\"; filename=\"cat.jpg\"\r\n\
+         content-type: image/jpeg\r\n\r\n\
+         meow\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"note\"\r\n\
+         content-type: text/plain; charset=utf-8\r\n\r\n\
+         hi\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"plain\"\r\n\r\n\
+         bye\r\n\
+         --{0}--\r\n\
+         ",
+        boundary
+    );
+
+    let req = warp::test::request()
+        .method("POST")
+        .header("content-length", body.len())
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .body(body);
+
+    let types = req.filter(&route).await.unwrap();
+    assert_eq!(
+        types,
+        [
+            Some("image/jpeg".to_owned()),
+            Some("text/plain; charset=utf-8".to_owned()),
+            None,
+        ]
+    );
+}
+
 #[tokio::test]
 async fn max_length_is_enforced() {
     let _ = pretty_env_logger::try_init();
@@ -100,3 +150,239 @@ async fn max_length_can_be_disabled() {
     let resp = req.filter(&route).await;
     assert!(resp.is_ok());
 }
+
+#[tokio::test]
+async fn typed() {
+    use serde_derive::Deserialize;
+
+    let _ = pretty_env_logger::try_init();
+
+    #[derive(Deserialize)]
+    struct Upload {
+        title: String,
+        count: u32,
+        tags: Vec<String>,
+        note: Option<String>,
+        photo: multipart::UploadedFile,
+    }
+
+    let route = multipart::typed::<Upload>();
+
+    let boundary = "--abcdef1234--";
+    let body = format!(
+        "\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"title\"\r\n\r\n\
+         cat\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"count\"\r\n\r\n\
+         3\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"tags\"\r\n\r\n\
+         a\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"tags\"\r\n\r\n\
+         b\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"photo\"; filename=\"cat.png\"\r\n\
+         content-type: image/png\r\n\r\n\
+         meow\r\n\
+         --{0}--\r\n\
+         ",
+        boundary
+    );
+
+    let req = warp::test::request()
+        .method("POST")
+        .header("content-length", body.len())
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .body(body);
+
+    let upload = req.filter(&route).await.unwrap();
+    assert_eq!(upload.title, "cat");
+    assert_eq!(upload.count, 3);
+    assert_eq!(upload.tags, ["a", "b"]);
+    assert_eq!(upload.note, None);
+    assert_eq!(upload.photo.filename(), "cat.png");
+    assert_eq!(upload.photo.content_type(), Some("image/png"));
+    assert_eq!(upload.photo.data().as_ref(), b"meow");
+
+    // missing the photo
+    let body = format!(
+        "\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"title\"\r\n\r\n\
+         cat\r\n\
+         --{0}--\r\n\
+         ",
+        boundary
+    );
+    let res = warp::test::request()
+        .method("POST")
+        .header("content-length", body.len())
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={}", boundary),
+        )
+        .body(body)
+        .reply(&route.map(|_: Upload| warp::reply()))
+        .await;
+    assert_eq!(res.status(), 400);
+}
+
+#[tokio::test]
+async fn max_part_size_and_max_parts() {
+    use std::error::Error;
+
+    let _ = pretty_env_logger::try_init();
+
+    let boundary = "--abcdef1234--";
+    let body = format!(
+        "\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"a\"\r\n\r\n\
+         short\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"b\"\r\n\r\n\
+         much too long\r\n\
+         --{0}--\r\n\
+         ",
+        boundary
+    );
+    let req = || {
+        warp::test::request()
+            .method("POST")
+            .header("content-length", body.len())
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(body.clone())
+    };
+
+    let route = multipart::form()
+        .max_part_size(8)
+        .and_then(|form: multipart::FormData| async {
+            let err = form
+                .try_for_each(|part| part.stream().try_for_each(|_| async { Ok(()) }))
+                .await
+                .unwrap_err();
+            Ok::<_, warp::Rejection>(err.source().unwrap().is::<multipart::PartTooLarge>())
+        });
+    assert!(req().filter(&route).await.unwrap());
+
+    let route = multipart::form()
+        .max_parts(1)
+        .and_then(|form: multipart::FormData| async {
+            let err = form.try_for_each(|_| async { Ok(()) }).await.unwrap_err();
+            Ok::<_, warp::Rejection>(err.source().unwrap().is::<multipart::TooManyParts>())
+        });
+    assert!(req().filter(&route).await.unwrap());
+
+    let route = multipart::typed::<std::collections::HashMap<String, String>>()
+        .max_part_size(8)
+        .map(|_| warp::reply());
+    assert_eq!(req().reply(&route).await.status(), 413);
+
+    let route = multipart::typed::<std::collections::HashMap<String, String>>()
+        .max_parts(2)
+        .map(|_| warp::reply());
+    assert_eq!(req().reply(&route).await.status(), 200);
+}
+
+#[tokio::test]
+async fn try_for_each_part() {
+    use bytes::Buf;
+    use std::error::Error;
+    use std::sync::{Arc, Mutex};
+
+    let _ = pretty_env_logger::try_init();
+
+    let boundary = "--abcdef1234--";
+    let body = format!(
+        "\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"a\"\r\n\r\n\
+         one\r\n\
+         --{0}\r\n\
+         content-disposition: form-data; name=\"b\"; filename=\"b.txt\"\r\n\r\n\
+         two\r\n\
+         --{0}--\r\n\
+         ",
+        boundary
+    );
+    let req = || {
+        warp::test::request()
+            .method("POST")
+            .header("content-length", body.len())
+            .header(
+                "content-type",
+                format!("multipart/form-data; boundary={}", boundary),
+            )
+            .body(body.clone())
+    };
+
+    let route = multipart::form().and_then(|form: multipart::FormData| async {
+        let parts = Arc::new(Mutex::new(Vec::new()));
+        form.try_for_each_part(|part| {
+            let parts = parts.clone();
+            async move {
+                let name = part.name().to_owned();
+                let data = part
+                    .stream()
+                    .try_fold(Vec::new(), |mut vec, data| {
+                        vec.put(data);
+                        async move { Ok(vec) }
+                    })
+                    .await?;
+                parts.lock().unwrap().push((name, data));
+                Ok::<_, warp::Error>(())
+            }
+        })
+        .await
+        .unwrap();
+        let parts = parts.lock().unwrap().clone();
+        Ok::<_, warp::Rejection>(parts)
+    });
+    let parts = req().filter(&route).await.unwrap();
+    assert_eq!(
+        parts,
+        [
+            ("a".to_owned(), b"one".to_vec()),
+            ("b".to_owned(), b"two".to_vec())
+        ]
+    );
+
+    // A single `data()` reads a small part to its end.
+    let route = multipart::form().and_then(|form: multipart::FormData| async {
+        let parts = Arc::new(Mutex::new(Vec::new()));
+        form.try_for_each_part(|mut part| {
+            let parts = parts.clone();
+            async move {
+                let mut data = part.data().await.unwrap()?;
+                let data = data.copy_to_bytes(data.remaining());
+                parts.lock().unwrap().push(data);
+                Ok::<_, warp::Error>(())
+            }
+        })
+        .await
+        .unwrap();
+        let parts = parts.lock().unwrap().clone();
+        Ok::<_, warp::Rejection>(parts)
+    });
+    let parts = req().filter(&route).await.unwrap();
+    assert_eq!(parts, ["one", "two"]);
+
+    // Skipping the body of a part is an error.
+    let route = multipart::form().and_then(|form: multipart::FormData| async {
+        let err = form
+            .try_for_each_part(|_| async { Ok::<_, warp::Error>(()) })
+            .await
+            .unwrap_err();
+        Ok::<_, warp::Rejection>(err.source().unwrap().is::<multipart::PartNotConsumed>())
+    });
+    assert!(req().filter(&route).await.unwrap());
+}
@@ -59,6 +59,89 @@ async fn param() {
     );
 }
 
+#[tokio::test]
+async fn param_in() {
+    let _ = pretty_env_logger::try_init();
+
+    let page = warp::path::param_in(1u32, 100);
+
+    let req = warp::test::request().path("/1");
+    assert_eq!(req.filter(&page).await.unwrap(), 1);
+    let req = warp::test::request().path("/100/next");
+    assert_eq!(req.filter(&page).await.unwrap(), 100);
+
+    // not a number isn't a match
+    let req = warp::test::request().path("/warp");
+    assert!(!req.matches(&page).await);
+
+    let route = page.map(|_| warp::reply());
+    for out in &["/0", "/101"] {
+        let res = warp::test::request().path(out).reply(&route).await;
+        assert_eq!(res.status(), 400, "{:?}", out);
+        assert_eq!(
+            res.body(),
+            "Path parameter out of range, must be between 1 and 100"
+        );
+    }
+
+    let rej = warp::test::request()
+        .path("/500")
+        .filter(&page)
+        .await
+        .unwrap_err();
+    let out = rej
+        .find::<warp::path::ParamOutOfRange>()
+        .expect("ParamOutOfRange");
+    assert_eq!((out.min(), out.max()), ("1", "100"));
+}
+
+#[tokio::test]
+async fn rewrite() {
+    let _ = pretty_env_logger::try_init();
+
+    let items = warp::path!("items" / u32)
+        .and(warp::path::full())
+        .and(warp::query::raw())
+        .map(|id, full: warp::path::FullPath, query| format!("{} {} {}", id, full.as_str(), query));
+    let legacy = items.with(warp::path::rewrite(|path: &str| {
+        path.strip_prefix("/old/")
+            .map(|id| format!("/items/{}", id))
+    }));
+
+    let res = warp::test::request()
+        .path("/old/5?a=b")
+        .reply(&legacy)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "5 /items/5 a=b");
+
+    // `None` rejects
+    let res = warp::test::request().path("/new/5").reply(&legacy).await;
+    assert_eq!(res.status(), 404);
+
+    // the path is put back for other filters
+    let route =
+        legacy.or(warp::path::full().map(|full: warp::path::FullPath| full.as_str().to_owned()));
+    let res = warp::test::request().path("/old/x?a=b").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "/old/x");
+
+    // only the unmatched part is given to the rewriter
+    let mounted = warp::path("api").and(
+        warp::path::full()
+            .map(|full: warp::path::FullPath| full.as_str().to_owned())
+            .with(warp::path::rewrite(|path: &str| {
+                assert_eq!(path, "/v1/users");
+                Some(path.replacen("/v1", "/v2", 1))
+            })),
+    );
+    let res = warp::test::request()
+        .path("/api/v1/users")
+        .reply(&mounted)
+        .await;
+    assert_eq!(res.body(), "/api/v2/users");
+}
+
 #[tokio::test]
 async fn end() {
     let _ = pretty_env_logger::try_init();
@@ -330,6 +413,41 @@ async fn full_path() {
     assert_eq!(ex.as_str(), "/");
 }
 
+#[tokio::test]
+async fn matched_pattern() {
+    let _ = pretty_env_logger::try_init();
+
+    let pattern = warp::path::matched_pattern();
+    // The first branch matches `/users/:param` before rejecting, which
+    // shouldn't leak into the pattern of the second.
+    let route = path!("users" / u32 / "posts")
+        .and(pattern)
+        .map(|_, pattern: String| pattern)
+        .or(path!("users" / String / ..)
+            .and(pattern)
+            .map(|_, pattern| pattern))
+        .unify()
+        .or(warp::path("static")
+            .and(warp::path::tail())
+            .and(pattern)
+            .map(|_, pattern| pattern))
+        .unify()
+        .or(pattern)
+        .unify();
+
+    let req = warp::test::request().path("/users/42/posts");
+    assert_eq!(req.filter(&route).await.unwrap(), "/users/:param/posts");
+
+    let req = warp::test::request().path("/users/42/comments");
+    assert_eq!(req.filter(&route).await.unwrap(), "/users/:param");
+
+    let req = warp::test::request().path("/static/css/app.css");
+    assert_eq!(req.filter(&route).await.unwrap(), "/static/*");
+
+    let req = warp::test::request().path("/other");
+    assert_eq!(req.filter(&route).await.unwrap(), "/");
+}
+
 #[tokio::test]
 async fn peek() {
     let peek = warp::path::peek();
@@ -409,3 +527,60 @@ async fn peek_segments() {
     let segs = ex.segments().collect::<Vec<_>>();
     assert_eq!(segs, Vec::<&str>::new());
 }
+
+#[tokio::test]
+async fn params() {
+    use serde_derive::Deserialize;
+
+    let _ = pretty_env_logger::try_init();
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct PostPath {
+        user_id: u64,
+        slug: String,
+        draft: Option<bool>,
+    }
+
+    let posts = warp::path("posts").and(warp::path::params::<PostPath>());
+
+    let req = warp::test::request().path("/posts/7/hello/true");
+    assert_eq!(
+        req.filter(&posts).await.unwrap(),
+        PostPath {
+            user_id: 7,
+            slug: "hello".into(),
+            draft: Some(true),
+        }
+    );
+
+    let tuple = warp::path::params::<(String, u32)>()
+        .and(warp::path::end())
+        .and(warp::path::matched_pattern());
+    let req = warp::test::request().path("/sean/42/");
+    let ((name, id), pattern) = req.filter(&tuple).await.unwrap();
+    assert_eq!(
+        (name.as_str(), id, pattern.as_str()),
+        ("sean", 42, "/:param/:param")
+    );
+
+    // too few, too many, or a bad value
+    for path in [
+        "/posts/7",
+        "/posts/7/hello/true/more",
+        "/posts/seven/hello/true",
+    ] {
+        let res = warp::test::request()
+            .path(path)
+            .reply(&posts.map(|p: PostPath| p.slug))
+            .await;
+        assert_eq!(res.status(), 404, "{}", path);
+    }
+
+    let err = warp::test::request()
+        .path("/posts/seven/hello/true")
+        .filter(&posts)
+        .await
+        .unwrap_err();
+    let err = err.find::<warp::path::InvalidPathParams>().unwrap();
+    assert!(err.to_string().contains("segment 1 (\"seven\")"), "{}", err);
+}
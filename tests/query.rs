@@ -119,7 +119,74 @@ async fn missing_required_query_struct_no_query() {
 
     let res = req.reply(&as_struct).await;
     assert_eq!(res.status(), 400);
-    assert_eq!(res.body(), "Invalid query string");
+    assert_eq!(res.body(), "Invalid query string: missing field `foo`");
+}
+
+#[tokio::test]
+async fn invalid_query_field() {
+    #[derive(Deserialize, Debug)]
+    struct Page {
+        #[allow(dead_code)]
+        q: String,
+        #[allow(dead_code)]
+        limit: u8,
+    }
+
+    let route = warp::query::<Page>();
+
+    let req = warp::test::request().path("/?q=a&limit=lots");
+    let rejection = req.filter(&route).await.unwrap_err();
+    let err = rejection
+        .find::<warp::reject::InvalidQuery>()
+        .expect("InvalidQuery");
+    assert_eq!(err.field(), Some("limit"));
+
+    let res = warp::test::request()
+        .path("/?limit=300&q=a")
+        .reply(&route.map(|_| warp::reply()))
+        .await;
+    assert_eq!(res.status(), 400);
+    assert_eq!(
+        res.body(),
+        "Invalid query string: field `limit`: number too large to fit in target type"
+    );
+}
+
+#[tokio::test]
+async fn lenient_and_strict_query() {
+    #[derive(Deserialize, Debug, Eq, PartialEq)]
+    #[serde(deny_unknown_fields)]
+    struct Search {
+        q: String,
+    }
+
+    let req = warp::test::request().path("/?q=warp&utm_source=newsletter");
+    assert!(req.filter(&warp::query::<Search>()).await.is_err());
+
+    let req = warp::test::request().path("/?q=warp&utm_source=newsletter");
+    let extracted = req.filter(&warp::query::lenient::<Search>()).await.unwrap();
+    assert_eq!(extracted.q, "warp");
+
+    let strict = warp::query::strict::<MyArgs>();
+
+    let req = warp::test::request().path("/?foo=bar");
+    let extracted = req.filter(&strict).await.unwrap();
+    assert_eq!(extracted.foo.as_deref(), Some("bar"));
+
+    let req = warp::test::request().path("/?foo=bar&utm_source=newsletter");
+    let rejection = req.filter(&strict).await.unwrap_err();
+    let err = rejection
+        .find::<warp::reject::InvalidQuery>()
+        .expect("InvalidQuery");
+    assert_eq!(err.field(), Some("utm_source"));
+
+    // Maps have no known fields.
+    let req = warp::test::request().path("/?foo=bar");
+    let extracted = req
+        .filter(&warp::query::strict::<HashMap<String, String>>())
+        .await
+        .unwrap();
+    assert_eq!(extracted["foo"], "bar");
 }
 
 #[derive(Deserialize, Debug, Eq, PartialEq)]
@@ -0,0 +1,505 @@
+#![deny(warnings)]
+use std::fs;
+use warp::Filter;
+
+#[tokio::test]
+async fn file_once() {
+    let _ = pretty_env_logger::try_init();
+
+    let path = std::env::temp_dir().join(format!("warp-file-once-{}.csv", std::process::id()));
+    fs::write(&path, "a,b\n1,2\n").expect("fs::write");
+
+    let route = {
+        let path = path.clone();
+        warp::any().then(move || warp::reply::file_once(path.clone()))
+    };
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "text/csv");
+    assert_eq!(res.headers()["content-length"], "8");
+    assert_eq!(
+        res.headers()["content-disposition"],
+        format!(
+            "attachment; filename=\"{}\"",
+            path.file_name().unwrap().to_str().unwrap()
+        )
+    );
+    assert_eq!(res.body(), "a,b\n1,2\n");
+    // The file is removed in the background.
+    for _ in 0..100 {
+        if !path.exists() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    assert!(!path.exists(), "file should be removed after being sent");
+
+    // already downloaded
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 404);
+}
+
+#[tokio::test]
+async fn with_cookies() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let reply = warp::reply::with_header("hi", "set-cookie", "a=1");
+        let reply = warp::reply::with_header(reply, "set-cookie", "b=2; Path=/");
+        warp::reply::with_cookies(reply, vec!["c=3; HttpOnly", "bad\ncookie", "d=4"])
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    let cookies = res
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(cookies, ["a=1", "b=2; Path=/", "c=3; HttpOnly", "d=4"]);
+}
+
+#[tokio::test]
+async fn flushing_stream() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let lines = vec![Ok::<_, std::io::Error>("one\n"), Ok("two\n"), Ok("three\n")];
+        warp::reply::flushing_stream(futures_util::stream::iter(lines))
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert!(!res.headers().contains_key("content-length"));
+    assert_eq!(res.body(), "one\ntwo\nthree\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn flushing_stream_live() {
+    use futures_util::StreamExt;
+    use std::io::{Read, Write};
+    use std::sync::{mpsc, Arc, Mutex};
+    use std::time::Duration;
+
+    let _ = pretty_env_logger::try_init();
+
+    // The second item is only produced once the client has read the first,
+    // which it can't while the first is still buffered in the server.
+    let (ack_tx, ack_rx) = mpsc::channel::<()>();
+    let ack_rx = Arc::new(Mutex::new(ack_rx));
+    let route = warp::any().map(move || {
+        let ack_rx = ack_rx.clone();
+        let second = futures_util::stream::iter(Some(())).map(move |()| {
+            match ack_rx.lock().unwrap().recv_timeout(Duration::from_secs(5)) {
+                Ok(()) => "two\n",
+                Err(_) => "not flushed\n",
+            }
+        });
+        let lines = futures_util::stream::iter(Some("one\n")).chain(second);
+        warp::reply::flushing_stream(lines.map(Ok::<_, std::io::Error>))
+    });
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let res = tokio::task::spawn_blocking(move || {
+        let mut conn = std::net::TcpStream::connect(addr).unwrap();
+        conn.write_all(b"GET / HTTP/1.1\r\nhost: warp\r\nconnection: close\r\n\r\n")
+            .unwrap();
+        let mut res = Vec::new();
+        let mut buf = [0; 1024];
+        while !String::from_utf8_lossy(&res).contains("one\n") {
+            let n = conn.read(&mut buf).unwrap();
+            assert_ne!(n, 0, "closed before the first item");
+            res.extend_from_slice(&buf[..n]);
+        }
+        ack_tx.send(()).unwrap();
+        conn.read_to_end(&mut res).unwrap();
+        String::from_utf8(res).unwrap()
+    })
+    .await
+    .unwrap();
+
+    assert!(res.contains("two\n"), "{:?}", res);
+}
+
+#[tokio::test]
+async fn lazy() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use warp::http::header::{HeaderMap, HeaderValue, ETAG};
+
+    let _ = pretty_env_logger::try_init();
+
+    let rendered = Arc::new(AtomicUsize::new(0));
+    let counter = rendered.clone();
+    let get = warp::get().map(move || {
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+        let counter = counter.clone();
+        warp::reply::lazy(headers, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            "expensive"
+        })
+    });
+    let route = warp::head::derive_from_get(get);
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["etag"], "\"v1\"");
+    assert_eq!(res.body(), "expensive");
+    assert_eq!(rendered.load(Ordering::SeqCst), 1);
+
+    let res = warp::test::request()
+        .header("if-none-match", "\"v1\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 304);
+    assert_eq!(res.headers()["etag"], "\"v1\"");
+    assert_eq!(res.body(), "");
+
+    let res = warp::test::request().method("HEAD").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["etag"], "\"v1\"");
+    assert!(!res.headers().contains_key("content-length"));
+    assert_eq!(res.body(), "");
+
+    assert_eq!(rendered.load(Ordering::SeqCst), 1);
+
+    let res = warp::test::request()
+        .header("if-none-match", "\"v0\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(rendered.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn status_tuple() {
+    use warp::http::header::{HeaderMap, HeaderValue, CONTENT_TYPE, LOCATION};
+    use warp::http::StatusCode;
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path("created")
+        .map(|| (StatusCode::CREATED, warp::reply::json(&"made")))
+        .or(warp::path("moved").map(|| {
+            let mut headers = HeaderMap::new();
+            headers.insert(LOCATION, HeaderValue::from_static("/new"));
+            headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/x-moved"));
+            (StatusCode::MOVED_PERMANENTLY, headers, "gone")
+        }));
+
+    let res = warp::test::request().path("/created").reply(&route).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert_eq!(res.body(), "\"made\"");
+
+    let res = warp::test::request().path("/moved").reply(&route).await;
+    assert_eq!(res.status(), 301);
+    assert_eq!(res.headers()["location"], "/new");
+    assert_eq!(res.headers()["content-type"], "text/x-moved");
+    assert_eq!(res.body(), "gone");
+}
+
+#[tokio::test]
+async fn with_trailers() {
+    use warp::http::header::{HeaderMap, HeaderValue};
+    use warp::hyper::body::HttpBody;
+    use warp::Reply;
+
+    let _ = pretty_env_logger::try_init();
+
+    let mut trailers = HeaderMap::new();
+    trailers.insert("grpc-status", HeaderValue::from_static("0"));
+    let res = warp::reply::with_header("done", "x-header", "kept");
+    let res = warp::reply::with_trailers(res, trailers).into_response();
+    assert_eq!(res.headers()["x-header"], "kept");
+
+    let mut body = res.into_body();
+    let mut data = Vec::new();
+    while let Some(chunk) = body.data().await {
+        data.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(data, b"done");
+    let trailers = body.trailers().await.unwrap().expect("trailers");
+    assert_eq!(trailers["grpc-status"], "0");
+}
+
+#[tokio::test]
+async fn with_ranges() {
+    use warp::http::HeaderMap;
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::header::headers_cloned()
+        .map(|headers: HeaderMap| warp::reply::with_ranges(&b"0123456789"[..], &headers));
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["accept-ranges"], "bytes");
+    assert_eq!(res.headers()["content-length"], "10");
+    assert_eq!(res.body(), "0123456789");
+
+    let res = warp::test::request()
+        .header("range", "bytes=2-5")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 206);
+    assert_eq!(res.headers()["content-range"], "bytes 2-5/10");
+    assert_eq!(res.headers()["content-length"], "4");
+    assert_eq!(res.body(), "2345");
+
+    let res = warp::test::request()
+        .header("range", "bytes=6-")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 206);
+    assert_eq!(res.body(), "6789");
+
+    let res = warp::test::request()
+        .header("range", "bytes=20-30")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 416);
+    assert_eq!(res.headers()["content-range"], "bytes */10");
+
+    let res = warp::test::request()
+        .header("range", "bytes=2-5")
+        .header("if-range", "\"v1\"")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "0123456789");
+}
+
+#[tokio::test]
+async fn bytes_cacheable() {
+    let _ = pretty_env_logger::try_init();
+
+    let modified = std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_600_000_000);
+    let data = warp::reply::bytes_cacheable(
+        &b"hello world"[..],
+        "text/plain",
+        Some("v1"),
+        Some(modified),
+    );
+
+    let res = warp::test::request().reply(&data).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "text/plain");
+    assert_eq!(res.headers()["content-length"], "11");
+    assert_eq!(res.headers()["accept-ranges"], "bytes");
+    assert_eq!(res.headers()["etag"], "\"v1\"");
+    assert_eq!(
+        res.headers()["last-modified"],
+        "Sun, 13 Sep 2020 12:26:40 GMT"
+    );
+    assert_eq!(res.body(), "hello world");
+
+    // if-none-match
+    let res = warp::test::request()
+        .header("if-none-match", "\"v0\", \"v1\"")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 304);
+    assert_eq!(res.headers()["etag"], "\"v1\"");
+    assert_eq!(res.body(), "");
+
+    // if-none-match takes precedence over if-modified-since
+    let res = warp::test::request()
+        .header("if-none-match", "\"v0\"")
+        .header("if-modified-since", "Sun, 13 Sep 2020 12:26:40 GMT")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 200);
+
+    let res = warp::test::request()
+        .header("if-modified-since", "Sun, 13 Sep 2020 12:26:40 GMT")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 304);
+
+    // range
+    let res = warp::test::request()
+        .header("range", "bytes=6-")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 206);
+    assert_eq!(res.headers()["content-range"], "bytes 6-10/11");
+    assert_eq!(res.headers()["content-length"], "5");
+    assert_eq!(res.body(), "world");
+
+    // if-range with a matching and stale etag
+    let res = warp::test::request()
+        .header("range", "bytes=0-4")
+        .header("if-range", "\"v1\"")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 206);
+    assert_eq!(res.body(), "hello");
+
+    let res = warp::test::request()
+        .header("range", "bytes=0-4")
+        .header("if-range", "\"v0\"")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "hello world");
+
+    let res = warp::test::request()
+        .header("range", "bytes=20-30")
+        .reply(&data)
+        .await;
+    assert_eq!(res.status(), 416);
+    assert_eq!(res.headers()["content-range"], "bytes */11");
+}
+
+#[tokio::test]
+async fn reply_methods() {
+    use warp::http::StatusCode;
+    use warp::Reply;
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        warp::reply::json(&"made")
+            .with_status(StatusCode::CREATED)
+            .with_header("x-foo", "bar")
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 201);
+    assert_eq!(res.headers()["x-foo"], "bar");
+    assert_eq!(res.headers()["content-type"], "application/json");
+    assert_eq!(res.body(), "\"made\"");
+}
+
+#[tokio::test]
+async fn with_cookie() {
+    use std::time::{Duration, SystemTime};
+    use warp::reply::{Cookie, SameSite};
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let reply = warp::reply::with_header("hi", "set-cookie", "a=1");
+        let session = Cookie::new("session", "38afes7a8")
+            .path("/")
+            .domain("example.com")
+            .secure(true)
+            .http_only(true)
+            .same_site(SameSite::Strict)
+            .max_age(Duration::from_secs(3600));
+        let reply = warp::reply::with_cookie(reply, session);
+        let note = Cookie::new("note", "hello; \"world\"")
+            .expires(SystemTime::UNIX_EPOCH + Duration::from_secs(784111777));
+        let reply = warp::reply::with_cookie(reply, note);
+        // Invalid names are skipped.
+        warp::reply::with_cookie(reply, Cookie::new("a=b", "c"))
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    let cookies = res
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        cookies,
+        [
+            "a=1",
+            "session=38afes7a8; Path=/; Domain=example.com; Max-Age=3600; Secure; HttpOnly; SameSite=Strict",
+            "note=hello%3B%20%22world%22; Expires=Sun, 06 Nov 1994 08:49:37 GMT",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn stream() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let chunks = vec![
+            Ok::<_, std::io::Error>(bytes::Bytes::from_static(b"one")),
+            Ok(bytes::Bytes::from_static(b"two")),
+        ];
+        warp::reply::with_header(
+            warp::reply::stream(futures_util::stream::iter(chunks)),
+            "content-type",
+            "application/octet-stream",
+        )
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "application/octet-stream");
+    assert!(!res.headers().contains_key("content-length"));
+    assert_eq!(res.body(), "onetwo");
+}
+
+#[tokio::test]
+async fn stream_error() {
+    use futures_util::StreamExt;
+    use warp::hyper::body::HttpBody;
+    use warp::Reply;
+
+    let _ = pretty_env_logger::try_init();
+
+    let chunks = vec![
+        Ok(bytes::Bytes::from_static(b"one")),
+        Err(std::io::Error::new(std::io::ErrorKind::Other, "boom")),
+        Ok(bytes::Bytes::from_static(b"never")),
+    ];
+    let res = warp::reply::stream(futures_util::stream::iter(chunks)).into_response();
+
+    let mut body = res.into_body();
+    assert_eq!(body.next().await.unwrap().unwrap(), "one");
+    assert!(body.data().await.unwrap().is_err());
+}
+
+#[tokio::test]
+async fn multipart() {
+    use warp::reply::BodyPart;
+    use warp::Reply;
+
+    let _ = pretty_env_logger::try_init();
+
+    let res = warp::reply::multipart(vec![
+        BodyPart::new("hello").header("content-type", "text/plain"),
+        BodyPart::new("world"),
+    ])
+    .subtype("byteranges")
+    .into_response();
+
+    let content_type = res.headers()["content-type"].to_str().unwrap().to_owned();
+    let boundary = content_type
+        .strip_prefix("multipart/byteranges; boundary=")
+        .expect("content-type");
+
+    let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let expected = format!(
+        "--{b}\r\ncontent-type: text/plain\r\n\r\nhello\r\n--{b}\r\n\r\nworld\r\n--{b}--\r\n",
+        b = boundary,
+    );
+    assert_eq!(body, expected);
+}
+
+#[tokio::test]
+async fn reply_take() {
+    use std::convert::Infallible;
+
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let chunks = futures_util::stream::repeat(Ok::<_, Infallible>("tick\n"));
+        warp::reply::stream(chunks)
+    });
+
+    let res = warp::test::request().reply_take(&route, 2).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), &["tick\n", "tick\n"]);
+}
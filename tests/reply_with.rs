@@ -62,3 +62,27 @@ async fn default_header() {
 
     assert_eq!(resp.headers()["foo"], "sean", "doesn't replace header");
 }
+
+#[tokio::test]
+async fn set_cookie_appends() {
+    let mut headers = HeaderMap::new();
+    headers.append("set-cookie", HeaderValue::from_static("c=3"));
+    headers.append("set-cookie", HeaderValue::from_static("d=4"));
+
+    let route = warp::any()
+        .map(warp::reply)
+        .with(warp::reply::with::header("set-cookie", "a=1"))
+        .with(warp::reply::with::header("set-cookie", "b=2; Path=/"))
+        .with(warp::reply::with::headers(headers))
+        .with(warp::reply::with::default_header("set-cookie", "a=default"))
+        .with(warp::reply::with::default_header("set-cookie", "e=5"));
+
+    let resp = warp::test::request().reply(&route).await;
+    let cookies = resp
+        .headers()
+        .get_all("set-cookie")
+        .iter()
+        .map(|value| value.to_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(cookies, ["a=1", "b=2; Path=/", "c=3", "d=4", "e=5"]);
+}
@@ -0,0 +1,378 @@
+#![deny(warnings)]
+use warp::Filter;
+
+#[tokio::test]
+async fn alt_svc() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("own")
+        .map(|| warp::reply::with_header("own", "alt-svc", "clear"))
+        .or(warp::path::end().map(warp::reply));
+    let (addr, server) = warp::serve(routes)
+        .alt_svc("h3=\":443\"; ma=86400")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let client = warp::hyper::Client::new();
+
+    let uri = format!("http://{}/", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.headers()["alt-svc"], "h3=\":443\"; ma=86400");
+
+    let uri = format!("http://{}/own", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.headers()["alt-svc"], "clear");
+
+    let uri = format!("http://{}/missing", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["alt-svc"], "h3=\":443\"; ma=86400");
+}
+
+#[tokio::test]
+async fn bind_all() {
+    let _ = pretty_env_logger::try_init();
+
+    // Reserve two distinct ephemeral ports, and free them for the server.
+    let listeners = (0..2)
+        .map(|_| std::net::TcpListener::bind("127.0.0.1:0").unwrap())
+        .collect::<Vec<_>>();
+    let addrs = listeners
+        .iter()
+        .map(|listener| listener.local_addr().unwrap())
+        .collect::<Vec<_>>();
+    drop(listeners);
+
+    let routes = warp::any().map(|| "both");
+    tokio::spawn(warp::serve(routes).bind_all(addrs.clone()));
+
+    let client = warp::hyper::Client::new();
+    for addr in addrs {
+        let uri = format!("http://{}/", addr).parse().unwrap();
+        let res = client.get(uri).await.expect("request");
+        assert_eq!(res.status(), 200);
+        let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, "both");
+    }
+}
+
+#[tokio::test]
+async fn server_header() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("own")
+        .map(|| warp::reply::with_header("own", "server", "other/1.0"))
+        .or(warp::path::end().map(warp::reply));
+    let (addr, server) = warp::serve(routes)
+        .server_header("myapp/1.2.3")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let client = warp::hyper::Client::new();
+
+    let uri = format!("http://{}/", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.headers()["server"], "myapp/1.2.3");
+
+    let uri = format!("http://{}/own", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.headers()["server"], "other/1.0");
+
+    let uri = format!("http://{}/missing", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["server"], "myapp/1.2.3");
+}
+
+#[tokio::test]
+async fn shutdown_token() {
+    use futures_util::StreamExt;
+    use std::convert::Infallible;
+    use warp::shutdown::ShutdownToken;
+
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::shutdown_token().map(|token: ShutdownToken| {
+        let ticks = futures_util::stream::repeat(())
+            .then(|()| async {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                Ok::<_, Infallible>("tick\n")
+            })
+            .take_until(async move { token.triggered().await })
+            .chain(futures_util::stream::once(async { Ok("bye\n") }));
+        warp::reply::flushing_stream(ticks)
+    });
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let (addr, server) =
+        warp::serve(routes).bind_with_graceful_shutdown(([127, 0, 0, 1], 0), async {
+            rx.await.ok();
+        });
+    let server = tokio::spawn(server);
+
+    let uri = format!("http://{}/", addr).parse().unwrap();
+    let res = warp::hyper::Client::new().get(uri).await.expect("request");
+    let mut body = res.into_body();
+    assert_eq!(body.next().await.unwrap().unwrap(), "tick\n");
+
+    // The stream ends itself, letting the server finish shutting down.
+    tx.send(()).unwrap();
+    let mut rest = Vec::new();
+    while let Some(chunk) = body.next().await {
+        rest.extend_from_slice(&chunk.unwrap());
+    }
+    assert!(rest.ends_with(b"bye\n"), "{:?}", rest);
+    tokio::time::timeout(std::time::Duration::from_secs(5), server)
+        .await
+        .expect("server shut down")
+        .unwrap();
+}
+
+#[tokio::test]
+async fn shutdown_token_not_served() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::shutdown_token()
+        .map(|token: warp::shutdown::ShutdownToken| format!("{}", token.is_triggered()));
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.body(), "false");
+}
+
+#[tokio::test]
+async fn max_connections() {
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(|| "ok");
+    let (addr, server) = warp::serve(routes)
+        .max_connections(1)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    // Keep the only allowed connection open.
+    let mut first = tokio::net::TcpStream::connect(addr).await.unwrap();
+    first
+        .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+    let mut buf = [0; 1024];
+    let n = first.read(&mut buf).await.unwrap();
+    assert!(buf[..n].starts_with(b"HTTP/1.1 200 OK"));
+
+    let uri: warp::http::Uri = format!("http://{}/", addr).parse().unwrap();
+    let second = tokio::spawn(warp::hyper::Client::new().get(uri));
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!second.is_finished(), "second connection wasn't paused");
+
+    drop(first);
+    let res = tokio::time::timeout(Duration::from_secs(5), second)
+        .await
+        .expect("second connection accepted")
+        .unwrap()
+        .expect("request");
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn map_response() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("hello").map(|| warp::reply::with_header("hi", "x-remove", "me"));
+    let (addr, server) = warp::serve(routes)
+        .map_response(|mut res| {
+            res.headers_mut()
+                .insert("x-content-type-options", "nosniff".parse().unwrap());
+            res.headers_mut().remove("x-remove");
+            res
+        })
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let client = warp::hyper::Client::new();
+
+    let uri = format!("http://{}/hello", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+    assert!(!res.headers().contains_key("x-remove"));
+
+    let uri = format!("http://{}/missing", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["x-content-type-options"], "nosniff");
+}
+
+#[tokio::test]
+async fn not_found() {
+    use warp::http::StatusCode;
+
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::path("hello")
+        .map(|| "hi")
+        .or(warp::path("gone").map(|| StatusCode::NOT_FOUND));
+    let (addr, server) = warp::serve(routes)
+        .not_found(|method, path| {
+            let error = format!("no route for {} {}", method, path);
+            (StatusCode::NOT_FOUND, warp::reply::json(&error))
+        })
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let client = warp::hyper::Client::new();
+
+    let uri = format!("http://{}/missing", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 404);
+    assert_eq!(res.headers()["content-type"], "application/json");
+    let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+    assert_eq!(body, "\"no route for GET /missing\"");
+
+    // a 404 replied by a route is left alone
+    let uri = format!("http://{}/gone", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 404);
+    assert!(!res.headers().contains_key("content-type"));
+
+    let uri = format!("http://{}/hello", addr).parse().unwrap();
+    let res = client.get(uri).await.expect("request");
+    assert_eq!(res.status(), 200);
+}
+
+#[tokio::test]
+async fn max_concurrency() {
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::Semaphore;
+
+    let _ = pretty_env_logger::try_init();
+
+    // Requests to `/wait` are held until the gate is opened.
+    let gate = Arc::new(Semaphore::new(0));
+    let routes = {
+        let gate = gate.clone();
+        warp::path("wait")
+            .then(move || {
+                let gate = gate.clone();
+                async move {
+                    gate.acquire().await.unwrap().forget();
+                    "waited"
+                }
+            })
+            .or(warp::path("now").map(|| "now"))
+    };
+    let get = |addr: std::net::SocketAddr, path: &str| {
+        let uri = format!("http://{}/{}", addr, path).parse().unwrap();
+        warp::hyper::Client::new().get(uri)
+    };
+
+    // shedding
+    let (addr, server) = warp::serve(routes.clone())
+        .max_concurrency(1)
+        .shed_load(true)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let waiting = tokio::spawn(get(addr, "wait"));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let res = get(addr, "now").await.expect("request");
+    assert_eq!(res.status(), 503);
+    gate.add_permits(1);
+    assert_eq!(waiting.await.unwrap().expect("request").status(), 200);
+    let res = get(addr, "now").await.expect("request");
+    assert_eq!(res.status(), 200);
+
+    // queueing
+    let (addr, server) = warp::serve(routes)
+        .max_concurrency(1)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let waiting = tokio::spawn(get(addr, "wait"));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let mut queued = tokio::spawn(get(addr, "now"));
+    let timeout = tokio::time::timeout(Duration::from_millis(50), &mut queued).await;
+    assert!(timeout.is_err(), "second request should wait its turn");
+    gate.add_permits(1);
+    assert_eq!(waiting.await.unwrap().expect("request").status(), 200);
+    assert_eq!(queued.await.unwrap().expect("request").status(), 200);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn bind_unix() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let _ = pretty_env_logger::try_init();
+
+    let path = std::env::temp_dir().join(format!("warp-bind-unix-{}.sock", std::process::id()));
+    // A stale socket file, as left behind by a crashed server.
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let server =
+        warp::serve(warp::any().map(|| "unix")).bind_unix_with_graceful_shutdown(&path, async {
+            rx.await.ok();
+        });
+    let server = tokio::spawn(server);
+
+    let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut res = String::new();
+    stream.read_to_string(&mut res).await.unwrap();
+    assert!(res.starts_with("HTTP/1.1 200 OK\r\n"), "{:?}", res);
+    assert!(res.ends_with("\r\n\r\nunix"), "{:?}", res);
+
+    tx.send(()).unwrap();
+    server.await.unwrap();
+    assert!(!path.exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn bind_unix_in_use() {
+    let _ = pretty_env_logger::try_init();
+
+    let path = std::env::temp_dir().join(format!("warp-unix-in-use-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let live = std::os::unix::net::UnixListener::bind(&path).unwrap();
+
+    let bound = std::panic::catch_unwind(|| {
+        drop(warp::serve(warp::any().map(|| "unix")).bind_unix(&path));
+    });
+    assert!(bound.is_err(), "binding a live socket should fail");
+
+    // The live server keeps its socket.
+    assert!(path.exists());
+    std::os::unix::net::UnixStream::connect(&path).unwrap();
+    live.accept().unwrap();
+
+    drop(live);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+async fn http2_only() {
+    let _ = pretty_env_logger::try_init();
+
+    let routes = warp::any().map(|| "h2c");
+    let (addr, server) = warp::serve(routes)
+        .http2_only(true)
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let uri: warp::http::Uri = format!("http://{}/", addr).parse().unwrap();
+    let client = warp::hyper::Client::builder()
+        .http2_only(true)
+        .build_http::<warp::hyper::Body>();
+    let res = client.get(uri.clone()).await.expect("h2c request");
+    assert_eq!(res.version(), warp::http::Version::HTTP_2);
+
+    let client = warp::hyper::Client::new();
+    assert!(client.get(uri).await.is_err());
+}
@@ -0,0 +1,176 @@
+#![deny(warnings)]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use ring::hmac;
+use warp::Filter;
+
+fn sign(secret: &str, message: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    hmac::sign(&key, message.as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[tokio::test]
+async fn verify() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::sign::verify(
+        "secret",
+        "x-timestamp",
+        "x-signature",
+        Duration::from_secs(60),
+        32,
+    )
+    .and(warp::body::bytes())
+    .map(|body: bytes::Bytes| body.to_vec());
+
+    let ts = now().to_string();
+    let signature = sign(
+        "secret",
+        &format!("POST\n/orders?a=1\n{}\n{{\"tea\":1}}", ts),
+    );
+
+    let res = warp::test::request()
+        .method("POST")
+        .path("/orders?a=1")
+        .header("x-timestamp", &ts)
+        .header("x-signature", &signature)
+        .body("{\"tea\":1}")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "{\"tea\":1}");
+
+    // Tampered body
+    let res = warp::test::request()
+        .method("POST")
+        .path("/orders?a=1")
+        .header("x-timestamp", &ts)
+        .header("x-signature", &signature)
+        .body("{\"tea\":9}")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 401);
+
+    // Missing signature
+    let res = warp::test::request()
+        .method("POST")
+        .path("/orders?a=1")
+        .header("x-timestamp", &ts)
+        .body("{\"tea\":1}")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 401);
+
+    // Body past the limit
+    let body = format!("{{\"tea\":\"{}\"}}", "a".repeat(32));
+    let signature = sign("secret", &format!("POST\n/orders?a=1\n{}\n{}", ts, body));
+    let res = warp::test::request()
+        .method("POST")
+        .path("/orders?a=1")
+        .header("x-timestamp", &ts)
+        .header("x-signature", &signature)
+        .body(body)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 413);
+}
+
+#[tokio::test]
+async fn verify_replayed() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::sign::verify(
+        "secret",
+        "x-timestamp",
+        "x-signature",
+        Duration::from_secs(60),
+        32,
+    )
+    .map(warp::reply);
+
+    let ts = (now() - 120).to_string();
+    let signature = sign("secret", &format!("GET\n/\n{}\n", ts));
+
+    let res = warp::test::request()
+        .header("x-timestamp", &ts)
+        .header("x-signature", &signature)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn cookies() {
+    use warp::cookie::Key;
+    use warp::reply::Cookie;
+
+    let _ = pretty_env_logger::try_init();
+
+    let key = Key::new("a secret that is at least 32 bytes long");
+    let signed = key.sign(Cookie::new("user", "sean.m")).to_string();
+    let encrypted = key.encrypt(Cookie::new("user", "sean")).to_string();
+    assert!(signed.starts_with("user=sean.m."));
+    assert!(!encrypted.contains("sean"));
+
+    let route = warp::path("signed")
+        .and(warp::cookie::signed(key.clone(), "user"))
+        .or(warp::path("encrypted").and(warp::cookie::encrypted(key, "user")))
+        .unify()
+        .map(|user: String| user);
+
+    let res = warp::test::request()
+        .path("/signed")
+        .header("cookie", &signed)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "sean.m");
+
+    let res = warp::test::request()
+        .path("/encrypted")
+        .header("cookie", &encrypted)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "sean");
+
+    // changed values, or values signed for another cookie
+    let tampered = signed.replacen("sean", "root", 1);
+    let res = warp::test::request()
+        .path("/signed")
+        .header("cookie", &tampered)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 400);
+
+    let other = Key::generate()
+        .sign(Cookie::new("user", "sean.m"))
+        .to_string();
+    let res = warp::test::request()
+        .path("/signed")
+        .header("cookie", &other)
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 400);
+
+    let res = warp::test::request()
+        .path("/encrypted")
+        .header("cookie", &encrypted.replacen("user=", "user=00", 1))
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 400);
+
+    let res = warp::test::request().path("/signed").reply(&route).await;
+    assert_eq!(res.status(), 400);
+}
@@ -0,0 +1,49 @@
+#![deny(warnings)]
+use std::convert::Infallible;
+use std::time::Duration;
+
+use futures_util::stream;
+use warp::sse::Event;
+use warp::Filter;
+
+#[tokio::test]
+async fn retry() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let events = stream::iter(vec![
+            Ok::<_, Infallible>(Event::default().data("one")),
+            Ok(Event::default().data("two")),
+        ]);
+        warp::sse::reply(events).retry(Duration::from_secs(5))
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["content-type"], "text/event-stream");
+    assert_eq!(res.body(), "retry:5000\n\ndata:one\n\ndata:two\n\n");
+}
+
+#[tokio::test]
+async fn header() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::any().map(|| {
+        let events = stream::iter(vec![Ok::<_, Infallible>(Event::default().data("hi"))]);
+        warp::sse::reply(events)
+            .header("cache-control", "no-store")
+            .header("x-accel-buffering", "no")
+            .header("invalid name", "skipped")
+    });
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.headers()["content-type"], "text/event-stream");
+    assert_eq!(
+        res.headers().get_all("cache-control").iter().count(),
+        1,
+        "default cache-control is replaced"
+    );
+    assert_eq!(res.headers()["cache-control"], "no-store");
+    assert_eq!(res.headers()["x-accel-buffering"], "no");
+    assert_eq!(res.body(), "data:hi\n\n");
+}
@@ -0,0 +1,28 @@
+#![deny(warnings)]
+use std::time::Duration;
+
+use warp::Filter;
+
+#[tokio::test]
+async fn timeout() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::path::param::<u64>()
+        .then(|millis: u64| async move {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            "done"
+        })
+        .with(warp::timeout(Duration::from_millis(50)));
+
+    let res = warp::test::request().path("/1").reply(&route).await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.body(), "done");
+
+    let res = warp::test::request().path("/5000").reply(&route).await;
+    assert_eq!(res.status(), 504);
+    assert_eq!(res.body(), "Request handling timed out");
+
+    // rejections of the wrapped filter pass through
+    let res = warp::test::request().path("/nope").reply(&route).await;
+    assert_eq!(res.status(), 404);
+}
@@ -0,0 +1,175 @@
+#![deny(warnings)]
+use std::convert::TryFrom;
+use std::sync::Arc;
+
+use tokio::net::TcpStream;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{ring, verify_tls12_signature, verify_tls13_signature};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{ClientConfig, DigitallySignedStruct, Error, SignatureScheme};
+use tokio_rustls::TlsConnector;
+use warp::filters::tls::ClientIdentity;
+use warp::Filter;
+
+#[tokio::test]
+async fn client_identity() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::filters::tls::client_identity()
+        .map(|id: ClientIdentity| format!("{} {:?} {:?}", id.subject(), id.uris(), id.emails()));
+    let (addr, server) = warp::serve(route)
+        .tls()
+        .cert_path("examples/tls/cert.pem")
+        .key_path("examples/tls/key.rsa")
+        .client_auth_optional_path("examples/tls/client_ca.pem")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let (status, body) = get(addr, true).await;
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        "C=US, O=Warp, OU=Tests, CN=client.test \
+         [\"spiffe://warp.test/client\"] [\"client@warp.test\"]"
+    );
+
+    let (status, _) = get(addr, false).await;
+    assert_eq!(status, 401);
+}
+
+#[tokio::test]
+async fn client_identity_without_tls() {
+    let route = warp::filters::tls::client_identity().map(|_: ClientIdentity| "");
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 401);
+}
+
+#[tokio::test]
+async fn peer_certificates() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::filters::tls::peer_certificates().map(|certs: Option<Vec<Vec<u8>>>| {
+        let client = pem(include_bytes!("../examples/tls/client.pem"));
+        match certs {
+            Some(certs) => {
+                (certs.len() == client.len() && certs[0] == client[0].as_ref()).to_string()
+            }
+            None => "none".to_owned(),
+        }
+    });
+    let (addr, server) = warp::serve(route)
+        .tls()
+        .cert_path("examples/tls/cert.pem")
+        .key_path("examples/tls/key.rsa")
+        .client_auth_optional_path("examples/tls/client_ca.pem")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    assert_eq!(get(addr, true).await, (200, "true".to_owned()));
+    assert_eq!(get(addr, false).await, (200, "none".to_owned()));
+}
+
+#[tokio::test]
+async fn remote_addr() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::addr::remote().map(|addr: Option<std::net::SocketAddr>| {
+        addr.map(|addr| addr.ip().to_string()).unwrap_or_default()
+    });
+    let (addr, server) = warp::serve(route)
+        .tls()
+        .cert_path("examples/tls/cert.pem")
+        .key_path("examples/tls/key.rsa")
+        .client_auth_optional_path("examples/tls/client_ca.pem")
+        .bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let (status, body) = get(addr, false).await;
+    assert_eq!(status, 200);
+    assert_eq!(body, "127.0.0.1");
+}
+
+async fn get(addr: std::net::SocketAddr, with_cert: bool) -> (u16, String) {
+    let builder = ClientConfig::builder()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AnyServer));
+    let config = if with_cert {
+        let certs = pem(include_bytes!("../examples/tls/client.pem"));
+        let key =
+            rustls_pemfile::private_key(&mut &include_bytes!("../examples/tls/client.key")[..])
+                .unwrap()
+                .unwrap();
+        builder.with_client_auth_cert(certs, key).unwrap()
+    } else {
+        builder.with_no_client_auth()
+    };
+
+    let tcp = TcpStream::connect(addr).await.unwrap();
+    let name = ServerName::try_from("localhost").unwrap();
+    let tls = TlsConnector::from(Arc::new(config))
+        .connect(name, tcp)
+        .await
+        .unwrap();
+
+    let (mut client, conn) = warp::hyper::client::conn::handshake(tls).await.unwrap();
+    tokio::spawn(conn);
+    let req = warp::http::Request::get("/")
+        .header("host", "localhost")
+        .body(warp::hyper::Body::empty())
+        .unwrap();
+    let res = client.send_request(req).await.unwrap();
+    let status = res.status().as_u16();
+    let body = warp::hyper::body::to_bytes(res.into_body()).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+fn pem(bytes: &[u8]) -> Vec<CertificateDer<'static>> {
+    rustls_pemfile::certs(&mut &bytes[..])
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+// The test server certificate's issuer isn't available, so accept it as is.
+#[derive(Debug)]
+struct AnyServer;
+
+impl ServerCertVerifier for AnyServer {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        let algs = ring::default_provider().signature_verification_algorithms;
+        verify_tls12_signature(message, cert, dss, &algs)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        let algs = ring::default_provider().signature_verification_algorithms;
+        verify_tls13_signature(message, cert, dss, &algs)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        ring::default_provider()
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
@@ -46,3 +46,46 @@ async fn uses_tracing() {
     let resp = req.reply(&ok);
     assert_eq!(resp.await.status(), 200);
 }
+
+#[tokio::test]
+async fn request_id() {
+    use std::sync::{Arc, Mutex};
+    use warp::trace::RequestId;
+
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let sink = logged.clone();
+    let route = warp::ext::get::<RequestId>()
+        .map(|id: RequestId| id.to_string())
+        .with(warp::log::custom(move |info| {
+            sink.lock()
+                .unwrap()
+                .push(info.request_id().map(ToOwned::to_owned));
+        }))
+        .with(warp::trace::request())
+        .with(warp::trace::request_id());
+
+    let res = warp::test::request()
+        .header("x-request-id", "abc-123")
+        .reply(&route)
+        .await;
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.headers()["x-request-id"], "abc-123");
+    assert_eq!(res.body(), "abc-123");
+
+    let res = warp::test::request()
+        .header("x-request-id", "not valid")
+        .reply(&route)
+        .await;
+    let id = res.headers()["x-request-id"].to_str().unwrap().to_owned();
+    assert_eq!(id.len(), 36);
+    assert_eq!(&id[14..15], "4");
+    assert_eq!(res.body(), id.as_str());
+
+    let res = warp::test::request().reply(&route).await;
+    let other = res.headers()["x-request-id"].to_str().unwrap();
+    assert_ne!(other, id);
+
+    let logged = logged.lock().unwrap();
+    assert_eq!(logged[0].as_deref(), Some("abc-123"));
+    assert_eq!(logged[1].as_deref(), Some(id.as_str()));
+}
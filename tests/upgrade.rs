@@ -0,0 +1,58 @@
+#![deny(warnings)]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use warp::http::StatusCode;
+use warp::hyper::Body;
+use warp::upgrade::OnUpgrade;
+use warp::Filter;
+
+#[tokio::test]
+async fn upgrade() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::upgrade().map(|on_upgrade: OnUpgrade| {
+        tokio::spawn(async move {
+            let upgraded = on_upgrade.await.expect("upgrade");
+            let (mut rd, mut wr) = tokio::io::split(upgraded);
+            tokio::io::copy(&mut rd, &mut wr).await.expect("echo");
+        });
+        warp::http::Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header("connection", "upgrade")
+            .header("upgrade", "echo")
+            .body(Body::empty())
+    });
+    let (addr, server) = warp::serve(route).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    let mut stream = TcpStream::connect(addr).await.expect("connect");
+    stream
+        .write_all(
+            b"GET / HTTP/1.1\r\nhost: localhost\r\nconnection: upgrade\r\nupgrade: echo\r\n\r\n",
+        )
+        .await
+        .expect("write request");
+
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        let mut byte = [0];
+        stream.read_exact(&mut byte).await.expect("read response");
+        head.push(byte[0]);
+    }
+    assert!(head.starts_with(b"HTTP/1.1 101 "));
+
+    stream.write_all(b"ping").await.expect("write");
+    let mut buf = [0; 4];
+    stream.read_exact(&mut buf).await.expect("read");
+    assert_eq!(&buf, b"ping");
+}
+
+#[tokio::test]
+async fn upgrade_missing() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::upgrade().map(|_: OnUpgrade| warp::reply());
+
+    let res = warp::test::request().reply(&route).await;
+    assert_eq!(res.status(), 400);
+}
@@ -39,6 +39,43 @@ async fn upgrade() {
     assert_eq!(resp.status(), 101);
 }
 
+#[tokio::test]
+async fn upgrade_or() {
+    let _ = pretty_env_logger::try_init();
+
+    let page = warp::any().map(|| "the page");
+    let route = warp::ws::upgrade_or(page)
+        .map(|upgrade: warp::ws::UpgradeOr<_>| upgrade.on_upgrade(|_| async {}));
+
+    let resp = warp::test::request().reply(&route).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body(), "the page");
+
+    let resp = warp::test::request()
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .header("sec-websocket-version", "13")
+        .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .reply(&route)
+        .await;
+    assert_eq!(resp.status(), 101);
+
+    // An invalid handshake doesn't fall back to the page.
+    let resp = warp::test::request()
+        .header("connection", "upgrade")
+        .header("upgrade", "websocket")
+        .reply(&route)
+        .await;
+    assert_eq!(resp.status(), 400);
+
+    let page = warp::path("page").map(|| "the page");
+    let route = warp::ws::upgrade_or(page)
+        .map(|upgrade: warp::ws::UpgradeOr<_>| upgrade.on_upgrade(|_| async {}));
+
+    let resp = warp::test::request().path("/nope").reply(&route).await;
+    assert_eq!(resp.status(), 404, "no handshake rejections leak");
+}
+
 #[tokio::test]
 async fn fail() {
     let _ = pretty_env_logger::try_init();
@@ -274,6 +311,56 @@ async fn ws_with_query() {
         .expect("handshake");
 }
 
+#[tokio::test]
+async fn protocols() {
+    let _ = pretty_env_logger::try_init();
+
+    let route = warp::ws().map(|ws: warp::ws::Ws| {
+        ws.protocols(&["chat", "graphql-ws"])
+            .require_protocol(true)
+            .on_upgrade(|mut websocket| async move {
+                let protocol = websocket.protocol().unwrap_or("none").to_owned();
+                websocket.send(Message::text(protocol)).await.unwrap();
+            })
+    });
+
+    let handshake = || {
+        warp::test::request()
+            .header("connection", "upgrade")
+            .header("upgrade", "websocket")
+            .header("sec-websocket-version", "13")
+            .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+    };
+
+    let resp = handshake()
+        .header("sec-websocket-protocol", "v2.chat, graphql-ws, chat")
+        .reply(&route)
+        .await;
+    assert_eq!(resp.status(), 101);
+    assert_eq!(resp.headers()["sec-websocket-protocol"], "graphql-ws");
+
+    let resp = handshake()
+        .header("sec-websocket-protocol", "v2.chat")
+        .reply(&route)
+        .await;
+    assert_eq!(resp.status(), 400);
+
+    // Without `require_protocol`, still upgrades without a protocol.
+    let optional =
+        warp::ws().map(|ws: warp::ws::Ws| ws.protocols(&["chat"]).on_upgrade(|_| async {}));
+    let resp = handshake().reply(&optional).await;
+    assert_eq!(resp.status(), 101);
+    assert!(!resp.headers().contains_key("sec-websocket-protocol"));
+
+    let mut client = warp::test::ws()
+        .header("sec-websocket-protocol", "chat")
+        .handshake(route)
+        .await
+        .expect("handshake");
+    let msg = client.recv().await.expect("recv");
+    assert_eq!(msg.to_str(), Ok("chat"));
+}
+
 // Websocket filter that echoes all messages back.
 fn ws_echo() -> impl Filter<Extract = (impl warp::Reply,), Error = warp::Rejection> + Copy {
     warp::ws().map(|ws: warp::ws::Ws| {